@@ -0,0 +1,21 @@
+ALTER TABLE receipt_items
+    ADD COLUMN anders_pay BOOLEAN NOT NULL DEFAULT 1;
+
+ALTER TABLE receipt_items
+    ADD COLUMN andreas_pay BOOLEAN NOT NULL DEFAULT 1;
+
+ALTER TABLE receipt_items
+    ADD COLUMN ac_pay BOOLEAN NOT NULL DEFAULT 1;
+
+UPDATE receipt_items
+SET anders_pay = anders_share > 0,
+    andreas_pay = andreas_share > 0,
+    ac_pay = ac_share > 0;
+
+ALTER TABLE receipt_items DROP COLUMN anders_share;
+ALTER TABLE receipt_items DROP COLUMN andreas_share;
+ALTER TABLE receipt_items DROP COLUMN ac_share;
+
+ALTER TABLE receipts DROP COLUMN anders_share;
+ALTER TABLE receipts DROP COLUMN andreas_share;
+ALTER TABLE receipts DROP COLUMN ac_share;
@@ -0,0 +1,28 @@
+-- Per receipt weights, multiplied with the per item weights when splitting
+ALTER TABLE receipts
+    ADD COLUMN anders_share INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE receipts
+    ADD COLUMN andreas_share INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE receipts
+    ADD COLUMN ac_share INTEGER NOT NULL DEFAULT 1;
+
+-- Per item weights replace the old boolean "pays" flags
+ALTER TABLE receipt_items
+    ADD COLUMN anders_share INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE receipt_items
+    ADD COLUMN andreas_share INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE receipt_items
+    ADD COLUMN ac_share INTEGER NOT NULL DEFAULT 1;
+
+UPDATE receipt_items
+SET anders_share = anders_pay,
+    andreas_share = andreas_pay,
+    ac_share = ac_pay;
+
+ALTER TABLE receipt_items DROP COLUMN anders_pay;
+ALTER TABLE receipt_items DROP COLUMN andreas_pay;
+ALTER TABLE receipt_items DROP COLUMN ac_pay;
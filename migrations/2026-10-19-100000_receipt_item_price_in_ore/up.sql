@@ -0,0 +1,22 @@
+-- Store prices as whole øre instead of floating point kroner.
+-- The table is rebuilt so the price column keeps its position.
+CREATE TABLE receipt_items_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    receipt_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    price INTEGER NOT NULL,
+    anders_share INTEGER NOT NULL DEFAULT 1,
+    andreas_share INTEGER NOT NULL DEFAULT 1,
+    ac_share INTEGER NOT NULL DEFAULT 1,
+
+    FOREIGN KEY (receipt_id)
+        REFERENCES receipts(id)
+        ON DELETE CASCADE
+);
+
+INSERT INTO receipt_items_new (id, receipt_id, name, price, anders_share, andreas_share, ac_share)
+SELECT id, receipt_id, name, CAST(ROUND(price * 100) AS INTEGER), anders_share, andreas_share, ac_share
+FROM receipt_items;
+
+DROP TABLE receipt_items;
+ALTER TABLE receipt_items_new RENAME TO receipt_items;
@@ -0,0 +1,3 @@
+-- 'equal' splits by the receipt shares, 'attendance' by who ate on the linked days
+ALTER TABLE receipts
+    ADD COLUMN split_mode TEXT NOT NULL DEFAULT 'equal' CHECK (split_mode IN ('equal', 'attendance'));
@@ -0,0 +1,10 @@
+-- Receipt scans waiting for, or done with, OCR. `result` holds the parsed receipt as JSON
+CREATE TABLE ocr_jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'running', 'done', 'failed')),
+    file_path TEXT NOT NULL,
+    result TEXT,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
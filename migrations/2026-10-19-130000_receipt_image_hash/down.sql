@@ -0,0 +1 @@
+ALTER TABLE receipts DROP COLUMN image_hash;
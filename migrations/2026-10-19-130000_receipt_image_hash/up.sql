@@ -0,0 +1,3 @@
+-- Perceptual hash of the scanned photo, used to spot the same receipt uploaded twice
+ALTER TABLE receipts
+    ADD COLUMN image_hash BIGINT;
@@ -0,0 +1 @@
+ALTER TABLE receipt_items DROP COLUMN kind;
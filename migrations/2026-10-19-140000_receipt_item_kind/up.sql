@@ -0,0 +1,7 @@
+-- Discounts and deposits are split like the product line above them
+ALTER TABLE receipt_items
+    ADD COLUMN kind TEXT NOT NULL DEFAULT 'product' CHECK (kind IN ('product', 'discount', 'deposit', 'deposit_return'));
+
+UPDATE receipt_items SET kind = 'discount' WHERE lower(name) LIKE '%rabatt%';
+UPDATE receipt_items SET kind = 'deposit_return' WHERE lower(name) LIKE '%panteretur%';
+UPDATE receipt_items SET kind = 'deposit' WHERE kind = 'product' AND lower(name) LIKE '%pant%';
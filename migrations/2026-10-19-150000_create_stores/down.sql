@@ -0,0 +1,4 @@
+ALTER TABLE receipts
+    DROP COLUMN store_id;
+
+DROP TABLE stores;
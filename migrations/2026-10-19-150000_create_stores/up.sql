@@ -0,0 +1,28 @@
+CREATE TABLE stores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- e.g. "Rema 1000", the store name is the branch, e.g. "Grünerløkka"
+    chain TEXT NOT NULL,
+    name TEXT NOT NULL,
+    address TEXT,
+    -- Printed on every receipt, so the same store is recognised on the next scan
+    org_number TEXT UNIQUE,
+    -- Aisle names in walking order, one per line
+    aisles TEXT NOT NULL DEFAULT ''
+);
+
+ALTER TABLE receipts
+    ADD COLUMN store_id INTEGER REFERENCES stores(id) ON DELETE SET NULL;
+
+-- One store per chain seen so far, older scans only knew the short chain names
+INSERT INTO stores (chain, name)
+SELECT DISTINCT
+    CASE store WHEN 'Rema' THEN 'Rema 1000' ELSE store END,
+    CASE store WHEN 'Rema' THEN 'Rema 1000' ELSE store END
+FROM receipts
+WHERE store != 'Unknown';
+
+UPDATE receipts
+SET store_id = (
+    SELECT id FROM stores
+    WHERE stores.chain = CASE receipts.store WHEN 'Rema' THEN 'Rema 1000' ELSE receipts.store END
+);
@@ -0,0 +1,5 @@
+ALTER TABLE receipt_items
+    DROP COLUMN category_id;
+
+DROP TABLE category_rules;
+DROP TABLE categories;
@@ -0,0 +1,109 @@
+CREATE TABLE categories (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL UNIQUE
+);
+
+-- Items with a word starting or ending with `keyword` get the category, so
+-- "løk" matches "rødløk". Manual rules hold the whole name of an item that was
+-- recategorised by hand and win over the rest.
+CREATE TABLE category_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    keyword TEXT NOT NULL,
+    category_id INTEGER NOT NULL REFERENCES categories(id) ON DELETE CASCADE,
+    manual BOOLEAN NOT NULL DEFAULT 0,
+
+    UNIQUE (keyword, manual)
+);
+
+ALTER TABLE receipt_items
+    ADD COLUMN category_id INTEGER REFERENCES categories(id) ON DELETE SET NULL;
+
+INSERT INTO categories (name) VALUES
+    ('Vegetables'),
+    ('Fruit'),
+    ('Meat'),
+    ('Fish'),
+    ('Dairy'),
+    ('Bakery'),
+    ('Dry goods'),
+    ('Snacks'),
+    ('Drinks'),
+    ('Frozen'),
+    ('Household');
+
+INSERT INTO category_rules (keyword, category_id)
+SELECT rule.keyword, categories.id
+FROM (
+    SELECT 'løk' AS keyword, 'Vegetables' AS category
+    UNION ALL SELECT 'tomat', 'Vegetables'
+    UNION ALL SELECT 'agurk', 'Vegetables'
+    UNION ALL SELECT 'paprika', 'Vegetables'
+    UNION ALL SELECT 'salat', 'Vegetables'
+    UNION ALL SELECT 'gulrot', 'Vegetables'
+    UNION ALL SELECT 'potet', 'Vegetables'
+    UNION ALL SELECT 'brokkoli', 'Vegetables'
+    UNION ALL SELECT 'avokado', 'Vegetables'
+    UNION ALL SELECT 'mais', 'Vegetables'
+    UNION ALL SELECT 'eple', 'Fruit'
+    UNION ALL SELECT 'banan', 'Fruit'
+    UNION ALL SELECT 'appelsin', 'Fruit'
+    UNION ALL SELECT 'druer', 'Fruit'
+    UNION ALL SELECT 'sitron', 'Fruit'
+    UNION ALL SELECT 'kjøttdeig', 'Meat'
+    UNION ALL SELECT 'kylling', 'Meat'
+    UNION ALL SELECT 'bacon', 'Meat'
+    UNION ALL SELECT 'pølse', 'Meat'
+    UNION ALL SELECT 'skinke', 'Meat'
+    UNION ALL SELECT 'biff', 'Meat'
+    UNION ALL SELECT 'laks', 'Fish'
+    UNION ALL SELECT 'torsk', 'Fish'
+    UNION ALL SELECT 'reker', 'Fish'
+    UNION ALL SELECT 'fisk', 'Fish'
+    UNION ALL SELECT 'melk', 'Dairy'
+    UNION ALL SELECT 'ost', 'Dairy'
+    UNION ALL SELECT 'yoghurt', 'Dairy'
+    UNION ALL SELECT 'smør', 'Dairy'
+    UNION ALL SELECT 'rømme', 'Dairy'
+    UNION ALL SELECT 'fløte', 'Dairy'
+    UNION ALL SELECT 'egg', 'Dairy'
+    UNION ALL SELECT 'brød', 'Bakery'
+    UNION ALL SELECT 'rundstykke', 'Bakery'
+    UNION ALL SELECT 'tortilla', 'Bakery'
+    UNION ALL SELECT 'lompe', 'Bakery'
+    UNION ALL SELECT 'ris', 'Dry goods'
+    UNION ALL SELECT 'pasta', 'Dry goods'
+    UNION ALL SELECT 'spaghetti', 'Dry goods'
+    UNION ALL SELECT 'hvetemel', 'Dry goods'
+    UNION ALL SELECT 'havregryn', 'Dry goods'
+    UNION ALL SELECT 'krydder', 'Dry goods'
+    UNION ALL SELECT 'chips', 'Snacks'
+    UNION ALL SELECT 'sjokolade', 'Snacks'
+    UNION ALL SELECT 'smågodt', 'Snacks'
+    UNION ALL SELECT 'kjeks', 'Snacks'
+    UNION ALL SELECT 'nøtter', 'Snacks'
+    UNION ALL SELECT 'pepsi', 'Drinks'
+    UNION ALL SELECT 'cola', 'Drinks'
+    UNION ALL SELECT 'brus', 'Drinks'
+    UNION ALL SELECT 'juice', 'Drinks'
+    UNION ALL SELECT 'kaffe', 'Drinks'
+    UNION ALL SELECT 'farris', 'Drinks'
+    UNION ALL SELECT 'pizza', 'Frozen'
+    UNION ALL SELECT 'frossen', 'Frozen'
+    UNION ALL SELECT 'bærepose', 'Household'
+    UNION ALL SELECT 'tørkerull', 'Household'
+    UNION ALL SELECT 'toalettpapir', 'Household'
+    UNION ALL SELECT 'oppvask', 'Household'
+    UNION ALL SELECT 'såpe', 'Household'
+) AS rule
+JOIN categories ON categories.name = rule.category;
+
+-- Best effort for saved items, SQLite only ignores the case of ASCII letters so
+-- e.g. 'KJØTTDEIG' is left for a manual category
+UPDATE receipt_items
+SET category_id = (
+    SELECT category_id FROM category_rules
+    WHERE ' ' || receipt_items.name || ' ' LIKE '% ' || category_rules.keyword || '%'
+        OR ' ' || receipt_items.name || ' ' LIKE '%' || category_rules.keyword || ' %'
+    ORDER BY length(category_rules.keyword) DESC
+    LIMIT 1
+);
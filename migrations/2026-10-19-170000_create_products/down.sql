@@ -0,0 +1,7 @@
+ALTER TABLE receipt_items
+    DROP COLUMN product_id;
+ALTER TABLE receipt_items
+    DROP COLUMN raw_name;
+
+DROP TABLE product_aliases;
+DROP TABLE products;
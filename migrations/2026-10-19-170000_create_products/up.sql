@@ -0,0 +1,26 @@
+CREATE TABLE products (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+
+-- Learned from renaming scanned items in the receipt form
+CREATE TABLE product_aliases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- The name as read by OCR, lowercase with single spaces
+    raw_name TEXT NOT NULL UNIQUE,
+    product_id INTEGER NOT NULL REFERENCES products(id) ON DELETE CASCADE
+);
+
+-- The name as read by OCR, only for scanned items
+ALTER TABLE receipt_items
+    ADD COLUMN raw_name TEXT;
+ALTER TABLE receipt_items
+    ADD COLUMN product_id INTEGER REFERENCES products(id) ON DELETE SET NULL;
+
+-- Start from the names already used for ingredients, so receipts and shopping
+-- lists use the same names
+INSERT OR IGNORE INTO products (name)
+SELECT DISTINCT trim(name) FROM ingredients WHERE trim(name) != '';
+
+UPDATE receipt_items
+SET product_id = (SELECT id FROM products WHERE products.name = trim(receipt_items.name));
@@ -0,0 +1,8 @@
+ALTER TABLE ingredients
+    ADD COLUMN name TEXT NOT NULL DEFAULT '';
+UPDATE ingredients
+SET name = (SELECT name FROM ingredient_catalog WHERE ingredient_catalog.id = ingredients.catalog_id);
+ALTER TABLE ingredients
+    DROP COLUMN catalog_id;
+
+DROP TABLE ingredient_catalog;
@@ -0,0 +1,55 @@
+CREATE TABLE ingredient_catalog (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL,
+    -- The name lowercase with single spaces, so "Løk" and "løk" are the same
+    key TEXT NOT NULL UNIQUE,
+    -- Other names for the same thing, one per line, e.g. "onion" for "Løk"
+    aliases TEXT NOT NULL DEFAULT '',
+    -- e.g. "stk", "g" or "dl"
+    unit TEXT,
+    category_id INTEGER REFERENCES categories(id) ON DELETE SET NULL
+);
+
+-- The key like `normalize_name`: tabs and line breaks become spaces, runs of
+-- spaces are collapsed and the name is lowercased. SQLite only lowercases ASCII
+-- letters, so the Norwegian ones are lowercased by hand. Other letters are left to
+-- `CatalogIngredient::normalize_keys`, which the server runs after migrating
+CREATE TEMP TABLE ingredient_keys AS
+SELECT id, trim(name) AS name, lower(replace(replace(replace(
+    trim(replace(replace(replace(replace(replace(
+        replace(replace(replace(name, char(9), ' '), char(10), ' '), char(13), ' '),
+        '  ', ' '), '  ', ' '), '  ', ' '), '  ', ' '), '  ', ' ')),
+    'Æ', 'æ'), 'Ø', 'ø'), 'Å', 'å')) AS key
+FROM ingredients;
+
+-- One entry per distinct name, spelled like its first use
+INSERT INTO ingredient_catalog (name, key)
+SELECT ingredient_keys.name, distinct_names.key
+FROM (
+    SELECT MIN(id) AS id, key
+    FROM ingredient_keys
+    GROUP BY key
+) AS distinct_names
+JOIN ingredient_keys ON ingredient_keys.id = distinct_names.id;
+
+UPDATE ingredient_catalog
+SET category_id = (
+    SELECT category_id FROM category_rules
+    WHERE NOT manual
+        AND (' ' || ingredient_catalog.key || ' ' LIKE '% ' || category_rules.keyword || '%'
+            OR ' ' || ingredient_catalog.key || ' ' LIKE '%' || category_rules.keyword || ' %')
+    ORDER BY length(category_rules.keyword) DESC
+    LIMIT 1
+);
+
+ALTER TABLE ingredients
+    ADD COLUMN catalog_id INTEGER REFERENCES ingredient_catalog(id);
+UPDATE ingredients
+SET catalog_id = (
+    SELECT ingredient_catalog.id FROM ingredient_catalog
+    JOIN ingredient_keys ON ingredient_keys.key = ingredient_catalog.key
+    WHERE ingredient_keys.id = ingredients.id
+);
+DROP TABLE ingredient_keys;
+ALTER TABLE ingredients
+    DROP COLUMN name;
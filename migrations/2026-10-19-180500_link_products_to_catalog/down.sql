@@ -0,0 +1 @@
+ALTER TABLE products DROP COLUMN catalog_id;
@@ -0,0 +1,5 @@
+-- The catalog entry a product is, so receipts use the same name as shopping lists.
+-- Existing products are linked by `Product::link_catalog` at startup, which
+-- matches names the same way as the app
+ALTER TABLE products
+    ADD COLUMN catalog_id INTEGER REFERENCES ingredient_catalog(id) ON DELETE SET NULL;
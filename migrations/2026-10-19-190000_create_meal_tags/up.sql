@@ -0,0 +1,7 @@
+CREATE TABLE meal_tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    meal_id INTEGER NOT NULL REFERENCES meals(id) ON DELETE CASCADE,
+    -- Lowercase with single spaces
+    name TEXT NOT NULL,
+    UNIQUE (meal_id, name)
+);
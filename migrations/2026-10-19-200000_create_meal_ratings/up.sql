@@ -0,0 +1,11 @@
+CREATE TABLE meal_ratings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- `Member::as_str`
+    member TEXT NOT NULL,
+    meal_id INTEGER NOT NULL REFERENCES meals(id) ON DELETE CASCADE,
+    day_id INTEGER NOT NULL REFERENCES days(id) ON DELETE CASCADE,
+    score INTEGER NOT NULL CHECK (score BETWEEN 1 AND 5),
+    comment TEXT,
+    -- One rating per member for each dinner
+    UNIQUE (member, day_id)
+);
@@ -0,0 +1,2 @@
+DROP TABLE member_restrictions;
+ALTER TABLE ingredient_catalog DROP COLUMN allergens;
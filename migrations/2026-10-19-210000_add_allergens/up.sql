@@ -0,0 +1,28 @@
+-- Comma separated and lowercase, like meal tags
+ALTER TABLE ingredient_catalog
+    ADD COLUMN allergens TEXT NOT NULL DEFAULT '';
+
+-- What a member can't eat, matched against the allergens of ingredients
+CREATE TABLE member_restrictions (
+    -- `Member::as_str`
+    member TEXT NOT NULL,
+    allergen TEXT NOT NULL,
+    PRIMARY KEY (member, allergen)
+);
+
+-- Start with the obvious ones
+UPDATE ingredient_catalog
+SET allergens = 'shellfish'
+WHERE key LIKE '%reke%'
+    OR key LIKE '%scampi%'
+    OR key LIKE '%krabbe%'
+    OR key LIKE '%hummer%'
+    OR key LIKE '%skjell%'
+    OR key LIKE '%kreps%';
+UPDATE ingredient_catalog
+SET allergens = 'fish'
+WHERE allergens = ''
+    AND (key LIKE '%laks%'
+        OR key LIKE '%torsk%'
+        OR key LIKE '%fisk%'
+        OR key LIKE '%makrell%');
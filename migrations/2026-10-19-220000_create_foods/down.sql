@@ -0,0 +1,7 @@
+ALTER TABLE meals
+    DROP COLUMN servings;
+ALTER TABLE ingredient_catalog
+    DROP COLUMN grams_per_unit;
+ALTER TABLE ingredient_catalog
+    DROP COLUMN food_id;
+DROP TABLE foods;
@@ -0,0 +1,23 @@
+-- Imported from a food composition table, e.g. Matvaretabellen
+CREATE TABLE foods (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- The id in the imported table, e.g. "06.530", so importing again updates
+    source_id TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    -- Per 100 g of the edible part
+    kcal REAL NOT NULL,
+    protein REAL NOT NULL,
+    fat REAL NOT NULL,
+    carbs REAL NOT NULL
+);
+
+ALTER TABLE ingredient_catalog
+    ADD COLUMN food_id INTEGER REFERENCES foods(id) ON DELETE SET NULL;
+-- What one `unit` weighs, e.g. 120 for "stk" of onion. Grams and litres are
+-- known without it
+ALTER TABLE ingredient_catalog
+    ADD COLUMN grams_per_unit REAL;
+
+-- How many the ingredients of a meal are for
+ALTER TABLE meals
+    ADD COLUMN servings INTEGER NOT NULL DEFAULT 3;
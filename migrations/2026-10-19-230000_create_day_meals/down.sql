@@ -0,0 +1,28 @@
+PRAGMA foreign_keys = OFF;
+BEGIN;
+
+-- Only one meal per day fits, the main course is kept
+CREATE TABLE new_days (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    date DATE NOT NULL UNIQUE,
+    meal_id INTEGER REFERENCES meals(id) ON DELETE SET NULL,
+    week INTEGER NOT NULL,
+    year INTEGER NOT NULL,
+    anders_attend BOOLEAN NOT NULL DEFAULT 1,
+    ac_attend BOOLEAN NOT NULL DEFAULT 1,
+    andreas_attend BOOLEAN NOT NULL DEFAULT 1
+);
+INSERT INTO new_days (id, date, meal_id, week, year, anders_attend, ac_attend, andreas_attend)
+SELECT id, date,
+    (SELECT meal_id FROM day_meals
+        WHERE day_id = days.id
+        ORDER BY course = 'main' DESC, rowid
+        LIMIT 1),
+    week, year, anders_attend, ac_attend, andreas_attend
+FROM days;
+DROP TABLE days;
+ALTER TABLE new_days RENAME TO days;
+DROP TABLE day_meals;
+
+COMMIT;
+PRAGMA foreign_keys = ON;
@@ -0,0 +1,33 @@
+PRAGMA foreign_keys = OFF;
+BEGIN;
+
+-- The dishes of a day, so a dinner can have sides and dessert
+CREATE TABLE day_meals (
+    day_id INTEGER NOT NULL REFERENCES days(id) ON DELETE CASCADE,
+    meal_id INTEGER NOT NULL REFERENCES meals(id) ON DELETE CASCADE,
+    -- `Course::as_str`
+    course TEXT NOT NULL DEFAULT 'main',
+    PRIMARY KEY (day_id, meal_id)
+);
+CREATE INDEX day_meals_meal_id ON day_meals (meal_id);
+
+INSERT INTO day_meals (day_id, meal_id)
+SELECT id, meal_id FROM days WHERE meal_id IS NOT NULL;
+
+-- SQLite can't drop a column with a foreign key, so the table is rebuilt
+CREATE TABLE new_days (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    date DATE NOT NULL UNIQUE,
+    week INTEGER NOT NULL,
+    year INTEGER NOT NULL,
+    anders_attend BOOLEAN NOT NULL DEFAULT 1,
+    ac_attend BOOLEAN NOT NULL DEFAULT 1,
+    andreas_attend BOOLEAN NOT NULL DEFAULT 1
+);
+INSERT INTO new_days (id, date, week, year, anders_attend, ac_attend, andreas_attend)
+SELECT id, date, week, year, anders_attend, ac_attend, andreas_attend FROM days;
+DROP TABLE days;
+ALTER TABLE new_days RENAME TO days;
+
+COMMIT;
+PRAGMA foreign_keys = ON;
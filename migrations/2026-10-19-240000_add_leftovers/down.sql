@@ -0,0 +1 @@
+ALTER TABLE days DROP COLUMN leftovers_from_day_id;
@@ -0,0 +1,3 @@
+-- A dinner of leftovers from the day it was cooked, which buys for it
+ALTER TABLE days
+    ADD COLUMN leftovers_from_day_id INTEGER REFERENCES days(id) ON DELETE SET NULL;
@@ -0,0 +1,7 @@
+-- Remote meal images that could not be downloaded, so they aren't tried again on
+-- every start
+CREATE TABLE image_download_failures (
+    url TEXT PRIMARY KEY NOT NULL,
+    error TEXT NOT NULL,
+    failed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -21,25 +21,45 @@ def get_image(path: str):
     new_w, new_h = int(w * scale), int(h * scale)
     img_resized = img.resize((new_w, new_h), Image.LANCZOS)
     
-    return np.array(img_resized)
+    return np.array(img_resized), scale
 
 
 
+def to_line(words, scale: float):
+    """The words left to right, the lowest confidence and the box around them in
+    the coordinates of the uploaded image."""
+    words.sort(key=lambda w: w[0][0])  # left to right
+    boxes = np.array([w[0] for w in words])
+    box = [
+        int(boxes[:, 0].min() / scale),
+        int(boxes[:, 1].min() / scale),
+        int(boxes[:, 2].max() / scale),
+        int(boxes[:, 3].max() / scale),
+    ]
+    return {
+        "words": [w[1] for w in words],
+        "confidence": float(min(w[2] for w in words)),
+        "box": box,
+    }
+
+
 def main():
     if len(sys.argv) != 2:
         return json.dumps({"result": False, "error": "Usage: ocr.py <image_path>"})
 
     try:
         image_path = sys.argv[1]
-        image = get_image(image_path)
+        image, scale = get_image(image_path)
 
         ocr = PaddleOCR(
             lang="en",
             text_detection_model_name="PP-OCRv5_mobile_det",
             text_recognition_model_name="en_PP-OCRv5_mobile_rec",
             cpu_threads=cpu_count(),
-            use_doc_orientation_classify=True,
-            use_doc_unwarping=True,
+            # Orientation and skew are already fixed before OCR, and unwarping
+            # would move the boxes away from the uploaded image
+            use_doc_orientation_classify=False,
+            use_doc_unwarping=False,
             use_textline_orientation=True,
         )
         result = ocr.predict(image)
@@ -47,7 +67,7 @@ def main():
         lines = []
 
         for r in result:
-            items = list(zip(r["rec_boxes"], r["rec_texts"]))
+            items = list(zip(r["rec_boxes"], r["rec_texts"], r["rec_scores"]))
 
             # Sort top to bottom
             items.sort(key=lambda x: x[0][1])
@@ -56,24 +76,22 @@ def main():
             current_y = None
             Y_THRESHOLD = 20  # tweak if needed
 
-            for box, text in items:
+            for box, text, score in items:
                 y = box[1]  # top y
 
                 if current_y is None or abs(y - current_y) <= Y_THRESHOLD:
-                    current_line.append((box[0], text))  # keep x for sorting
+                    current_line.append((box, text, score))
                     current_y = y if current_y is None else current_y
                 else:
                     # finish previous line
-                    current_line.sort(key=lambda x: x[0])  # left to right
-                    lines.append([t[1] for t in current_line])
+                    lines.append(to_line(current_line, scale))
 
-                    current_line = [(box[0], text)]
+                    current_line = [(box, text, score)]
                     current_y = y
 
             # flush last line
             if current_line:
-                current_line.sort(key=lambda x: x[0])
-                lines.append([t[1] for t in current_line])
+                lines.append(to_line(current_line, scale))
 
         return json.dumps({"result": True, "lines": lines})
 
@@ -0,0 +1,171 @@
+use leptos::prelude::*;
+
+use crate::models::category::{Category, CategoryRule, CategoryRuleForm, CategorySpending};
+use crate::models::receipt::ReceiptItem;
+
+#[server]
+pub async fn get_categories() -> Result<Vec<Category>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(Category::get_all(db), "Could not get categories")
+}
+
+#[server]
+pub async fn get_category_rules() -> Result<Vec<CategoryRule>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(CategoryRule::get_all(db), "Could not get category rules")
+}
+
+#[server]
+pub async fn insert_category_rule(
+    rule_form: CategoryRuleForm,
+) -> Result<CategoryRule, ServerFnError> {
+    use crate::api::ssr::*;
+    use crate::models::category::normalize_name;
+    let db = &mut get_db()?;
+    let rule_form = CategoryRuleForm {
+        keyword: normalize_name(&rule_form.keyword),
+        ..rule_form
+    };
+    server_err!(
+        rule_form.insert(db),
+        "Could not insert category rule {rule_form:?}"
+    )
+}
+
+#[server]
+pub async fn delete_category_rule(id: i32) -> Result<usize, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(
+        CategoryRule::delete(db, id),
+        "Could not delete category rule {id}"
+    )
+}
+
+/// Sets the category of a saved item and remembers it for items with the same name.
+#[server]
+pub async fn set_item_category(
+    item_id: i32,
+    category_id: Option<i32>,
+) -> Result<ReceiptItem, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(
+        db.transaction(|db| {
+            let item = ReceiptItem::set_category(db, item_id, category_id)?;
+            CategoryRule::remember(db, &item.name, category_id)?;
+            Ok::<_, Error>(item)
+        }),
+        "Could not set category {category_id:?} of item {item_id}"
+    )
+}
+
+#[server]
+pub async fn get_category_report() -> Result<Vec<CategorySpending>, ServerFnError> {
+    use crate::api::ssr::*;
+    use crate::models::category::category_report;
+    use crate::models::receipt::ReceiptWithItems;
+    let db = &mut get_db()?;
+    let receipts = server_err!(
+        ReceiptWithItems::get_all(db),
+        "Could not get receipts for category report"
+    )?;
+    Ok(category_report(&receipts))
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use crate::db::tests::TEST_POOL;
+    use crate::models::category::{categorize, category_report, Category, CategoryRule};
+    use crate::models::money::Money;
+    use crate::models::receipt::{
+        ItemKind, ReceiptForm, ReceiptItem, ReceiptItemForm, ReceiptWithItems, SplitMode,
+    };
+    use chrono::NaiveDate;
+    use diesel::Connection;
+
+    #[test]
+    fn test_category_report() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let categories = Category::get_all(db).unwrap();
+            let id = |name: &str| categories.iter().find(|c| c.name == name).unwrap().id;
+            let receipt = ReceiptForm {
+                store: String::from("Kiwi"),
+                datetime: NaiveDate::from_ymd_opt(2031, 2, 3)
+                    .unwrap()
+                    .and_hms_opt(12, 0, 0)
+                    .unwrap(),
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                split_mode: SplitMode::Equal,
+                image_hash: None,
+                store_id: None,
+            }
+            .insert(db)
+            .unwrap();
+            let mut items = vec![];
+            for (name, price, shares, kind) in [
+                ("Pepsi Max 1,5L", "30,00", [0, 1, 0], ItemKind::Product),
+                ("Pant", "3,00", [1, 1, 1], ItemKind::Deposit),
+                ("Smågodt", "10,00", [1, 1, 1], ItemKind::Product),
+                ("Rart vi ikke kjenner", "9,00", [1, 1, 1], ItemKind::Product),
+            ] {
+                let rules = CategoryRule::get_all(db).unwrap();
+                let item = ReceiptItemForm {
+                    receipt_id: receipt.id,
+                    name: String::from(name),
+                    price: price.parse().unwrap(),
+                    anders_share: shares[0],
+                    andreas_share: shares[1],
+                    ac_share: shares[2],
+                    kind,
+                    category_id: categorize(&rules, name),
+                    raw_name: None,
+                    product_id: None,
+                };
+                items.push(item.insert(db).unwrap());
+            }
+
+            // Picking a category by hand is remembered for the same name
+            ReceiptItem::set_category(db, items[2].id, Some(id("Household"))).unwrap();
+            CategoryRule::remember(db, "SMÅGODT", Some(id("Household"))).unwrap();
+            let rules = CategoryRule::get_all(db).unwrap();
+            assert_eq!(categorize(&rules, "Smågodt"), Some(id("Household")));
+            CategoryRule::remember(db, "Smågodt", None).unwrap();
+            let rules = CategoryRule::get_all(db).unwrap();
+            assert_eq!(categorize(&rules, "Smågodt"), Some(id("Snacks")));
+
+            let receipt = ReceiptWithItems::get(db, receipt.id).unwrap();
+            let report = category_report(&[receipt]);
+            let rows: Vec<_> = report
+                .iter()
+                .map(|r| {
+                    (
+                        r.month,
+                        r.category_id,
+                        r.total.ore(),
+                        r.member_totals.map(|m| m.ore()),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                rows,
+                vec![
+                    (2, Some(id("Drinks")), 3300, [0, 3300, 0]),
+                    (2, Some(id("Household")), 1000, [334, 333, 333]),
+                    (2, None, 900, [300, 300, 300]),
+                ]
+            );
+            assert_eq!(
+                report.iter().map(|r| r.total).sum::<Money>(),
+                Money::from_ore(5200)
+            );
+            Ok(())
+        });
+    }
+}
@@ -1,8 +1,8 @@
 use crate::models::{
     day::{Day, DayForm},
+    day_meal::DayMeal,
     days_ingredients::DayWithMealAndIngredients,
     meal::Meal,
-    receipt::ReceiptWithItems,
 };
 use leptos::prelude::*;
 
@@ -13,18 +13,31 @@ pub async fn get_day(id: i32) -> Result<Day, ServerFnError> {
     server_err!(Day::get(db, id), "Could not get day {id}")
 }
 
+/// Every day, newest first, with its dishes.
 #[server]
-pub async fn get_all_days_with_meals() -> Result<Vec<(Day, Option<Meal>)>, ServerFnError> {
+pub async fn get_all_days_with_meals() -> Result<Vec<(Day, Vec<Meal>)>, ServerFnError> {
     use crate::api::ssr::*;
+    use crate::schema::day_meals;
     let db = &mut get_db()?;
-    server_err!(
-        days::table
-            .left_join(meals::table)
-            .select((Day::as_select(), Option::<Meal>::as_select()))
-            .order_by(days::date.desc())
-            .load::<(Day, Option<Meal>)>(db),
-        "Could not get all days with weeks"
-    )
+    let days = server_err!(Day::get_all_ordered(db), "Could not get all days")?;
+    let dishes = server_err!(
+        DayMeal::belonging_to(&days)
+            .inner_join(meals::table)
+            .order_by(day_meals::meal_id)
+            .select((DayMeal::as_select(), Meal::as_select()))
+            .load::<(DayMeal, Meal)>(db),
+        "Could not get meals of days"
+    )?;
+    Ok(dishes
+        .grouped_by(&days)
+        .into_iter()
+        .zip(days)
+        .map(|(mut dishes, day)| {
+            // The course is stored as text, so sort by the enum's order
+            dishes.sort_by_key(|(dish, _)| dish.course);
+            (day, dishes.into_iter().map(|(_, meal)| meal).collect())
+        })
+        .collect())
 }
 #[cfg(feature = "ssr")]
 pub async fn get_days_for_meal(meal_id: i32) -> Result<Vec<Day>, ServerFnError> {
@@ -51,58 +64,76 @@ pub async fn update_attendance_for_day(
     )
 }
 
+/// Saves the day with its dishes, `day_id` of the dishes is ignored. The shopping
+/// list for the day is reset to the ingredients of the dishes. A day of leftovers
+/// has no dishes or shopping of its own.
 #[server]
-pub async fn upsert_day(day_form: DayForm) -> Result<DayWithMealAndIngredients, ServerFnError> {
-    use crate::api::days_ingredients::{delete_day_ingredient_for_day, insert_day_ingredient};
-    use crate::api::ingredient::get_ingredients_for_meal;
+pub async fn upsert_day(
+    day_form: DayForm,
+    meals: Vec<DayMeal>,
+) -> Result<DayWithMealAndIngredients, ServerFnError> {
     use crate::api::ssr::*;
-    use crate::models::days_ingredients::IngredientWithBought;
     let db = &mut get_db()?;
-    let day = server_err!(
-        day_form.upsert(db),
-        "Could not create day with {day_form:?}"
+    let meals = if day_form.leftovers_from_day_id.is_some() {
+        vec![]
+    } else {
+        meals
+    };
+    // Checked in the transaction of the save, so the days can't change in between
+    let saved = server_err!(
+        db.transaction(|db| -> Result<_, Error> {
+            if let Some(source_id) = day_form.leftovers_from_day_id {
+                let source = Day::get(db, source_id)?;
+                if source.date >= day_form.date || source.leftovers_from_day_id.is_some() {
+                    return Ok(Err(
+                        "Leftovers have to be from an earlier day the dinner was cooked",
+                    ));
+                }
+                // Others eating leftovers of this day would be left without a dinner
+                let existing = days::table
+                    .filter(days::date.eq(day_form.date))
+                    .select(Day::as_select())
+                    .first(db)
+                    .optional()?;
+                let cooked_for_others = match existing {
+                    Some(day) => Day::get_leftover_days(db, day.id)?.len(),
+                    None => 0,
+                };
+                if cooked_for_others > 0 {
+                    return Ok(Err(
+                        "Other days eat leftovers of this dinner, so it has to be cooked",
+                    ));
+                }
+            }
+            let day = day_form.upsert(db)?;
+            DayIngredient::delete_for_day(db, day.id)?;
+            for dish in DayMeal::set_for_day(db, day.id, &meals)? {
+                for ingredient in Ingredient::get_for_meal(db, dish.meal_id)? {
+                    DayIngredient {
+                        day_id: day.id,
+                        ingredient_id: ingredient.id,
+                        bought: false,
+                    }
+                    .insert(db)?;
+                }
+            }
+            DayWithMealAndIngredients::load(db, day).map(Ok)
+        }),
+        "Could not save day with {day_form:?} and {meals:?}"
     )?;
-    delete_day_ingredient_for_day(day.id).await?;
-    let mut meal = None;
-    if let Some(meal_id) = day_form.meal_id {
-        let mut ingredients: Vec<IngredientWithBought> = Vec::new();
-        for ingredient in get_ingredients_for_meal(db, meal_id)? {
-            let ingredient = insert_day_ingredient(DayIngredient {
-                day_id: day.id,
-                ingredient_id: ingredient.id,
-                bought: false,
-            })
-            .await?;
-            ingredients.push(IngredientWithBought {
-                day_id: day.id,
-                ingredient: server_err!(
-                    Ingredient::get(db, ingredient.ingredient_id),
-                    "Could not get ingredient {}",
-                    ingredient.ingredient_id
-                )?,
-                bought: ingredient.bought,
-            });
-        }
-        meal = Some((
-            server_err!(Meal::get(db, meal_id), "Could not get meal {meal_id}")?,
-            ingredients,
-        ));
-    }
-    let receipts = ReceiptWithItems::get_by_day(db, day.id)?;
-    Ok(DayWithMealAndIngredients {
-        day,
-        meal,
-        receipts,
-    })
+    saved.map_err(ServerFnError::new)
 }
 
 #[cfg(feature = "ssr")]
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::api::ssr::{DayIngredient, Ingredient};
     use crate::db::tests::TEST_POOL;
+    use crate::models::day_meal::Course;
+    use crate::models::ingredient::IngredientForm;
     use crate::{api::week::Week, models::meal::MealForm};
-    use chrono::{Datelike, Local};
+    use chrono::{Datelike, Local, NaiveDate};
     use diesel::Connection;
 
     #[test]
@@ -110,12 +141,17 @@ mod test {
         let day = Local::now();
         let db = &mut TEST_POOL.clone().get().unwrap();
         db.test_transaction(|db| -> Result<(), ()> {
-        assert!(matches!(DayForm {
+        let day = DayForm {
                 date: day.date_naive(),
-                meal_id: Some(1),
                 week: day.iso_week().week() as i32,
                 year: day.year(),
-            }.upsert(db), Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::ForeignKeyViolation, ref info)) if info.message() == "FOREIGN KEY constraint failed" ));
+                leftovers_from_day_id: None,
+            }.upsert(db).unwrap();
+        assert!(matches!(DayMeal::set_for_day(db, day.id, &[DayMeal {
+                day_id: -1,
+                meal_id: 99999,
+                course: Course::Main,
+            }]), Err(diesel::result::Error::DatabaseError(diesel::result::DatabaseErrorKind::ForeignKeyViolation, ref info)) if info.message() == "FOREIGN KEY constraint failed" ));
         Ok(())
         });
     }
@@ -127,9 +163,9 @@ mod test {
         db.test_transaction(|db| -> Result<(), ()> {
             let day = DayForm {
                 date: day.date_naive(),
-                meal_id: None,
                 week: day.iso_week().week() as i32,
                 year: day.year(),
+                leftovers_from_day_id: None,
             }
             .upsert(db)
             .unwrap();
@@ -153,32 +189,228 @@ mod test {
                 image: String::new(),
                 name: String::new(),
                 recipie_url: None,
+                servings: 3,
             }
             .insert(db)
             .unwrap();
             let day = DayForm {
                 date: day.date_naive(),
-                meal_id: Some(meal.id),
                 week: day.iso_week().week() as i32,
                 year: day.year(),
+                leftovers_from_day_id: None,
             }
             .upsert(db)
             .unwrap();
+            DayMeal::set_for_day(
+                db,
+                day.id,
+                &[DayMeal {
+                    day_id: -1,
+                    meal_id: meal.id,
+                    course: Course::Main,
+                }],
+            )
+            .unwrap();
             assert_eq!(vec![day.clone()], Day::get_for_meal(db, meal.id).unwrap());
             assert_eq!(0, Day::get_for_meal(db, 99).unwrap().len());
             Ok(())
         });
     }
     #[test]
+    fn test_day_meals() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let mut meal = |name: &str, ingredient: &str| {
+                let meal = MealForm {
+                    name: String::from(name),
+                    image: String::new(),
+                    recipie_url: None,
+                    servings: 3,
+                }
+                .insert(db)
+                .unwrap();
+                IngredientForm {
+                    name: String::from(ingredient),
+                    amount: 1,
+                    meal_id: meal.id,
+                }
+                .insert(db)
+                .unwrap();
+                meal
+            };
+            let taco = meal("Taco", "Tortilla");
+            let salad = meal("Salat", "Isbergsalat");
+            let ice_cream = meal("Is", "Vaniljeis");
+            let date = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap();
+            let day = DayForm {
+                date,
+                week: date.iso_week().week() as i32,
+                year: date.year(),
+                leftovers_from_day_id: None,
+            }
+            .upsert(db)
+            .unwrap();
+            let dish = |meal: &Meal, course| DayMeal {
+                day_id: -1,
+                meal_id: meal.id,
+                course,
+            };
+            let dishes = DayMeal::set_for_day(
+                db,
+                day.id,
+                &[
+                    dish(&ice_cream, Course::Dessert),
+                    dish(&salad, Course::Side),
+                    dish(&taco, Course::Main),
+                    // Only once per day
+                    dish(&taco, Course::Side),
+                ],
+            )
+            .unwrap();
+            assert_eq!(dishes.len(), 3);
+            assert!(dishes.iter().all(|d| d.day_id == day.id));
+            for dish in &dishes {
+                for ingredient in Ingredient::get_for_meal(db, dish.meal_id).unwrap() {
+                    DayIngredient {
+                        day_id: day.id,
+                        ingredient_id: ingredient.id,
+                        bought: false,
+                    }
+                    .insert(db)
+                    .unwrap();
+                }
+            }
+
+            let loaded = DayWithMealAndIngredients::load(db, day.clone()).unwrap();
+            assert_eq!(loaded.meal_names(), "Taco + Salat + Is");
+            assert_eq!(loaded.main_meal(), Some(&taco));
+            assert_eq!(loaded.meals[1].course, Course::Side);
+            assert_eq!(loaded.meals[2].ingredients[0].ingredient.name, "Vaniljeis");
+            assert_eq!(loaded.ingredients().count(), 3);
+            assert_eq!(Day::get_for_meal(db, salad.id).unwrap(), vec![day.clone()]);
+
+            // Saving again replaces the dishes
+            DayMeal::set_for_day(db, day.id, &[dish(&salad, Course::Main)]).unwrap();
+            assert_eq!(DayMeal::get_for_day(db, day.id).unwrap().len(), 1);
+            assert!(Day::get_for_meal(db, taco.id).unwrap().is_empty());
+
+            // Without a main course nothing is rated
+            DayMeal::set_for_day(db, day.id, &[dish(&salad, Course::Side)]).unwrap();
+            let loaded = DayWithMealAndIngredients::load(db, day.clone()).unwrap();
+            assert_eq!(loaded.meal_names(), "Salat");
+            assert_eq!(loaded.main_meal(), None);
+            Ok(())
+        });
+    }
+    #[test]
+    fn test_leftovers() {
+        use crate::models::days_ingredients::scale_amount;
+        use crate::models::ingredient_catalog::CatalogIngredient;
+        use crate::models::member::Member;
+        use crate::models::restriction::{Conflict, MemberRestriction};
+
+        assert_eq!(scale_amount(3, 3, 2), 5);
+        assert_eq!(scale_amount(1, 4, 1), 2);
+        assert_eq!(scale_amount(400, 4, 0), 400);
+        assert_eq!(scale_amount(2, 0, 3), 2);
+
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let taco = MealForm {
+                name: String::from("Taco"),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            }
+            .insert(db)
+            .unwrap();
+            IngredientForm {
+                name: String::from("Kjøttdeig"),
+                amount: 400,
+                meal_id: taco.id,
+            }
+            .insert(db)
+            .unwrap();
+            let day_form = |date: NaiveDate, leftovers_from_day_id| DayForm {
+                date,
+                week: date.iso_week().week() as i32,
+                year: date.year(),
+                leftovers_from_day_id,
+            };
+            let cooked = day_form(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(), None)
+                .upsert(db)
+                .unwrap();
+            DayMeal::set_for_day(
+                db,
+                cooked.id,
+                &[DayMeal {
+                    day_id: -1,
+                    meal_id: taco.id,
+                    course: Course::Main,
+                }],
+            )
+            .unwrap();
+            for ingredient in Ingredient::get_for_meal(db, taco.id).unwrap() {
+                DayIngredient {
+                    day_id: cooked.id,
+                    ingredient_id: ingredient.id,
+                    bought: false,
+                }
+                .insert(db)
+                .unwrap();
+            }
+            let leftovers = day_form(
+                NaiveDate::from_ymd_opt(2025, 1, 11).unwrap(),
+                Some(cooked.id),
+            )
+            .upsert(db)
+            .unwrap();
+            Day::update_attendance(db, leftovers.id, true, true, false).unwrap();
+            let leftovers = Day::get(db, leftovers.id).unwrap();
+            // Anders only eats the leftovers, and can't have meat
+            Day::update_attendance(db, cooked.id, false, true, true).unwrap();
+            let cooked = Day::get(db, cooked.id).unwrap();
+            let meat = CatalogIngredient::find(db, "Kjøttdeig").unwrap().unwrap();
+            CatalogIngredient {
+                allergens: String::from("meat"),
+                ..meat
+            }
+            .update(db)
+            .unwrap();
+            let restrictions = [MemberRestriction {
+                member: Member::Anders,
+                allergen: String::from("meat"),
+            }];
+            let conflict = Conflict {
+                member: Member::Anders,
+                allergen: String::from("meat"),
+                ingredient: String::from("Kjøttdeig"),
+            };
+
+            // Cooked for three, and two more the day after
+            let loaded = DayWithMealAndIngredients::load(db, cooked.clone()).unwrap();
+            assert_eq!(loaded.leftovers_on, vec![leftovers.clone()]);
+            assert_eq!(loaded.ingredients().next().unwrap().ingredient.amount, 667);
+            assert_eq!(loaded.conflicts(&restrictions), vec![conflict.clone()]);
+
+            let loaded = DayWithMealAndIngredients::load(db, leftovers).unwrap();
+            assert_eq!(loaded.leftovers_from, Some(cooked));
+            assert_eq!(loaded.meal_names(), "Taco");
+            assert_eq!(loaded.ingredients().count(), 0);
+            assert_eq!(loaded.conflicts(&restrictions), vec![conflict]);
+            Ok(())
+        });
+    }
+    #[test]
     fn test_get_day() {
         let day = Local::now();
         let db = &mut TEST_POOL.clone().get().unwrap();
         db.test_transaction(|db| -> Result<(), ()> {
             let day = DayForm {
                 date: day.date_naive(),
-                meal_id: None,
                 week: day.iso_week().week() as i32,
                 year: day.year(),
+                leftovers_from_day_id: None,
             }
             .upsert(db)
             .unwrap();
@@ -195,9 +427,9 @@ mod test {
             for day in dates {
                 DayForm {
                     date: day,
-                    meal_id: None,
                     week: day.iso_week().week() as i32,
                     year: day.year(),
+                    leftovers_from_day_id: None,
                 }
                 .upsert(db)
                 .unwrap();
@@ -209,7 +441,7 @@ mod test {
                 assert_eq!(day.date, dates[i]);
                 assert_eq!(day.week, (dates[i].iso_week().week()) as i32);
                 assert_eq!(day.year, dates[i].year());
-                assert_eq!(day.meal_id, None);
+                assert!(DayMeal::get_for_day(db, day.id).unwrap().is_empty());
                 assert!(day.anders_attend);
                 assert!(day.ac_attend);
                 assert!(day.andreas_attend);
@@ -218,9 +450,9 @@ mod test {
             for (i, day) in dates.iter().enumerate() {
                 DayForm {
                     date: *day,
-                    meal_id: None,
                     week: day.iso_week().week() as i32 + i as i32,
                     year: day.year() + i as i32,
+                    leftovers_from_day_id: None,
                 }
                 .upsert(db)
                 .unwrap();
@@ -230,7 +462,7 @@ mod test {
             assert_eq!(days.len(), 7);
             for (i, day) in days.iter().enumerate() {
                 assert_eq!(day.date, dates[i]);
-                assert_eq!(day.meal_id, None);
+                assert!(DayMeal::get_for_day(db, day.id).unwrap().is_empty());
                 assert_eq!(day.week, (dates[i].iso_week().week()) as i32 + i as i32);
                 assert_eq!(day.year, dates[i].year() + i as i32);
             }
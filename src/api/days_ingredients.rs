@@ -14,11 +14,13 @@ pub async fn update_day_ingredient(
 
     // Broadcast to all connected WebSocket clients; non-fatal if none are connected.
     if let Some(tx) = use_context::<crate::ws::server::BroadcastTx>() {
-        let _ = tx.send(crate::ws::IngredientUpdate {
-            day_id: result.day_id,
-            ingredient_id: result.ingredient_id,
-            bought: result.bought,
-        });
+        let _ = tx.send(crate::ws::WsMessage::IngredientUpdate(
+            crate::ws::IngredientUpdate {
+                day_id: result.day_id,
+                ingredient_id: result.ingredient_id,
+                bought: result.bought,
+            },
+        ));
     }
 
     Ok(result)
@@ -76,9 +78,9 @@ mod test {
         db.test_transaction(|db| -> Result<(), ()> {
             let day_id = DayForm {
                 date: day.date_naive(),
-                meal_id: None,
                 week: day.iso_week().week() as i32,
                 year: day.year(),
+                leftovers_from_day_id: None,
             }
             .upsert(db)
             .unwrap()
@@ -87,6 +89,7 @@ mod test {
                 name: String::new(),
                 image: String::new(),
                 recipie_url: None,
+                servings: 3,
             }
             .insert(db)
             .unwrap()
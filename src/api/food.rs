@@ -0,0 +1,140 @@
+use leptos::prelude::*;
+use leptos::server_fn::codec::{MultipartData, MultipartFormData};
+
+use crate::models::food::Food;
+
+/// What importing a food composition table did.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct FoodImport {
+    pub foods: usize,
+    /// Catalog ingredients linked to a food with the same name
+    pub linked: usize,
+}
+
+/// Imports a food composition table, see `parse_foods` for the formats. Foods
+/// imported before are updated, and catalog ingredients without a food are linked
+/// to one with the same name.
+#[server(input = MultipartFormData)]
+pub async fn import_foods(data: MultipartData) -> Result<FoodImport, ServerFnError> {
+    use crate::api::ssr::*;
+    use crate::models::food::{parse_foods, FoodForm};
+    use crate::ocr::normalize::max_upload_bytes;
+
+    let db = &mut get_db()?;
+    // Safe to unwrap
+    let mut data = data.into_inner().unwrap();
+    if let Ok(Some(mut field)) = data.next_field().await {
+        let mut bytes = Vec::new();
+        while let Ok(Some(chunk)) = field.chunk().await {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > max_upload_bytes() {
+                return Err(ServerFnError::new("The file is too large"));
+            }
+        }
+        let foods = parse_foods(&String::from_utf8_lossy(&bytes)).map_err(ServerFnError::new)?;
+        let foods = server_err!(
+            FoodForm::upsert_all(db, &foods),
+            "Could not import {} foods",
+            foods.len()
+        )?;
+        let linked = server_err!(Food::link_catalog(db), "Could not link foods")?;
+        return Ok(FoodImport { foods, linked });
+    }
+    Err(ServerFnError::new("No file provided"))
+}
+
+#[server]
+pub async fn search_foods(query: String) -> Result<Vec<Food>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(
+        Food::search(db, &query, 20),
+        "Could not search foods for {query}"
+    )
+}
+
+/// The foods catalog ingredients are linked to, to show their names.
+#[server]
+pub async fn get_linked_foods() -> Result<Vec<Food>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(Food::get_linked(db), "Could not get linked foods")
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use crate::db::tests::TEST_POOL;
+    use crate::models::food::{parse_foods, Food, FoodForm, MealNutrition};
+    use crate::models::ingredient::IngredientForm;
+    use crate::models::ingredient_catalog::CatalogIngredient;
+    use crate::models::meal::MealForm;
+    use diesel::Connection;
+
+    #[test]
+    fn test_import_foods() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal = MealForm {
+                name: String::from("Laks med ris"),
+                image: String::new(),
+                recipie_url: None,
+                servings: 2,
+            }
+            .insert(db)
+            .unwrap();
+            for (name, amount) in [("Testlaks", 400), ("Testris", 2), ("Testsitron", 1)] {
+                IngredientForm {
+                    name: String::from(name),
+                    amount,
+                    meal_id: meal.id,
+                }
+                .insert(db)
+                .unwrap();
+            }
+            let rice = CatalogIngredient::find(db, "Testris").unwrap().unwrap();
+            CatalogIngredient {
+                unit: Some(String::from("dl")),
+                ..rice
+            }
+            .update(db)
+            .unwrap();
+            let salmon = CatalogIngredient::find(db, "Testlaks").unwrap().unwrap();
+            CatalogIngredient {
+                unit: Some(String::from("g")),
+                ..salmon
+            }
+            .update(db)
+            .unwrap();
+
+            let csv = "Matvare ID;Matvare;Kilokalorier;Fett;Karbohydrat;Protein\n\
+                t.1;Testlaks, rå;200;13;0;20\n\
+                t.2;Testris, kokt;100;0;25;2\n\
+                t.3;Testris, kokt, med salt;100;0;25;2\n";
+            let foods = parse_foods(csv).unwrap();
+            assert_eq!(FoodForm::upsert_all(db, &foods).unwrap(), 3);
+            // Importing again updates instead of adding
+            let updated = FoodForm {
+                kcal: 120.0,
+                ..foods[1].clone()
+            };
+            FoodForm::upsert_all(db, &[updated]).unwrap();
+            assert_eq!(Food::search(db, "testris", 20).unwrap().len(), 2);
+            assert_eq!(Food::search(db, "testris salt", 20).unwrap().len(), 1);
+
+            // Linked by name, the shortest name wins
+            assert_eq!(Food::link_catalog(db).unwrap(), 2);
+            let rice = CatalogIngredient::find(db, "Testris").unwrap().unwrap();
+            let rice_food = Food::get(db, rice.food_id.unwrap()).unwrap();
+            assert_eq!(rice_food.name, "Testris, kokt");
+            assert_eq!(rice_food.kcal, 120.0);
+            assert_eq!(Food::get_linked(db).unwrap().len(), 2);
+
+            let nutrition = MealNutrition::for_meal(db, &meal).unwrap().unwrap();
+            assert_eq!(nutrition.total.kcal, 1040.0);
+            assert_eq!(nutrition.per_serving().protein, 42.0);
+            assert_eq!(nutrition.missing, ["Testsitron"]);
+            Ok(())
+        });
+    }
+}
@@ -78,6 +78,7 @@ mod test {
                 name: String::new(),
                 image: String::new(),
                 recipie_url: None,
+                servings: 3,
             }
             .insert(db)
             .unwrap()
@@ -86,6 +87,7 @@ mod test {
                 name: String::new(),
                 image: String::new(),
                 recipie_url: None,
+                servings: 3,
             }
             .insert(db)
             .unwrap()
@@ -0,0 +1,144 @@
+use leptos::prelude::*;
+
+use crate::models::ingredient_catalog::CatalogIngredient;
+
+#[server]
+pub async fn get_ingredient_catalog() -> Result<Vec<CatalogIngredient>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(
+        CatalogIngredient::get_all(db),
+        "Could not get ingredient catalog"
+    )
+}
+
+#[server]
+pub async fn update_catalog_ingredient(
+    entry: CatalogIngredient,
+) -> Result<CatalogIngredient, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(
+        entry.update(db),
+        "Could not update catalog ingredient {entry:?}"
+    )
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use crate::db::tests::TEST_POOL;
+    use crate::models::ingredient::{Ingredient, IngredientForm};
+    use crate::models::ingredient_catalog::CatalogIngredient;
+    use crate::models::meal::MealForm;
+    use diesel::Connection;
+
+    #[test]
+    fn test_shared_catalog() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal_id = MealForm {
+                name: String::from("Taco"),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            }
+            .insert(db)
+            .unwrap()
+            .id;
+            let ingredient = |name: &str| IngredientForm {
+                name: String::from(name),
+                amount: 1,
+                meal_id,
+            };
+
+            // Case and spacing don't make a new ingredient
+            let onion = ingredient("Rødløk").insert(db).unwrap();
+            let again = ingredient(" rødløk ").insert(db).unwrap();
+            assert_eq!(again.catalog_id, onion.catalog_id);
+            assert_eq!(again.name, "Rødløk");
+
+            // Aliases lead to the same entry
+            let mut entry = CatalogIngredient::find(db, "RØDLØK").unwrap().unwrap();
+            entry.aliases = String::from("red onion\n");
+            entry.unit = Some(String::from("stk"));
+            entry.update(db).unwrap();
+            let aliased = ingredient("Red  Onion").insert(db).unwrap();
+            assert_eq!(aliased.catalog_id, onion.catalog_id);
+
+            // Renaming the entry renames it in every meal
+            let entry = CatalogIngredient {
+                name: String::from("Løk, rød"),
+                ..entry
+            }
+            .update(db)
+            .unwrap();
+            assert_eq!(entry.key, "løk, rød");
+            assert!(Ingredient::get_for_meal(db, meal_id)
+                .unwrap()
+                .iter()
+                .all(|i| i.name == "Løk, rød"));
+
+            let other = ingredient("Tomat").insert(db).unwrap();
+            assert_ne!(other.catalog_id, onion.catalog_id);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_normalize_keys() {
+        use crate::schema::{ingredient_catalog, ingredients};
+        use diesel::prelude::*;
+
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal_id = MealForm {
+                name: String::from("Pasta"),
+                image: String::new(),
+                recipie_url: None,
+                servings: 2,
+            }
+            .insert(db)
+            .unwrap()
+            .id;
+            // Keyed like the migration, which only lowercases ASCII letters
+            let mut ingredient_ids = vec![];
+            for (name, key, allergens) in [
+                ("Crème fraîche", "crème fraîche", ""),
+                ("CRÈME FRAÎCHE", "crÈme fraÎche", "lactose"),
+            ] {
+                let catalog_id: i32 = diesel::insert_into(ingredient_catalog::table)
+                    .values((
+                        ingredient_catalog::name.eq(name),
+                        ingredient_catalog::key.eq(key),
+                        ingredient_catalog::allergens.eq(allergens),
+                    ))
+                    .returning(ingredient_catalog::id)
+                    .get_result(db)
+                    .unwrap();
+                let ingredient_id: i32 = diesel::insert_into(ingredients::table)
+                    .values((
+                        ingredients::amount.eq(1),
+                        ingredients::meal_id.eq(meal_id),
+                        ingredients::catalog_id.eq(catalog_id),
+                    ))
+                    .returning(ingredients::id)
+                    .get_result(db)
+                    .unwrap();
+                ingredient_ids.push(ingredient_id);
+            }
+
+            assert_eq!(CatalogIngredient::normalize_keys(db).unwrap(), 1);
+            let entry = CatalogIngredient::find(db, "crème fraîche")
+                .unwrap()
+                .unwrap();
+            assert_eq!(entry.name, "Crème fraîche");
+            assert_eq!(entry.allergens, "lactose");
+            for id in ingredient_ids {
+                assert_eq!(Ingredient::get(db, id).unwrap().catalog_id, entry.id);
+            }
+            assert_eq!(CatalogIngredient::normalize_keys(db).unwrap(), 0);
+            Ok(())
+        });
+    }
+}
@@ -1,13 +1,33 @@
+#[cfg(feature = "ssr")]
+use crate::models::day_meal::DayMeal;
+#[cfg(feature = "ssr")]
+use crate::models::food::MealNutrition;
+#[cfg(feature = "ssr")]
+use crate::models::meal::MealServing;
+#[cfg(feature = "ssr")]
+use crate::models::meal_rating::MealRating;
+#[cfg(feature = "ssr")]
+use crate::models::meal_tag::MealTag;
 use crate::models::ingredient::IngredientForm;
-use crate::models::meal::{Meal, MealForm, MealWithIngredients};
+use crate::models::meal::{Meal, MealForm, MealOverview, MealWithIngredients};
+use crate::models::search::MealHit;
 use leptos::prelude::*;
+use leptos::server_fn::codec::{MultipartData, MultipartFormData};
 
+/// Looks up an image for meals without one, and stores remote images locally so
+/// they don't rot. Never fails, so images can't stop a meal from being saved.
 #[cfg(feature = "ssr")]
-async fn get_image_url(image: String, name: String) -> Result<String, ServerFnError> {
+async fn get_image_url(image: String, name: String) -> String {
+    use crate::images::localize_image;
+    use crate::images::provider::{find_image, providers};
+    use crate::models::meal::is_local_image;
+    if is_local_image(&image) {
+        return image;
+    }
     if !utils::is_valid_url(&image) {
-        return utils::get_image_url(name).await;
+        return localize_image(find_image(&providers(), &name).await).await;
     }
-    Ok(image)
+    localize_image(image).await
 }
 
 #[server]
@@ -27,7 +47,30 @@ pub async fn get_meal(id: i32) -> Result<MealWithIngredients, ServerFnError> {
         Ingredient::get_for_meal(db, meal.id),
         "Could not get ingredients for meal_id {id}"
     )?;
-    Ok(MealWithIngredients { meal, ingredients })
+    let history = server_err!(
+        MealServing::get_for_meal(db, meal.id),
+        "Could not get history for meal_id {id}"
+    )?;
+    let tags = server_err!(
+        MealTag::get_for_meal(db, meal.id),
+        "Could not get tags for meal_id {id}"
+    )?;
+    let ratings = server_err!(
+        MealRating::get_for_meal(db, meal.id),
+        "Could not get ratings for meal_id {id}"
+    )?;
+    let nutrition = server_err!(
+        MealNutrition::for_meal(db, &meal),
+        "Could not get nutrition for meal_id {id}"
+    )?;
+    Ok(MealWithIngredients {
+        meal,
+        ingredients,
+        history,
+        tags,
+        ratings,
+        nutrition,
+    })
 }
 
 #[server]
@@ -41,6 +84,7 @@ pub async fn delete_meal(id: i32) -> Result<usize, ServerFnError> {
 pub async fn update_meal_with_ingredients(
     meal: Meal,
     ingredient_forms: Vec<IngredientForm>,
+    tags: Vec<String>,
 ) -> Result<MealWithIngredients, ServerFnError> {
     use crate::api::day::get_days_for_meal;
     use crate::api::days_ingredients::insert_day_ingredient;
@@ -48,7 +92,7 @@ pub async fn update_meal_with_ingredients(
     use crate::api::ssr::*;
     let db = &mut get_db()?;
     let mut meal = meal.clone();
-    meal.image = get_image_url(meal.image.clone(), meal.name.clone()).await?;
+    meal.image = get_image_url(meal.image.clone(), meal.name.clone()).await;
     server_err!(meal.update(db), "Could not update meal {meal:?}")?;
     // This is kinda hacky, but easy
     delete_ingredients(db, meal.id)?;
@@ -68,25 +112,70 @@ pub async fn update_meal_with_ingredients(
             .await?;
         }
     }
-    Ok(MealWithIngredients { meal, ingredients })
+    let history = server_err!(
+        MealServing::get_for_meal(db, meal.id),
+        "Could not get history for meal {}",
+        meal.id
+    )?;
+    let tags = server_err!(
+        MealTag::set_for_meal(db, meal.id, &tags),
+        "Could not set tags for meal {}",
+        meal.id
+    )?;
+    let ratings = server_err!(
+        MealRating::get_for_meal(db, meal.id),
+        "Could not get ratings for meal {}",
+        meal.id
+    )?;
+    let nutrition = server_err!(
+        MealNutrition::for_meal(db, &meal),
+        "Could not get nutrition for meal {}",
+        meal.id
+    )?;
+    Ok(MealWithIngredients {
+        meal,
+        ingredients,
+        history,
+        tags,
+        ratings,
+        nutrition,
+    })
 }
 #[server]
 pub async fn create_meal_with_ingredients(
     meal_form: MealForm,
     ingredient_forms: Vec<IngredientForm>,
+    tags: Vec<String>,
 ) -> Result<MealWithIngredients, ServerFnError> {
     use crate::api::ingredient::insert_ingredient;
     use crate::api::ssr::*;
     let db = &mut get_db()?;
     let mut meal_form = meal_form.clone();
-    meal_form.image = get_image_url(meal_form.image.clone(), meal_form.name.clone()).await?;
+    meal_form.image = get_image_url(meal_form.image.clone(), meal_form.name.clone()).await;
     let meal: Meal = server_err!(meal_form.insert(db), "Could not insert meal {meal_form:?}")?;
     let mut ingredients = vec![];
     for mut ingredient_form in ingredient_forms {
         ingredient_form.meal_id = meal.id;
         ingredients.push(insert_ingredient(db, ingredient_form)?);
     }
-    Ok(MealWithIngredients { meal, ingredients })
+    let tags = server_err!(
+        MealTag::set_for_meal(db, meal.id, &tags),
+        "Could not set tags for meal {}",
+        meal.id
+    )?;
+    let nutrition = server_err!(
+        MealNutrition::for_meal(db, &meal),
+        "Could not get nutrition for meal {}",
+        meal.id
+    )?;
+    Ok(MealWithIngredients {
+        meal,
+        ingredients,
+        history: vec![],
+        tags,
+        ratings: vec![],
+        nutrition,
+    })
 }
 
 #[server]
@@ -96,66 +185,160 @@ pub async fn get_all_meals_with_ingredients() -> Result<Vec<MealWithIngredients>
     let db = &mut get_db()?;
     let meals = get_meals_ordered().await?;
     let ingredients = get_ingredients(db)?;
+    let days = server_err!(
+        DayMeal::belonging_to(&meals)
+            .inner_join(days::table)
+            .select((DayMeal::as_select(), Day::as_select()))
+            .load::<(DayMeal, Day)>(db),
+        "Could not get days for meals"
+    )?;
+    let tags = server_err!(
+        MealTag::belonging_to(&meals).load::<MealTag>(db),
+        "Could not get tags for meals"
+    )?;
+    let ratings = server_err!(
+        MealRating::belonging_to(&meals)
+            .select(MealRating::as_select())
+            .load(db),
+        "Could not get ratings for meals"
+    )?;
+    let costs = server_err!(DayMeal::costs_by_dish(db), "Could not get dish costs")?;
+    let leftover_portions = server_err!(
+        Day::leftover_portions(db),
+        "Could not get leftover portions"
+    )?;
+    let mut nutrition = server_err!(
+        MealNutrition::for_meals(db, &meals),
+        "Could not get nutrition for meals"
+    )?;
     Ok(ingredients
         .grouped_by(&meals)
         .into_iter()
+        .zip(days.grouped_by(&meals))
+        .zip(tags.grouped_by(&meals))
+        .zip(ratings.grouped_by(&meals))
         .zip(meals)
-        .map(|(ingredients, meal)| MealWithIngredients { meal, ingredients })
+        .map(
+            |((((ingredients, days), tags), ratings), meal)| MealWithIngredients {
+                nutrition: nutrition.remove(&meal.id),
+                meal,
+                ingredients,
+                history: MealServing::from_days(days, &costs, &leftover_portions),
+                tags: tags.into_iter().map(|t| t.name).collect(),
+                ratings,
+            },
+        )
         .collect())
 }
 
-#[cfg(feature = "ssr")]
-mod utils {
-    use leptos::prelude::ServerFnError;
+/// Every meal with its tags and days, for the meal picker.
+#[server]
+pub async fn get_meal_overviews() -> Result<Vec<MealOverview>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    let meals = get_meals_ordered().await?;
+    let days = server_err!(
+        DayMeal::belonging_to(&meals)
+            .inner_join(days::table)
+            .select((DayMeal::as_select(), Day::as_select()))
+            .load::<(DayMeal, Day)>(db),
+        "Could not get days for meals"
+    )?;
+    let tags = server_err!(
+        MealTag::belonging_to(&meals).load::<MealTag>(db),
+        "Could not get tags for meals"
+    )?;
+    let ratings = server_err!(
+        MealRating::belonging_to(&meals)
+            .select(MealRating::as_select())
+            .load(db),
+        "Could not get ratings for meals"
+    )?;
+    let ingredients = server_err!(
+        Ingredient::belonging_to(&meals)
+            .inner_join(ingredient_catalog::table)
+            .select(Ingredient::as_select())
+            .load(db),
+        "Could not get ingredients for meals"
+    )?;
+    Ok(days
+        .grouped_by(&meals)
+        .into_iter()
+        .zip(tags.grouped_by(&meals))
+        .zip(ratings.grouped_by(&meals))
+        .zip(ingredients.grouped_by(&meals))
+        .zip(meals)
+        .map(|((((days, tags), ratings), ingredients), meal)| MealOverview {
+            meal,
+            tags: tags.into_iter().map(|t| t.name).collect(),
+            served: days.into_iter().map(|(_, day)| day.date).collect(),
+            ratings,
+            ingredients,
+        })
+        .collect())
+}
 
-    #[derive(serde::Deserialize, Debug)]
-    pub struct GoogleImageResult {
-        pub items: [ImageItem; 1],
-    }
+/// Meals matching the query by name, tag or ingredient, best match first. Allows
+/// typos and ignores case and diacritics.
+#[server]
+pub async fn search_meals(query: String) -> Result<Vec<MealHit>, ServerFnError> {
+    use crate::api::ssr::*;
+    use crate::models::search::{get_candidates, rank_meals};
+    let db = &mut get_db()?;
+    let candidates = server_err!(get_candidates(db), "Could not get meals to search")?;
+    Ok(rank_meals(&query, &candidates))
+}
 
-    #[derive(serde::Deserialize, Debug)]
-    pub struct ImageItem {
-        pub link: String,
+/// Stores an image uploaded for a meal, returning its URL.
+#[server(input = MultipartFormData)]
+pub async fn upload_meal_image(data: MultipartData) -> Result<String, ServerFnError> {
+    use crate::images::store_image;
+    use crate::ocr::normalize::max_upload_bytes;
+
+    // Safe to unwrap
+    let mut data = data.into_inner().unwrap();
+    if let Ok(Some(mut field)) = data.next_field().await {
+        let mut bytes = Vec::new();
+        while let Ok(Some(chunk)) = field.chunk().await {
+            bytes.extend_from_slice(&chunk);
+            if bytes.len() > max_upload_bytes() {
+                return Err(ServerFnError::new("The image is too large"));
+            }
+        }
+        return tokio::task::spawn_blocking(move || store_image(&bytes))
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?
+            .map_err(ServerFnError::new);
     }
+    Err(ServerFnError::new("No file provided"))
+}
 
+#[server]
+pub async fn get_meal_tags() -> Result<Vec<String>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(MealTag::get_names(db), "Could not get meal tags")
+}
+
+#[cfg(feature = "ssr")]
+mod utils {
     pub fn is_valid_url(url: &str) -> bool {
         use url::Url;
         Url::parse(url).is_ok()
     }
-
-    pub async fn get_image_url(query: String) -> Result<String, ServerFnError> {
-        let api_key = std::env::var("GOOGLE_KEY").map_err(|e| ServerFnError::new(e.to_string()))?;
-        let search_engine_id = std::env::var("GOOGLE_SEARCH_ENGINE_ID")
-            .map_err(|e| ServerFnError::new(e.to_string()))?;
-
-        let url = format!(
-        "https://customsearch.googleapis.com/customsearch/v1?key={}&cx={}&q={}&searchType=image&num=1",
-        api_key,
-        search_engine_id,
-        urlencoding::encode(&query)
-    );
-
-        let client = reqwest::Client::new();
-        let resp = client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ServerFnError::new(e.to_string()))?;
-
-        let data = resp
-            .json::<GoogleImageResult>()
-            .await
-            .map_err(|e| ServerFnError::new(e.to_string()))?;
-
-        Ok(data.items[0].link.clone())
-    }
 }
 
 #[cfg(feature = "ssr")]
 #[cfg(test)]
 mod test {
     use crate::db::tests::TEST_POOL;
-    use crate::models::meal::{Meal, MealForm};
+    use crate::models::day::{Day, DayForm};
+    use crate::models::day_meal::{Course, DayMeal};
+    use crate::models::meal::{Meal, MealForm, MealServing, MealWithIngredients};
+    use crate::models::meal_tag::MealTag;
+    use crate::models::money::Money;
+    use crate::models::receipt::{ItemKind, ReceiptDay, ReceiptForm, ReceiptItemForm, SplitMode};
+    use chrono::{Datelike, NaiveDate};
     use diesel::Connection;
 
     #[test]
@@ -166,6 +349,7 @@ mod test {
                 name: String::new(),
                 image: String::new(),
                 recipie_url: Some(String::from("https://example.com")),
+                servings: 3,
             }
             .insert(db)
             .unwrap();
@@ -173,6 +357,7 @@ mod test {
                 name: String::new(),
                 image: String::new(),
                 recipie_url: Some(String::from("https://example.com")),
+                servings: 3,
             }
             .insert(db)
             .unwrap();
@@ -192,4 +377,243 @@ mod test {
             Ok(())
         });
     }
+
+    #[test]
+    pub fn test_meal_cost_from_receipts() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal = MealForm {
+                name: String::from("Taco"),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            }
+            .insert(db)
+            .unwrap();
+            let mut day = |date: NaiveDate, meal_id: Option<i32>| {
+                let day = DayForm {
+                    date,
+                    week: date.iso_week().week() as i32,
+                    year: date.year(),
+                    leftovers_from_day_id: None,
+                }
+                .upsert(db)
+                .unwrap();
+                let dishes: Vec<DayMeal> = meal_id
+                    .map(|meal_id| DayMeal {
+                        day_id: day.id,
+                        meal_id,
+                        course: Course::Main,
+                    })
+                    .into_iter()
+                    .collect();
+                DayMeal::set_for_day(db, day.id, &dishes).unwrap();
+                day
+            };
+            let monday = day(NaiveDate::from_ymd_opt(2025, 1, 6).unwrap(), Some(meal.id));
+            let tuesday = day(NaiveDate::from_ymd_opt(2025, 1, 7).unwrap(), None);
+            let friday = day(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(), Some(meal.id));
+
+            let receipt = ReceiptForm {
+                store: String::from("Rema"),
+                datetime: monday.date.and_hms_opt(12, 0, 0).unwrap(),
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                split_mode: SplitMode::Equal,
+                image_hash: None,
+                store_id: None,
+            }
+            .insert(db)
+            .unwrap();
+            ReceiptItemForm {
+                receipt_id: receipt.id,
+                name: String::from("Tortilla"),
+                price: Money::from_ore(10001),
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                kind: ItemKind::Product,
+                category_id: None,
+                raw_name: None,
+                product_id: None,
+            }
+            .insert(db)
+            .unwrap();
+            for day in [&monday, &tuesday] {
+                ReceiptDay {
+                    day_id: day.id,
+                    receipt_id: receipt.id,
+                }
+                .upsert(db)
+                .unwrap();
+            }
+            Day::update_attendance(db, monday.id, true, false, true).unwrap();
+
+            let history = MealServing::get_for_meal(db, meal.id).unwrap();
+            assert_eq!(history.len(), 2);
+            assert_eq!(history[0].day.id, friday.id);
+            assert_eq!(history[0].cost, None);
+            assert_eq!(history[1].cost, Some(Money::from_ore(5001)));
+
+            let meal = MealWithIngredients {
+                meal,
+                ingredients: vec![],
+                history,
+                tags: vec![],
+                ratings: vec![],
+                nutrition: None,
+            };
+            assert_eq!(meal.cost_per_serving(), Some(Money::from_ore(2501)));
+
+            // Nobody ate on Friday
+            ReceiptDay {
+                day_id: friday.id,
+                receipt_id: receipt.id,
+            }
+            .upsert(db)
+            .unwrap();
+            Day::update_attendance(db, friday.id, false, false, false).unwrap();
+            // Anders has the leftovers on Wednesday
+            let wednesday = NaiveDate::from_ymd_opt(2025, 1, 8).unwrap();
+            let wednesday = DayForm {
+                date: wednesday,
+                week: wednesday.iso_week().week() as i32,
+                year: wednesday.year(),
+                leftovers_from_day_id: Some(monday.id),
+            }
+            .upsert(db)
+            .unwrap();
+            Day::update_attendance(db, wednesday.id, true, false, false).unwrap();
+            let history = MealServing::get_for_meal(db, meal.meal.id).unwrap();
+            assert_eq!(history[0].servings, 0);
+            assert_eq!(history[1].servings, 3);
+            let meal = MealWithIngredients { history, ..meal };
+            assert_eq!(meal.cost_per_serving(), Some(Money::from_ore(1111)));
+            Ok(())
+        });
+    }
+
+    #[test]
+    pub fn test_dish_costs() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal = |name: &str| MealForm {
+                name: String::from(name),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            };
+            let taco = meal("Taco").insert(db).unwrap();
+            let salad = meal("Salat").insert(db).unwrap();
+            let pizza = meal("Pizza").insert(db).unwrap();
+            let dish = |meal: &Meal, course| DayMeal {
+                day_id: -1,
+                meal_id: meal.id,
+                course,
+            };
+            let mut total = Money::ZERO;
+            for (date, dishes, price) in [
+                // A side doesn't count the day again
+                (
+                    NaiveDate::from_ymd_opt(2025, 2, 3).unwrap(),
+                    [dish(&taco, Course::Main), dish(&salad, Course::Side)],
+                    10001,
+                ),
+                // Two main courses share the cost
+                (
+                    NaiveDate::from_ymd_opt(2025, 2, 4).unwrap(),
+                    [dish(&taco, Course::Main), dish(&pizza, Course::Main)],
+                    5001,
+                ),
+            ] {
+                let day = DayForm {
+                    date,
+                    week: date.iso_week().week() as i32,
+                    year: date.year(),
+                    leftovers_from_day_id: None,
+                }
+                .upsert(db)
+                .unwrap();
+                DayMeal::set_for_day(db, day.id, &dishes).unwrap();
+                let receipt = ReceiptForm {
+                    store: String::from("Rema"),
+                    datetime: date.and_hms_opt(12, 0, 0).unwrap(),
+                    anders_share: 1,
+                    andreas_share: 1,
+                    ac_share: 1,
+                    split_mode: SplitMode::Equal,
+                    image_hash: None,
+                    store_id: None,
+                }
+                .insert(db)
+                .unwrap();
+                ReceiptItemForm {
+                    receipt_id: receipt.id,
+                    name: String::from("Mat"),
+                    price: Money::from_ore(price),
+                    anders_share: 1,
+                    andreas_share: 1,
+                    ac_share: 1,
+                    kind: ItemKind::Product,
+                    category_id: None,
+                    raw_name: None,
+                    product_id: None,
+                }
+                .insert(db)
+                .unwrap();
+                ReceiptDay {
+                    day_id: day.id,
+                    receipt_id: receipt.id,
+                }
+                .upsert(db)
+                .unwrap();
+                total += Money::from_ore(price);
+            }
+
+            let mut costs = |meal: &Meal| {
+                MealServing::get_for_meal(db, meal.id)
+                    .unwrap()
+                    .into_iter()
+                    .filter_map(|serving| serving.cost)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(costs(&salad), vec![]);
+            assert_eq!(costs(&taco).len(), 2);
+            assert_eq!(costs(&pizza).len(), 1);
+            let charged: Money = [taco, salad, pizza].iter().flat_map(costs).sum();
+            assert_eq!(charged, total);
+            Ok(())
+        });
+    }
+
+    #[test]
+    pub fn test_meal_tags() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal = |name: &str| MealForm {
+                name: String::from(name),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            };
+            let taco = meal("Taco").insert(db).unwrap();
+            let laks = meal("Laks").insert(db).unwrap();
+            let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+            let stored =
+                MealTag::set_for_meal(db, taco.id, &tags(&["Quick ", "kid  friendly", "quick"]))
+                    .unwrap();
+            assert_eq!(stored, ["kid friendly", "quick"]);
+            MealTag::set_for_meal(db, laks.id, &tags(&["fish", "quick"])).unwrap();
+            assert_eq!(MealTag::get_names(db).unwrap(), ["fish", "kid friendly", "quick"]);
+
+            // Setting tags replaces the old ones, and they go with the meal
+            MealTag::set_for_meal(db, taco.id, &tags(&["weekend"])).unwrap();
+            assert_eq!(MealTag::get_for_meal(db, taco.id).unwrap(), ["weekend"]);
+            Meal::delete(db, laks.id).unwrap();
+            assert_eq!(MealTag::get_names(db).unwrap(), ["weekend"]);
+            Ok(())
+        });
+    }
 }
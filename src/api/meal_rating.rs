@@ -0,0 +1,113 @@
+use crate::models::meal_rating::{MealRating, MealRatingForm};
+use leptos::prelude::*;
+
+#[server]
+pub async fn rate_meal(rating: MealRatingForm) -> Result<MealRating, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    if !(1..=5).contains(&rating.score) {
+        return Err(ServerFnError::new("A rating has to be from 1 to 5"));
+    }
+    let rating = MealRatingForm {
+        comment: rating
+            .comment
+            .map(|c| c.trim().to_owned())
+            .filter(|c| !c.is_empty()),
+        ..rating
+    };
+    server_err!(rating.upsert(db), "Could not save rating {rating:?}")
+}
+
+#[server]
+pub async fn delete_meal_rating(id: i32) -> Result<usize, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(MealRating::delete(db, id), "Could not delete rating {id}")
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use crate::db::tests::TEST_POOL;
+    use crate::models::day::DayForm;
+    use crate::models::day_meal::{Course, DayMeal};
+    use crate::models::meal::MealForm;
+    use crate::models::meal_rating::{average_score, favourites, MealRating, MealRatingForm};
+    use crate::models::member::Member;
+    use chrono::{Datelike, NaiveDate};
+    use diesel::Connection;
+
+    #[test]
+    fn test_meal_ratings() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal = MealForm {
+                name: String::from("Taco"),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            }
+            .insert(db)
+            .unwrap();
+            let mut day = |date: NaiveDate| {
+                let day = DayForm {
+                    date,
+                    week: date.iso_week().week() as i32,
+                    year: date.year(),
+                    leftovers_from_day_id: None,
+                }
+                .upsert(db)
+                .unwrap();
+                DayMeal::set_for_day(
+                    db,
+                    day.id,
+                    &[DayMeal {
+                        day_id: day.id,
+                        meal_id: meal.id,
+                        course: Course::Main,
+                    }],
+                )
+                .unwrap();
+                day
+            };
+            let friday = day(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap());
+            let saturday = day(NaiveDate::from_ymd_opt(2025, 1, 11).unwrap());
+            let rating = |member, day_id, score| MealRatingForm {
+                member,
+                meal_id: meal.id,
+                day_id,
+                score,
+                comment: None,
+            };
+
+            rating(Member::Anders, friday.id, 3).upsert(db).unwrap();
+            rating(Member::Ac, friday.id, 4).upsert(db).unwrap();
+            rating(Member::Ac, saturday.id, 5).upsert(db).unwrap();
+            // Rating the same dinner again changes the rating
+            let changed = MealRatingForm {
+                comment: Some(String::from("Mer chili")),
+                ..rating(Member::Anders, friday.id, 5)
+            }
+            .upsert(db)
+            .unwrap();
+            assert_eq!(changed.member, Member::Anders);
+            assert_eq!(changed.comment.as_deref(), Some("Mer chili"));
+
+            let ratings = MealRating::get_for_meal(db, meal.id).unwrap();
+            assert_eq!(ratings.len(), 3);
+            assert_eq!(average_score(&ratings), Some(14.0 / 3.0));
+            assert_eq!(favourites(&ratings), [Member::Anders, Member::Ac]);
+            assert_eq!(
+                MealRating::get_for_day(db, friday.id, meal.id)
+                    .unwrap()
+                    .len(),
+                2
+            );
+
+            MealRating::delete(db, changed.id).unwrap();
+            let ratings = MealRating::get_for_meal(db, meal.id).unwrap();
+            assert_eq!(favourites(&ratings), [Member::Ac]);
+            Ok(())
+        });
+    }
+}
@@ -1,10 +1,17 @@
 pub mod day;
 pub mod meal;
+pub mod meal_rating;
 pub mod ingredient;
+pub mod ingredient_catalog;
 pub mod days_ingredients;
 pub mod week;
 pub mod extra_items;
+pub mod food;
 pub mod receipt;
+pub mod store;
+pub mod category;
+pub mod product;
+pub mod restriction;
 
 #[macro_export]
 macro_rules! server_err {
@@ -19,7 +26,7 @@ pub mod ssr {
     pub use crate::models::{
         day::Day, days_ingredients::DayIngredient, ingredient::Ingredient, meal::Meal, receipt::Receipt
     };
-    pub use crate::schema::{days, days_ingredients, ingredients, meals, extra_items, receipts, receipt_items};
+    pub use crate::schema::{days, days_ingredients, ingredients, ingredient_catalog, meals, extra_items, receipts, receipt_items};
     pub use crate::server_err;
     pub use diesel::dsl::{delete, insert_into, update};
     pub use diesel::prelude::*;
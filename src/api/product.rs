@@ -0,0 +1,111 @@
+use leptos::prelude::*;
+
+/// Every product and catalog ingredient name, for suggestions when naming items.
+#[server]
+pub async fn get_product_names() -> Result<Vec<String>, ServerFnError> {
+    use crate::api::ssr::*;
+    use crate::models::ingredient_catalog::CatalogIngredient;
+    use crate::models::product::Product;
+    let db = &mut get_db()?;
+    // Products linked to the catalog go by the catalog's name
+    let mut names: Vec<String> = server_err!(Product::get_all(db), "Could not get products")?
+        .into_iter()
+        .filter(|p| p.catalog_id.is_none())
+        .map(|p| p.name)
+        .collect();
+    let catalog = server_err!(
+        CatalogIngredient::get_all(db),
+        "Could not get ingredient catalog"
+    )?;
+    names.extend(
+        catalog
+            .into_iter()
+            .map(|entry| entry.name)
+            .filter(|name| !name.is_empty()),
+    );
+    names.sort_by_key(|n| n.to_lowercase());
+    Ok(names)
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use crate::db::tests::TEST_POOL;
+    use crate::models::money::Money;
+    use crate::models::product::{apply_product_names, ProductAlias};
+    use crate::models::receipt::{ItemKind, ReceiptItemForm};
+    use diesel::Connection;
+
+    fn scanned(raw_name: &str) -> ReceiptItemForm {
+        ReceiptItemForm {
+            receipt_id: -1,
+            name: String::from(raw_name),
+            price: Money::from_ore(2490),
+            anders_share: 1,
+            andreas_share: 1,
+            ac_share: 1,
+            kind: ItemKind::Product,
+            category_id: None,
+            raw_name: Some(String::from(raw_name)),
+            product_id: None,
+        }
+    }
+
+    #[test]
+    fn test_learn_product_names() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let tomato = ProductAlias::learn(db, "TOMAT 4PK SOLMOD", "Tomat")
+                .unwrap()
+                .unwrap();
+            // OCR spacing doesn't matter, and the product is shared by every alias
+            let mut items = vec![scanned("TOMAT  4PK SOLMOD"), scanned("BÆREPOSE")];
+            apply_product_names(db, &mut items).unwrap();
+            assert_eq!(items[0].name, "Tomat");
+            assert_eq!(items[0].product_id, Some(tomato.id));
+            assert_eq!(items[1].name, "BÆREPOSE");
+            let cherry = ProductAlias::learn(db, "TOMAT CHERRY", "tomat").unwrap();
+            assert_eq!(cherry.map(|p| p.id), Some(tomato.id));
+
+            // Keeping the raw name forgets the rename
+            ProductAlias::learn(db, "TOMAT 4PK SOLMOD", "TOMAT 4PK SOLMOD").unwrap();
+            let mut items = vec![scanned("TOMAT 4PK SOLMOD")];
+            apply_product_names(db, &mut items).unwrap();
+            assert_eq!(items[0].name, "TOMAT 4PK SOLMOD");
+            assert_eq!(items[0].product_id, None);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_products_share_catalog_names() {
+        use crate::models::ingredient_catalog::CatalogIngredient;
+        use crate::models::product::Product;
+
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            // Bought before it was cooked with
+            let bought = Product::find_or_insert(db, "rødløk").unwrap();
+            assert_eq!(bought.catalog_id, None);
+            let mut entry = CatalogIngredient::resolve(db, "Rødløk").unwrap();
+            let bought = Product::find(db, "rødløk").unwrap().unwrap();
+            assert_eq!(bought.catalog_id, Some(entry.id));
+
+            // Scans use the name from the catalog
+            ProductAlias::learn(db, "RØDLØK 500G", "rødløk").unwrap();
+            let mut items = vec![scanned("RØDLØK 500G")];
+            apply_product_names(db, &mut items).unwrap();
+            assert_eq!(items[0].name, "Rødløk");
+            assert_eq!(items[0].product_id, Some(bought.id));
+
+            // New products find their entry by alias
+            entry.aliases = String::from("red onion");
+            entry.update(db).unwrap();
+            let product = Product::find_or_insert(db, "Red Onion").unwrap();
+            assert_eq!(product.catalog_id, Some(entry.id));
+            assert_eq!(product.shared_name(db).unwrap(), "Rødløk");
+            assert_eq!(Product::link_catalog(db).unwrap(), 0);
+            Ok(())
+        });
+    }
+}
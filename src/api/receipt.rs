@@ -1,227 +1,648 @@
-use leptos::logging::error;
 use leptos::prelude::*;
 use leptos::server_fn::codec::{MultipartData, MultipartFormData};
-use serde::Deserialize;
-use std::process::Command;
-
-use crate::models::receipt::{ReceiptDay, ReceiptForm, ReceiptItemForm, ReceiptWithItems};
-
-#[cfg(feature = "ssr")]
-#[derive(Debug, Deserialize)]
-struct ReceiptResult {
-    result: bool,
-    lines: Option<Vec<Vec<String>>>,
-    error: Option<Vec<String>>,
-}
 
+use crate::models::ocr_job::OcrJobUpdate;
 #[cfg(feature = "ssr")]
-fn ocr_image(image_path: &str, _: &mut super::ssr::DbConn) -> Result<Vec<Vec<String>>, String> {
-    use leptos::logging::error;
-    use serde_json;
-
-    let output = match Command::new("uv")
-        .arg("run")
-        .arg("--project")
-        .arg("ocr/")
-        .arg("ocr/main.py")
-        .arg(image_path)
-        .output()
-    {
-        Ok(o) => o,
-        Err(e) => {
-            error!("OCR command failed: {}", e);
-            return Err(String::from("Parsing OCR image failed"));
-        }
-    };
-
-    if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
-    }
-
-    let stdout = String::from_utf8(output.stdout).map_err(|e| {
-        error!("Got error while parsing string from utf8 {e}");
-        String::from("Could not parse OCR output")
-    })?;
-    let results: ReceiptResult = serde_json::from_str(&stdout).map_err(|e| {
-        error!("Got error while deserializing {e}");
-        String::from("Could not parse OCR output")
-    })?;
-
-    if !results.result {
-        error!("OCR command failed: {:?}", results.error);
-        return Err(String::from("Parsing OCR image failed"));
-    }
-
-    Ok(results.lines.unwrap_or_default())
-}
+use crate::models::receipt::ReceiptDay;
+use crate::models::receipt::{
+    ReceiptForm, ReceiptItemForm, ReceiptPage, ReceiptQuery, ReceiptWithItems,
+};
+use crate::models::store::StoreForm;
 
+/// Saves the uploaded receipt and queues it for scanning. Photos, HEIC included,
+/// are turned upright, scaled down and preprocessed before OCR, see
+/// `ocr::preprocess`. PDFs and digital JSON receipts are read directly. Progress
+/// is pushed over the websocket as `OcrJobUpdate`s, and the parsed receipt is
+/// fetched with `get_ocr_job` once the job is done. A `debug` field before the file
+/// returns the photo after every preprocessing stage with the queued job.
 #[server(input = MultipartFormData)]
-pub async fn scan_receipt(
-    data: MultipartData,
-) -> Result<
-    (
-        crate::models::receipt::ReceiptForm,
-        Vec<crate::models::receipt::ReceiptItemForm>,
-    ),
-    ServerFnError,
-> {
+pub async fn scan_receipt(data: MultipartData) -> Result<OcrJobUpdate, ServerFnError> {
     use crate::api::ssr::*;
+    use crate::models::ocr_job::OcrJobForm;
+    use crate::ocr::normalize::{max_upload_bytes, prepare_upload};
+    use crate::ocr::worker::OcrQueue;
     use tempfile::Builder;
     use tokio::fs::File;
     use tokio::io::AsyncWriteExt;
 
-    const STORE_NAMES: [&str; 3] = ["Rema", "Coop", "Kiwi"];
+    let queue = use_context::<OcrQueue>().ok_or(ServerFnError::new("Missing OCR queue"))?;
     let db = &mut get_db()?;
     // Safe to unwrap
     let mut data = data.into_inner().unwrap();
 
-    if let Ok(Some(mut field)) = data.next_field().await {
-        let extension = match field.content_type().unwrap().essence_str() {
-            "image/jpg" => "jpg",
-            "image/png" => "png",
-            "image/jpeg" => "jpeg",
-            "image/bmp" => "bmp",
-            "image/pdf" => "pdf",
-            t => return Err(ServerFnError::new(format!("Unsupported file type {t}"))),
+    let mut debug = false;
+    while let Ok(Some(mut field)) = data.next_field().await {
+        if field.name() == Some("debug") {
+            debug = field.text().await.is_ok_and(|value| value == "true");
+            continue;
+        }
+        let content_type = field
+            .content_type()
+            .map(|t| t.essence_str().to_owned())
+            .unwrap_or_default();
+        // Browsers don't always know HEIC, so fall back to the file name
+        let file_extension = field
+            .file_name()
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, extension)| extension.to_lowercase())
+            .unwrap_or_default();
+        let extension = match (content_type.as_str(), file_extension.as_str()) {
+            ("image/jpg", _) => "jpg",
+            ("image/png", _) => "png",
+            ("image/jpeg", _) => "jpeg",
+            ("image/bmp", _) => "bmp",
+            ("image/webp", _) => "webp",
+            ("image/heic" | "image/heif", _) => "heic",
+            ("application/pdf", _) => "pdf",
+            ("application/json", _) => "json",
+            ("" | "application/octet-stream", "heic" | "heif") => "heic",
+            (t, _) => return Err(ServerFnError::new(format!("Unsupported file type {t}"))),
         };
-        // log!("Got filetype {}", extension);
 
-        // 1. Create a temporary file
-        // NamedTempFile::new() creates it in the default temp dir
+        // The file is kept until the OCR job is done with it
         let temp_file = Builder::new()
             .suffix(&format!(".{extension}"))
             .tempfile()
             .map_err(|_| ServerFnError::new("Could not create temporary file"))?;
-        let path = temp_file.path().to_owned();
-        // log!("created tempfile at {:?}", path);
-
-        let (std_file, _path) = temp_file.keep().unwrap();
+        let (std_file, path) = temp_file
+            .keep()
+            .map_err(|_| ServerFnError::new("Could not keep temporary file"))?;
         let mut file = File::from_std(std_file);
 
+        let max_bytes = max_upload_bytes();
+        let mut size = 0;
         while let Ok(Some(chunk)) = field.chunk().await {
+            size += chunk.len();
+            if size > max_bytes {
+                drop(file);
+                let _ = tokio::fs::remove_file(&path).await;
+                return Err(ServerFnError::new(format!(
+                    "The file is larger than {} MB",
+                    max_bytes / 1024 / 1024
+                )));
+            }
             file.write_all(&chunk).await?;
         }
-
-        // Flush before OCR
         file.flush().await?;
+        drop(file);
 
-        // Return the path so you can use it elsewhere
-        let final_path = path.to_string_lossy().into_owned();
-
-        let lines = ocr_image(&final_path, db).map_err(ServerFnError::new)?;
-
-        let store = lines
-            .iter()
-            .flat_map(|row| row.iter()) // flatten Vec<Vec<String>> → iterator of &String
-            .find_map(|r| {
-                // stops at first match, returns Some
-                STORE_NAMES
-                    .iter()
-                    .find(|&&s| r.to_lowercase().contains(&s.to_lowercase())) // find keyword in STORE_NAMES
-                    .copied() // turn &&str → &str
-            })
-            .unwrap_or("Unknown");
-
-        let mut items = vec![];
-        for words in lines {
-            if let Some(price) = words.last() {
-                let price = price.replace(",", ".").replace(" ", "");
-                if !price.contains(".") {
-                    continue;
-                }
-                if let Ok(price) = price.parse::<f32>() {
-                    let words = &words[..words.len() - 1];
-                    // For kr per kg elements, ex: xxxkg x kr <price>
-                    // x and kr is either split into two words or combined
-                    // Inshallah no other items match this pattern
-                    if (words.contains(&String::from("x")) && words.contains(&String::from("kr")))
-                        || words.contains(&String::from("xkr"))
-                    {
-                        continue;
-                    }
-                    let name = words
-                        .iter()
-                        .filter(|word| !(word.ends_with('%') && word.len() <= 3))
-                        .cloned()
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    if name.contains("Totalt (") || name.contains("Sum ") {
-                        break;
-                    }
-
-                    if name.len() > 2 {
-                        items.push((name, price));
-                    }
-                }
-            }
-        }
+        let (path, stages) = prepare_upload(path, extension, debug)
+            .await
+            .map_err(ServerFnError::new)?;
 
-        let receipt = ReceiptForm {
-            store: store.to_owned(),
-            datetime: chrono::Local::now().naive_local(),
+        let job_form = OcrJobForm {
+            file_path: path.to_string_lossy().into_owned(),
         };
-
-        let mut receipt_items = vec![];
-        for (name, price) in items {
-            receipt_items.push(ReceiptItemForm {
-                receipt_id: -1, // This is a temporary hack as we dont have the id yet. It will be set in `create_receipt_with_items`
-                name,
-                price,
-                anders_pay: true,
-                andreas_pay: true,
-                ac_pay: true,
-            });
-        }
-        let _ = std::fs::remove_file(&final_path);
-        return Ok((receipt, receipt_items));
+        let job = server_err!(job_form.insert(db), "Could not queue OCR job {job_form:?}")?;
+        queue.push(job.id);
+        return Ok(OcrJobUpdate {
+            stages,
+            ..job.to_update()
+        });
     }
 
     Err(ServerFnError::new("No image provided"))
 }
 
+#[server]
+pub async fn get_ocr_job(id: i32) -> Result<OcrJobUpdate, ServerFnError> {
+    use crate::api::ssr::*;
+    use crate::models::ocr_job::OcrJob;
+    let db = &mut get_db()?;
+    server_err!(OcrJob::get(db, id), "Could not get OCR job {id}").map(|job| job.to_update())
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub enum CreateReceiptResult {
+    Created(ReceiptWithItems),
+    /// Nothing was saved because these receipts look like the same purchase.
+    Duplicate(Vec<ReceiptWithItems>),
+}
+
+/// Saves the receipt unless it looks like one that is already saved. Pass `force`
+/// to save it anyway. `new_store` is added and linked if the receipt isn't linked
+/// to a known store.
 #[server]
 pub async fn create_receipt_with_items(
     receipt_form: ReceiptForm,
     receipt_items_forms: Vec<ReceiptItemForm>,
     matched_days: Option<Vec<i32>>,
-) -> Result<ReceiptWithItems, ServerFnError> {
+    new_store: Option<StoreForm>,
+    force: bool,
+) -> Result<CreateReceiptResult, ServerFnError> {
     use crate::api::ssr::*;
+    use crate::models::category::{categorize, CategoryRule};
+    use crate::models::product::{Product, ProductAlias};
     let db = &mut get_db()?;
-    let receipt: Receipt = server_err!(
-        receipt_form.insert(db),
-        "Could not insert receipt {receipt_form:?}"
-    )?;
-    let mut items = vec![];
-    for mut item_form in receipt_items_forms {
-        item_form.receipt_id = receipt.id;
-        items.push(server_err!(
-            item_form.insert(db),
-            "Could not insert receipt_item {item_form:?}"
-        )?);
-    }
-    for day in matched_days.unwrap_or_default() {
-        server_err!(
-            ReceiptDay {
-                day_id: day,
-                receipt_id: receipt.id
+    // Stores, aliases and the receipt are saved together or not at all
+    db.transaction(|db| -> Result<_, ServerFnError> {
+        if !force {
+            let duplicates = server_err!(
+                ReceiptWithItems::find_duplicates(db, &receipt_form, &receipt_items_forms),
+                "Could not look for duplicates of {receipt_form:?}"
+            )?;
+            if !duplicates.is_empty() {
+                return Ok(CreateReceiptResult::Duplicate(duplicates));
             }
-            .upsert(db),
-            "Could not insert receipt day {day}"
+        }
+        let mut receipt_form = receipt_form;
+        if let (None, Some(new_store)) = (receipt_form.store_id, &new_store) {
+            let store = server_err!(
+                new_store.find_or_insert(db),
+                "Could not add store {new_store:?}"
+            )?;
+            receipt_form.store_id = Some(store.id);
+        }
+        let receipt: Receipt = server_err!(
+            receipt_form.insert(db),
+            "Could not insert receipt {receipt_form:?}"
         )?;
-    }
-    Ok(ReceiptWithItems {
-        days: Day::get_by_receipt(db, receipt.id)?,
-        receipt,
-        items,
+        let rules = server_err!(CategoryRule::get_all(db), "Could not get category rules")?;
+        let mut items = vec![];
+        for mut item_form in receipt_items_forms {
+            item_form.receipt_id = receipt.id;
+            let product = match &item_form.raw_name {
+                Some(raw_name) => ProductAlias::learn(db, raw_name, &item_form.name),
+                None => Product::find(db, &item_form.name),
+            };
+            item_form.product_id =
+                server_err!(product, "Could not find product for {}", item_form.name)?
+                    .map(|p| p.id);
+            if item_form.category_id.is_none() {
+                item_form.category_id = categorize(&rules, &item_form.name);
+            }
+            items.push(server_err!(
+                item_form.insert(db),
+                "Could not insert receipt_item {item_form:?}"
+            )?);
+        }
+        for day in matched_days.unwrap_or_default() {
+            server_err!(
+                ReceiptDay {
+                    day_id: day,
+                    receipt_id: receipt.id
+                }
+                .upsert(db),
+                "Could not insert receipt day {day}"
+            )?;
+        }
+        Ok(CreateReceiptResult::Created(ReceiptWithItems {
+            days: Day::get_by_receipt(db, receipt.id)?,
+            receipt,
+            items,
+        }))
     })
 }
 
 #[server]
-pub async fn get_all_receipts_with_items() -> Result<Vec<ReceiptWithItems>, ServerFnError> {
+pub async fn search_receipts(query: ReceiptQuery) -> Result<ReceiptPage, ServerFnError> {
     use crate::api::ssr::*;
     let db = &mut get_db()?;
     server_err!(
-        ReceiptWithItems::get_all(db),
-        "Could not get all receipts with items"
+        ReceiptWithItems::search(db, &query),
+        "Could not search receipts {query:?}"
     )
 }
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::tests::TEST_POOL;
+    use crate::models::day::{Day, DayForm};
+    use crate::models::member::Member;
+    use crate::models::money::Money;
+    use crate::models::receipt::{ItemKind, SplitMode};
+    use chrono::{Datelike, NaiveDate, Timelike};
+    use diesel::Connection;
+
+    fn item(name: &str, price: &str, shares: [i32; 3]) -> ReceiptItemForm {
+        ReceiptItemForm {
+            receipt_id: -1,
+            name: String::from(name),
+            price: price.parse().unwrap(),
+            anders_share: shares[0],
+            andreas_share: shares[1],
+            ac_share: shares[2],
+            kind: ItemKind::Product,
+            category_id: None,
+            raw_name: None,
+            product_id: None,
+        }
+    }
+
+    #[test]
+    fn test_weighted_sums_add_up_to_total() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let receipt = ReceiptForm {
+                store: String::from("Rema"),
+                datetime: chrono::Local::now().naive_local(),
+                anders_share: 2,
+                andreas_share: 1,
+                ac_share: 1,
+                split_mode: SplitMode::Equal,
+                image_hash: None,
+                store_id: None,
+            }
+            .insert(db)
+            .unwrap();
+            for mut item in [
+                item("Melk", "10,00", [1, 1, 1]),
+                item("Brød", "33,33", [1, 1, 1]),
+                item("Øl", "0,10", [0, 1, 0]),
+                item("Pant", "2,00", [0, 0, 0]),
+            ] {
+                item.receipt_id = receipt.id;
+                item.insert(db).unwrap();
+            }
+            let receipt = ReceiptWithItems::get(db, receipt.id).unwrap();
+            let [anders, andreas, ac] = receipt.member_sums();
+            assert_eq!(receipt.total(), Money::from_ore(4543));
+            assert_eq!(anders + andreas + ac, receipt.total());
+            assert_eq!(anders, Money::from_ore(2267));
+            assert_eq!(andreas - ac, Money::from_ore(10));
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_split_by_attendance() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let receipt = ReceiptForm {
+                store: String::from("Kiwi"),
+                datetime: chrono::Local::now().naive_local(),
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                split_mode: SplitMode::Attendance,
+                image_hash: None,
+                store_id: None,
+            }
+            .insert(db)
+            .unwrap();
+            let mut item = item("Kjøttdeig", "90,00", [1, 1, 1]);
+            item.receipt_id = receipt.id;
+            item.insert(db).unwrap();
+
+            let receipt = ReceiptWithItems::get(db, receipt.id).unwrap();
+            assert_eq!(receipt.split_shares(), [1, 1, 1]);
+
+            for (d, attend) in [(6, [true, true, false]), (7, [true, false, false])] {
+                let date = NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+                let day = DayForm {
+                    date,
+                    week: date.iso_week().week() as i32,
+                    year: date.year(),
+                    leftovers_from_day_id: None,
+                }
+                .upsert(db)
+                .unwrap();
+                Day::update_attendance(db, day.id, attend[0], attend[2], attend[1]).unwrap();
+                ReceiptDay {
+                    receipt_id: receipt.receipt.id,
+                    day_id: day.id,
+                }
+                .upsert(db)
+                .unwrap();
+            }
+
+            let receipt = ReceiptWithItems::get(db, receipt.receipt.id).unwrap();
+            assert_eq!(receipt.split_shares(), [2, 1, 0]);
+            assert_eq!(
+                receipt.member_sums(),
+                [Money::from_ore(6000), Money::from_ore(3000), Money::ZERO]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_ocr_job_lifecycle() {
+        use crate::models::ocr_job::{OcrJob, OcrJobForm, OcrJobStatus};
+        use crate::ocr::parse_receipt;
+
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let form = OcrJobForm {
+                file_path: String::from("/tmp/receipt.jpg"),
+            };
+            let first = form.insert(db).unwrap();
+            let second = form.insert(db).unwrap();
+            assert_eq!(first.status, OcrJobStatus::Queued);
+
+            // A job that was running when the server stopped is picked up again
+            OcrJob::start(db, first.id).unwrap();
+            assert_eq!(
+                OcrJob::requeue_unfinished(db).unwrap(),
+                vec![first.id, second.id]
+            );
+
+            let scanned = parse_receipt(vec![
+                vec![String::from("Melk"), String::from("21,90")].into()
+            ]);
+            let update = OcrJob::finish(db, first.id, &scanned).unwrap().to_update();
+            assert_eq!(update.status, OcrJobStatus::Done);
+            assert_eq!(update.result.unwrap().items[0].price, Money::from_ore(2190));
+
+            let update = OcrJob::fail(db, second.id, String::from("Timed out"))
+                .unwrap()
+                .to_update();
+            assert_eq!(update.status, OcrJobStatus::Failed);
+            assert_eq!(update.error.as_deref(), Some("Timed out"));
+            assert!(OcrJob::requeue_unfinished(db).unwrap().is_empty());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let datetime = NaiveDate::from_ymd_opt(2025, 3, 12)
+                .unwrap()
+                .and_hms_opt(17, 45, 0)
+                .unwrap();
+            let form = ReceiptForm {
+                store: String::from("Rema"),
+                datetime,
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                split_mode: SplitMode::Equal,
+                image_hash: Some(0b1011_0110),
+                store_id: None,
+            };
+            let receipt = form.insert(db).unwrap();
+            for mut item in [
+                item("Melk", "21,90", [1, 1, 1]),
+                item("Brød", "39,90", [1, 1, 1]),
+            ] {
+                item.receipt_id = receipt.id;
+                item.insert(db).unwrap();
+            }
+
+            // Same purchase, items in another order and read a few seconds off
+            let mut again = form.clone();
+            again.datetime = datetime.with_second(12).unwrap();
+            again.image_hash = None;
+            let items = [
+                item("brød ", "39,90", [1, 1, 1]),
+                item("MELK", "21,90", [1, 1, 1]),
+            ];
+            let found = ReceiptWithItems::find_duplicates(db, &again, &items).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].receipt.id, receipt.id);
+
+            let other_items = [item("Melk", "21,90", [1, 1, 1])];
+            assert!(ReceiptWithItems::find_duplicates(db, &again, &other_items)
+                .unwrap()
+                .is_empty());
+
+            // A photo of the same receipt is caught even if OCR read it differently
+            again.image_hash = Some(0b1011_0111);
+            assert_eq!(
+                ReceiptWithItems::find_duplicates(db, &again, &other_items)
+                    .unwrap()
+                    .len(),
+                1
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_discounts_follow_product() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let receipt = ReceiptForm {
+                store: String::from("Coop"),
+                datetime: chrono::Local::now().naive_local(),
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                split_mode: SplitMode::Equal,
+                image_hash: None,
+                store_id: None,
+            }
+            .insert(db)
+            .unwrap();
+            let lines = [
+                ("Pepsi Max", "32,90", [0, 1, 0], ItemKind::Product),
+                ("Pant", "3,00", [1, 1, 1], ItemKind::Deposit),
+                ("Kaffe", "89,90", [1, 0, 0], ItemKind::Product),
+                ("Rabatt kaffe", "-30,00", [1, 1, 1], ItemKind::Discount),
+                ("Panteretur", "-6,00", [1, 1, 1], ItemKind::DepositReturn),
+            ];
+            for (name, price, shares, kind) in lines {
+                let mut item = item(name, price, shares);
+                item.receipt_id = receipt.id;
+                item.kind = kind;
+                item.insert(db).unwrap();
+            }
+            let receipt = ReceiptWithItems::get(db, receipt.id).unwrap();
+            assert_eq!(receipt.total(), Money::from_ore(8980));
+            assert_eq!(
+                receipt.member_sums(),
+                [
+                    Money::from_ore(5790),
+                    Money::from_ore(3390),
+                    Money::from_ore(-200)
+                ]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_search_receipts() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let date = |day| {
+                NaiveDate::from_ymd_opt(2031, 5, day)
+                    .unwrap()
+                    .and_hms_opt(12, 0, 0)
+                    .unwrap()
+            };
+            let mut ids = vec![];
+            for (store, day, ac_share, items) in [
+                ("Rema 1000", 1, 1, vec![item("Melk", "20,00", [1, 1, 1])]),
+                ("Kiwi", 2, 0, vec![item("Taco_krydder", "15,00", [1, 1, 1])]),
+                (
+                    "Kiwi",
+                    3,
+                    1,
+                    vec![
+                        item("Melk", "20,00", [1, 1, 1]),
+                        item("Kjøttdeig", "80,00", [1, 1, 1]),
+                    ],
+                ),
+            ] {
+                let receipt = ReceiptForm {
+                    store: String::from(store),
+                    datetime: date(day),
+                    anders_share: 1,
+                    andreas_share: 1,
+                    ac_share,
+                    split_mode: SplitMode::Equal,
+                    image_hash: None,
+                    store_id: None,
+                }
+                .insert(db)
+                .unwrap();
+                for mut item in items {
+                    item.receipt_id = receipt.id;
+                    item.insert(db).unwrap();
+                }
+                ids.push(receipt.id);
+            }
+            let search = |db: &mut _, query: ReceiptQuery| -> Vec<i32> {
+                let query = ReceiptQuery {
+                    from: Some(date(1).date()),
+                    to: Some(date(3).date()),
+                    ..query
+                };
+                ReceiptWithItems::search(db, &query)
+                    .unwrap()
+                    .receipts
+                    .iter()
+                    .map(|r| r.receipt.id)
+                    .collect()
+            };
+
+            assert_eq!(
+                search(db, ReceiptQuery::default()),
+                [ids[2], ids[1], ids[0]]
+            );
+            let kiwi = ReceiptQuery {
+                store: Some(String::from("kiwi")),
+                ..Default::default()
+            };
+            assert_eq!(search(db, kiwi), [ids[2], ids[1]]);
+            let ac = ReceiptQuery {
+                member: Some(Member::Ac),
+                ..Default::default()
+            };
+            assert_eq!(search(db, ac), [ids[2], ids[0]]);
+            let melk = ReceiptQuery {
+                item: Some(String::from("MELK")),
+                ..Default::default()
+            };
+            assert_eq!(search(db, melk), [ids[2], ids[0]]);
+            // `_` is a literal underscore, not a wildcard
+            let underscore = ReceiptQuery {
+                item: Some(String::from("M_lk")),
+                ..Default::default()
+            };
+            assert!(search(db, underscore).is_empty());
+            let amount = ReceiptQuery {
+                min_total: Some(Money::from_kroner(15)),
+                max_total: Some(Money::from_kroner(20)),
+                ..Default::default()
+            };
+            assert_eq!(search(db, amount), [ids[1], ids[0]]);
+            let second_day = ReceiptQuery {
+                from: Some(date(2).date()),
+                to: Some(date(2).date()),
+                ..Default::default()
+            };
+            let page = ReceiptWithItems::search(db, &second_day).unwrap();
+            assert_eq!(page.total_count, 1);
+            assert_eq!(page.receipts[0].receipt.id, ids[1]);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_search_by_member_shares() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let date = NaiveDate::from_ymd_opt(2031, 6, 1).unwrap();
+            let day = DayForm {
+                date,
+                week: date.iso_week().week() as i32,
+                year: date.year(),
+                leftovers_from_day_id: None,
+            }
+            .upsert(db)
+            .unwrap();
+            // AC is away
+            Day::update_attendance(db, day.id, true, false, true).unwrap();
+            let mut ids = vec![];
+            for (hour, split_mode, items) in [
+                // Only AC has a share in the beer
+                (1, SplitMode::Equal, vec![item("Øl", "30,00", [0, 0, 1])]),
+                // Nobody else had the soda, but AC wasn't there either
+                (
+                    2,
+                    SplitMode::Attendance,
+                    vec![item("Brus", "20,00", [0, 0, 1])],
+                ),
+                // Everything but AC
+                (
+                    3,
+                    SplitMode::Equal,
+                    vec![
+                        item("Kjøttdeig", "80,00", [1, 1, 0]),
+                        item("Rabatt", "-10,00", [1, 1, 1]),
+                    ],
+                ),
+                // Split by attendance, and AC wasn't there
+                (
+                    4,
+                    SplitMode::Attendance,
+                    vec![item("Melk", "20,00", [1, 1, 1])],
+                ),
+            ] {
+                let receipt = ReceiptForm {
+                    store: String::from("Kiwi"),
+                    datetime: date.and_hms_opt(hour, 0, 0).unwrap(),
+                    anders_share: 1,
+                    andreas_share: 1,
+                    ac_share: 1,
+                    split_mode,
+                    image_hash: None,
+                    store_id: None,
+                }
+                .insert(db)
+                .unwrap();
+                for mut item in items {
+                    item.receipt_id = receipt.id;
+                    item.kind = ItemKind::from_name(&item.name);
+                    item.insert(db).unwrap();
+                }
+                ReceiptDay {
+                    receipt_id: receipt.id,
+                    day_id: day.id,
+                }
+                .upsert(db)
+                .unwrap();
+                ids.push(receipt.id);
+            }
+            let search = |db: &mut _, member| -> Vec<i32> {
+                let query = ReceiptQuery {
+                    from: Some(date),
+                    to: Some(date),
+                    member: Some(member),
+                    ..Default::default()
+                };
+                ReceiptWithItems::search(db, &query)
+                    .unwrap()
+                    .receipts
+                    .iter()
+                    .map(|r| r.receipt.id)
+                    .collect()
+            };
+            assert_eq!(search(db, Member::Ac), [ids[0]]);
+            assert_eq!(search(db, Member::Anders), [ids[3], ids[2], ids[1]]);
+            // Same as how the receipts are split
+            for id in &ids {
+                let receipt = ReceiptWithItems::get(db, *id).unwrap();
+                let pays = receipt.member_sums()[Member::Ac.index()] != Money::ZERO;
+                assert_eq!(pays, search(db, Member::Ac).contains(id));
+            }
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,94 @@
+use crate::models::member::Member;
+use crate::models::restriction::MemberRestriction;
+use leptos::prelude::*;
+
+#[server]
+pub async fn get_member_restrictions() -> Result<Vec<MemberRestriction>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(
+        MemberRestriction::get_all(db),
+        "Could not get member restrictions"
+    )
+}
+
+/// Replaces what the member can't eat, `allergens` is comma separated.
+#[server]
+pub async fn set_member_restrictions(
+    member: Member,
+    allergens: String,
+) -> Result<Vec<MemberRestriction>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(
+        db.transaction(|db| MemberRestriction::set_for_member(db, member, &allergens)),
+        "Could not set restrictions for {member}"
+    )
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use crate::db::tests::TEST_POOL;
+    use crate::models::ingredient::{Ingredient, IngredientForm};
+    use crate::models::ingredient_catalog::CatalogIngredient;
+    use crate::models::meal::MealForm;
+    use crate::models::member::Member;
+    use crate::models::restriction::{find_conflicts, MemberRestriction};
+    use diesel::Connection;
+
+    #[test]
+    fn test_member_restrictions() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let meal_id = MealForm {
+                name: String::from("Rekepasta"),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            }
+            .insert(db)
+            .unwrap()
+            .id;
+            let shrimp = IngredientForm {
+                name: String::from("Reker i lake"),
+                amount: 1,
+                meal_id,
+            }
+            .insert(db)
+            .unwrap();
+            let entry = CatalogIngredient::find(db, &shrimp.name).unwrap().unwrap();
+            CatalogIngredient {
+                allergens: String::from("Shellfish, shellfish"),
+                ..entry
+            }
+            .update(db)
+            .unwrap();
+
+            MemberRestriction::set_for_member(db, Member::Ac, "shellfish, nuts").unwrap();
+            // Setting them again replaces the old ones
+            let restrictions =
+                MemberRestriction::set_for_member(db, Member::Ac, " Shellfish ").unwrap();
+            assert_eq!(restrictions.len(), 1);
+            let restrictions = MemberRestriction::get_all(db).unwrap();
+            assert_eq!(
+                restrictions,
+                [MemberRestriction {
+                    member: Member::Ac,
+                    allergen: String::from("shellfish"),
+                }]
+            );
+
+            let ingredients = Ingredient::get_for_meal(db, meal_id).unwrap();
+            assert_eq!(ingredients[0].allergens, "shellfish");
+            let conflicts = find_conflicts(&restrictions, Member::ALL, &ingredients);
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].ingredient, "Reker i lake");
+            assert!(find_conflicts(&restrictions, [Member::Anders], &ingredients).is_empty());
+
+            MemberRestriction::set_for_member(db, Member::Ac, "").unwrap();
+            assert!(MemberRestriction::get_all(db).unwrap().is_empty());
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,73 @@
+use leptos::prelude::*;
+
+use crate::models::store::{Store, StoreSpending};
+
+#[server]
+pub async fn get_stores() -> Result<Vec<Store>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(Store::get_all(db), "Could not get stores")
+}
+
+#[server]
+pub async fn get_stores_with_spending() -> Result<Vec<StoreSpending>, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(StoreSpending::get_all(db), "Could not get store spending")
+}
+
+#[server]
+pub async fn update_store(store: Store) -> Result<Store, ServerFnError> {
+    use crate::api::ssr::*;
+    let db = &mut get_db()?;
+    server_err!(store.update(db), "Could not update store {store:?}")
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use crate::db::tests::TEST_POOL;
+    use crate::models::store::{DetectedStore, Store, StoreForm};
+    use diesel::Connection;
+
+    #[test]
+    fn test_detect_store() {
+        let db = &mut TEST_POOL.clone().get().unwrap();
+        db.test_transaction(|db| -> Result<(), ()> {
+            let known = |detected: Option<DetectedStore>| match detected {
+                Some(DetectedStore::Known(store)) => Some(store.id),
+                _ => None,
+            };
+            // A new org number only proposes the store, scanning saves nothing
+            let Some(DetectedStore::New(form)) =
+                Store::detect(db, Some("Meny"), Some("987654321")).unwrap()
+            else {
+                panic!("Expected a new store");
+            };
+            assert_eq!(form.chain, "Meny");
+            assert_eq!(Store::detect(db, None, Some("987654321")).unwrap(), None);
+            // Saving the receipt adds it once, and it is found on the next receipt
+            let store = form.find_or_insert(db).unwrap();
+            assert_eq!(form.find_or_insert(db).unwrap().id, store.id);
+            assert_eq!(
+                known(Store::detect(db, None, Some("987654321")).unwrap()),
+                Some(store.id)
+            );
+            // The chain alone is enough while it is the only store of the chain
+            assert_eq!(
+                known(Store::detect(db, Some("Meny"), None).unwrap()),
+                Some(store.id)
+            );
+            StoreForm {
+                chain: String::from("Meny"),
+                name: String::from("Storo"),
+                ..Default::default()
+            }
+            .insert(db)
+            .unwrap();
+            assert!(Store::detect(db, Some("Meny"), None).unwrap().is_none());
+            assert!(Store::detect(db, None, None).unwrap().is_none());
+            Ok(())
+        });
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(feature = "ssr")]
-use crate::models::{day::DayForm, days_ingredients::IngredientWithBought};
+use crate::models::day::DayForm;
 
-use crate::models::{days_ingredients::DayWithMealAndIngredients, receipt::ReceiptWithItems};
+use crate::models::days_ingredients::DayWithMealAndIngredients;
 use chrono::{Datelike, Local, NaiveDate, Weekday};
 use leptos::prelude::*;
 
@@ -91,7 +91,7 @@ pub async fn days_for_week(week: Week) -> Result<[DayWithMealAndIngredients; 7],
                     date,
                     week: date.iso_week().week() as i32,
                     year: date.year(),
-                    meal_id: None,
+                    leftovers_from_day_id: None,
                 };
                 server_err!(
                     insert_into(days::table).values(&day_form).execute(db),
@@ -101,10 +101,7 @@ pub async fn days_for_week(week: Week) -> Result<[DayWithMealAndIngredients; 7],
         }
     }
     let days_rows = server_err!(
-        days_query
-            .left_join(meals::table)
-            .select((Day::as_select(), Option::<Meal>::as_select()))
-            .load::<(Day, Option<Meal>)>(db),
+        days_query.select(Day::as_select()).load::<Day>(db),
         "Could not get days for week {week:?}"
     )?;
     if days_rows.len() != 7 {
@@ -114,35 +111,11 @@ pub async fn days_for_week(week: Week) -> Result<[DayWithMealAndIngredients; 7],
         )));
     }
 
-    for (day, meal) in days_rows {
-        let receipts = ReceiptWithItems::get_by_day(db, day.id)?;
-        if let Some(meal) = meal {
-            let ingredients = server_err!(
-                DayIngredient::belonging_to(&day)
-                    .inner_join(ingredients::table)
-                    .select((DayIngredient::as_select(), Ingredient::as_select()))
-                    .load::<(DayIngredient, Ingredient)>(db),
-                "Could not get ingredeints for {day:?}"
-            )?
-            .into_iter()
-            .map(|(di, ingredient)| IngredientWithBought {
-                day_id: di.day_id,
-                ingredient,
-                bought: di.bought,
-            })
-            .collect();
-            days.push(DayWithMealAndIngredients {
-                day,
-                meal: Some((meal, ingredients)),
-                receipts,
-            });
-        } else {
-            days.push(DayWithMealAndIngredients {
-                day,
-                meal: None,
-                receipts,
-            })
-        }
+    for day in days_rows {
+        days.push(server_err!(
+            DayWithMealAndIngredients::load(db, day.clone()),
+            "Could not get meals for {day:?}"
+        )?);
     }
     days.try_into()
         .map_err(|_| ServerFnError::new("Expected 7 days in week"))
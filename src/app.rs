@@ -1,5 +1,13 @@
 use crate::components::notifications::Notifications;
+use crate::models::category::Category;
+use crate::models::food::Food;
+use crate::models::ocr_job::OcrJobUpdate;
+use crate::models::restriction::MemberRestriction;
 use crate::routes::receipt::{ReceiptCreateRoute, ReceiptListRoute};
+use crate::routes::category::CategoryReportRoute;
+use crate::routes::ingredient_catalog::IngredientCatalogRoute;
+use crate::routes::restriction::RestrictionsRoute;
+use crate::routes::store::StoreListRoute;
 use crate::routes::{meal_list::MealList, shopping_list::ShoppingList, week::Week};
 use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
@@ -17,6 +25,14 @@ pub struct GlobalState {
 }
 
 pub type IngredientUpdateMap = RwSignal<HashMap<(i32, i32), bool>>;
+/// Categories items can be put in, provided by the routes that show receipts.
+pub type CategoryList = RwSignal<Vec<Category>>;
+/// Latest state of every OCR job seen over the websocket, by job id.
+pub type OcrJobUpdateMap = RwSignal<HashMap<i32, OcrJobUpdate>>;
+/// Foods catalog ingredients are linked to, provided by the ingredient catalog.
+pub type FoodList = RwSignal<Vec<Food>>;
+/// What each member can't eat, provided by the routes that warn about allergens.
+pub type RestrictionList = RwSignal<Vec<MemberRestriction>>;
 
 #[derive(Clone)]
 pub enum RouteUrl {
@@ -25,6 +41,10 @@ pub enum RouteUrl {
     ShoppingList,
     ReceiptCreate,
     ReceiptList,
+    StoreList,
+    CategoryReport,
+    IngredientCatalog,
+    Restrictions,
 }
 impl RouteUrl {
     fn as_path(&self) -> String {
@@ -34,6 +54,10 @@ impl RouteUrl {
             RouteUrl::ShoppingList => "/shopping-list".to_string(),
             RouteUrl::ReceiptCreate => "/receipt".to_string(),
             RouteUrl::ReceiptList => "/receipt-list".to_string(),
+            RouteUrl::StoreList => "/stores".to_string(),
+            RouteUrl::CategoryReport => "/categories".to_string(),
+            RouteUrl::IngredientCatalog => "/ingredients".to_string(),
+            RouteUrl::Restrictions => "/restrictions".to_string(),
         }
     }
 
@@ -92,6 +116,8 @@ pub fn App() -> impl IntoView {
 
     let ingredient_updates: IngredientUpdateMap = RwSignal::new(HashMap::new());
     provide_context(ingredient_updates);
+    let ocr_job_updates: OcrJobUpdateMap = RwSignal::new(HashMap::new());
+    provide_context(ocr_job_updates);
 
     view! {
         // injects a stylesheet into the document <head>
@@ -112,6 +138,10 @@ pub fn App() -> impl IntoView {
                     <Route path=path!("/meals") view=MealList />
                     <Route path=path!("/receipt") view=ReceiptCreateRoute />
                     <Route path=path!("/receipt-list") view=ReceiptListRoute />
+                    <Route path=path!("/stores") view=StoreListRoute />
+                    <Route path=path!("/categories") view=CategoryReportRoute />
+                    <Route path=path!("/ingredients") view=IngredientCatalogRoute />
+                    <Route path=path!("/restrictions") view=RestrictionsRoute />
 
                 </Routes>
             </main>
@@ -120,9 +150,9 @@ pub fn App() -> impl IntoView {
 }
 
 /// Invisible component that holds a single WebSocket connection for the lifetime
-/// of the app session. Receives ingredient-update messages and writes them into
-/// the shared `IngredientUpdateMap` context so every `DayIngredient` component
-/// can react in real time.
+/// of the app session. Receives ingredient and OCR job updates and writes them into
+/// the shared `IngredientUpdateMap` and `OcrJobUpdateMap` contexts so every
+/// `DayIngredient` and `ReceiptUpload` component can react in real time.
 #[component]
 fn WsListener() -> impl IntoView {
     // Derive the WebSocket URL from the current page's origin at runtime so the
@@ -130,11 +160,12 @@ fn WsListener() -> impl IntoView {
     // The entire block is compiled out under SSR.
     #[cfg(not(feature = "ssr"))]
     {
-        use crate::ws::IngredientUpdate;
+        use crate::ws::WsMessage;
         use codee::string::FromToStringCodec;
         use leptos_use::{use_websocket, UseWebSocketReturn};
 
         let ingredient_updates = expect_context::<IngredientUpdateMap>();
+        let ocr_job_updates = expect_context::<OcrJobUpdateMap>();
 
         let ws_url = {
             let location = web_sys::window().expect("no window").location();
@@ -152,10 +183,18 @@ fn WsListener() -> impl IntoView {
 
         Effect::new(move |_| {
             if let Some(msg) = message.get() {
-                if let Ok(update) = serde_json::from_str::<IngredientUpdate>(&msg) {
-                    ingredient_updates.update(|map| {
-                        map.insert((update.day_id, update.ingredient_id), update.bought);
-                    });
+                match serde_json::from_str::<WsMessage>(&msg) {
+                    Ok(WsMessage::IngredientUpdate(update)) => {
+                        ingredient_updates.update(|map| {
+                            map.insert((update.day_id, update.ingredient_id), update.bought);
+                        });
+                    }
+                    Ok(WsMessage::OcrJobUpdate(update)) => {
+                        ocr_job_updates.update(|map| {
+                            map.insert(update.job_id, *update);
+                        });
+                    }
+                    Err(_) => {}
                 }
             }
         });
@@ -1 +1,2 @@
-pub mod attendance;
\ No newline at end of file
+pub mod attendance;
+pub mod rating;
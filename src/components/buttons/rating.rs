@@ -0,0 +1,104 @@
+use crate::api::meal_rating::{delete_meal_rating, rate_meal};
+use crate::models::day::Day;
+use crate::models::meal_rating::{MealRating, MealRatingForm};
+use crate::models::member::Member;
+use leptos::prelude::*;
+
+/// Stars and a comment for one member. Clicking the current score again removes
+/// the rating.
+#[component]
+fn MemberRating(
+    member: Member,
+    day_id: i32,
+    meal_id: i32,
+    rating: Option<MealRating>,
+) -> impl IntoView {
+    let comment = RwSignal::new(
+        rating
+            .as_ref()
+            .and_then(|r| r.comment.clone())
+            .unwrap_or_default(),
+    );
+    let rating = RwSignal::new(rating);
+    let rate_action = Action::new(|form: &MealRatingForm| {
+        let form = form.clone();
+        async move { rate_meal(form).await }
+    });
+    let delete_action = Action::new(|id: &i32| {
+        let id = *id;
+        async move { delete_meal_rating(id).await }
+    });
+    Effect::new(move || {
+        if let Some(Ok(saved)) = rate_action.value().get() {
+            rating.set(Some(saved));
+        }
+    });
+    let save = move |score: i32| {
+        rate_action.dispatch(MealRatingForm {
+            member,
+            meal_id,
+            day_id,
+            score,
+            comment: Some(comment.get()),
+        });
+    };
+    let rate = move |score: i32| match rating.get() {
+        Some(current) if current.score == score => {
+            delete_action.dispatch(current.id);
+            rating.set(None);
+            comment.set(String::new());
+        }
+        _ => save(score),
+    };
+    let score = move || rating.read().as_ref().map_or(0, |r| r.score);
+
+    view! {
+        <div class="flex items-center gap-2">
+            <span class="w-16 text-sm text-gray-700 dark:text-gray-300">{member.name()}</span>
+            <span class="flex">
+                {(1..=5)
+                    .map(|value| {
+                        view! {
+                            <button
+                                type="button"
+                                title=format!("{value} of 5")
+                                class=move || {
+                                    if score() >= value {
+                                        "text-xl leading-none text-yellow-400"
+                                    } else {
+                                        "text-xl leading-none text-gray-300 dark:text-gray-600"
+                                    }
+                                }
+                                on:click=move |_| rate(value)
+                            >
+                                "★"
+                            </button>
+                        }
+                    })
+                    .collect::<Vec<_>>()}
+            </span>
+            <Show when=move || rating.read().is_some() fallback=|| view! {}>
+                <input
+                    type="text"
+                    placeholder="Comment"
+                    bind:value=comment
+                    on:change=move |_| save(score())
+                    class="flex-1 min-w-0 px-2 py-0.5 text-xs border rounded focus:outline-none focus:ring-1 focus:ring-blue-400 dark:bg-gray-700 dark:text-white"
+                />
+            </Show>
+        </div>
+    }
+}
+
+/// Asks everyone who ate the dinner what they thought of it.
+#[component]
+pub fn MealRatings(day: Day, meal_id: i32, ratings: Vec<MealRating>) -> impl IntoView {
+    Member::ALL
+        .into_iter()
+        .filter(|member| day.attends(*member))
+        .map(|member| {
+            let rating = ratings.iter().find(|r| r.member == member).cloned();
+            view! { <MemberRating member day_id=day.id meal_id rating /> }
+        })
+        .collect::<Vec<_>>()
+}
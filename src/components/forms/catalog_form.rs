@@ -0,0 +1,203 @@
+use crate::api::food::search_foods;
+use crate::api::ingredient_catalog::update_catalog_ingredient;
+use crate::app::{CategoryList, FoodList};
+use crate::components::forms::restriction_form::AllergenChips;
+use crate::models::food::Food;
+use crate::models::ingredient_catalog::CatalogIngredient;
+use leptos::prelude::*;
+use leptos_use::signal_debounced;
+
+#[component]
+pub fn EditCatalogIngredientForm(
+    entry: CatalogIngredient,
+    saved: WriteSignal<Option<CatalogIngredient>>,
+) -> impl IntoView {
+    let update_entry_action = Action::new(|entry: &CatalogIngredient| {
+        let entry = entry.clone();
+        async move { update_catalog_ingredient(entry).await }
+    });
+    Effect::new(move || {
+        if let Some(Ok(entry)) = update_entry_action.value().get() {
+            saved.set(Some(entry));
+        }
+    });
+
+    let categories = use_context::<CategoryList>().unwrap_or_default();
+    let name = RwSignal::new(entry.name.clone());
+    let aliases = RwSignal::new(entry.aliases.clone());
+    let unit = RwSignal::new(entry.unit.clone().unwrap_or_default());
+    let category_id = RwSignal::new(entry.category_id);
+    let allergens = RwSignal::new(entry.allergens.clone());
+    let grams_per_unit = RwSignal::new(
+        entry
+            .grams_per_unit
+            .map(|grams| grams.to_string())
+            .unwrap_or_default(),
+    );
+    let foods = use_context::<FoodList>().unwrap_or_default();
+    let food: RwSignal<Option<Food>> = RwSignal::new(
+        entry
+            .food_id
+            .and_then(|id| foods.read_untracked().iter().find(|f| f.id == id).cloned()),
+    );
+    let food_query = RwSignal::new(String::new());
+    let debounced_query: Signal<String> = signal_debounced(food_query, 250.0);
+    let found_foods = LocalResource::new(move || {
+        let query = debounced_query.get();
+        async move {
+            if query.trim().is_empty() {
+                Ok(vec![])
+            } else {
+                search_foods(query).await
+            }
+        }
+    });
+
+    let form_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        let unit = unit.get();
+        if let Some(food) = food.get() {
+            if !foods.read().contains(&food) {
+                foods.write().push(food);
+            }
+        }
+        update_entry_action.dispatch(CatalogIngredient {
+            name: name.get(),
+            aliases: aliases.get(),
+            unit: (!unit.trim().is_empty()).then(|| unit.trim().to_owned()),
+            category_id: category_id.get(),
+            allergens: allergens.get(),
+            food_id: food.read().as_ref().map(|f| f.id),
+            grams_per_unit: grams_per_unit.get().replace(',', ".").parse().ok(),
+            ..entry.clone()
+        });
+    };
+
+    let input_class = "px-3 py-2 w-full border rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-400 dark:bg-gray-700 dark:text-white";
+    view! {
+        <div class="w-full max-w-md sm:max-w-lg mx-auto p-6 bg-white dark:bg-gray-900 rounded-xl shadow-lg">
+            <form on:submit=form_submit class="space-y-3">
+                <input type="text" placeholder="Name" bind:value=name class=input_class required />
+                <textarea
+                    placeholder="Other names, one per line"
+                    rows="3"
+                    bind:value=aliases
+                    class=input_class
+                ></textarea>
+                <input
+                    type="text"
+                    placeholder="Unit, e.g. stk, g or dl"
+                    bind:value=unit
+                    class=input_class
+                />
+                <select
+                    class=input_class
+                    on:change:target=move |ev| {
+                        category_id.set(ev.target().value().parse::<i32>().ok())
+                    }
+                >
+                    <option value="" selected=move || category_id.get().is_none()>
+                        "No category"
+                    </option>
+                    {move || {
+                        categories
+                            .get()
+                            .into_iter()
+                            .map(|category| {
+                                let id = category.id;
+                                view! {
+                                    <option value=id selected=move || category_id.get() == Some(id)>
+                                        {category.name}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </select>
+                <input
+                    type="text"
+                    placeholder="Allergens, comma separated"
+                    bind:value=allergens
+                    class=input_class
+                />
+                <AllergenChips allergens />
+                <input
+                    type="text"
+                    inputmode="decimal"
+                    placeholder="Grams per unit, e.g. 120 for a stk"
+                    bind:value=grams_per_unit
+                    class=input_class
+                />
+                {move || {
+                    food.get()
+                        .map(|selected| {
+                            view! {
+                                <div class="flex items-center justify-between text-sm text-gray-700 dark:text-gray-200">
+                                    <span>{format!("Nutrition from {}", selected.name)}</span>
+                                    <button
+                                        type="button"
+                                        class="text-red-600 hover:underline"
+                                        on:click=move |_| food.set(None)
+                                    >
+                                        "Remove"
+                                    </button>
+                                </div>
+                            }
+                        })
+                }}
+                <input
+                    type="text"
+                    placeholder="Search foods for nutrition..."
+                    bind:value=food_query
+                    class=input_class
+                />
+                <Transition fallback=|| view! {}>
+                    <ul class="text-sm text-gray-700 dark:text-gray-200 max-h-40 overflow-y-auto">
+                        {move || {
+                            found_foods
+                                .get()
+                                .and_then(Result::ok)
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|found| {
+                                    let kcal = format!("{:.0} kcal", found.kcal);
+                                    let name = found.name.clone();
+                                    view! {
+                                        <li>
+                                            <button
+                                                type="button"
+                                                class="inline-flex w-full px-2 py-1 hover:bg-gray-100 dark:hover:bg-gray-600"
+                                                on:click=move |_| {
+                                                    food.set(Some(found.clone()));
+                                                    food_query.set(String::new());
+                                                }
+                                            >
+                                                {name}
+                                                <span class="ml-auto ps-2 text-xs text-gray-400">
+                                                    {kcal}
+                                                </span>
+                                            </button>
+                                        </li>
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                        }}
+                    </ul>
+                </Transition>
+                <button
+                    type="submit"
+                    class="w-full py-2 bg-blue-500 text-white font-semibold rounded-lg hover:bg-blue-600 transition"
+                >
+                    "Update Ingredient"
+                </button>
+            </form>
+            {move || {
+                update_entry_action
+                    .value()
+                    .get()
+                    .and_then(Result::err)
+                    .map(|e| view! { <p class="mt-2 text-sm text-red-600">{e.to_string()}</p> })
+            }}
+        </div>
+    }
+}
@@ -1,9 +1,15 @@
-use crate::api::day::upsert_day;
+use crate::api::day::{get_all_days_with_meals, upsert_day};
+use crate::app::RestrictionList;
 use crate::components::error_list;
-use crate::models::day::DayForm;
+use crate::components::forms::meal_filter::{use_meal_search, MealFilter};
+use crate::components::models::restriction::AllergenWarning;
+use crate::models::day::{Day, DayForm};
+use crate::models::day_meal::{Course, DayMeal};
 use crate::models::days_ingredients::DayWithMealAndIngredients;
-use crate::models::meal::Meal;
-use chrono::Datelike;
+use crate::models::meal::{all_tags, filter_meals, Meal, MealListing, MealOverview, MealQuery};
+use crate::models::member::Member;
+use crate::models::restriction::find_conflicts;
+use chrono::{Datelike, Local};
 use leptos::html::{Div, Input};
 use leptos::prelude::*;
 use leptos_use::{on_click_outside_with_options, OnClickOutsideOptions};
@@ -12,7 +18,7 @@ use leptos_use::{on_click_outside_with_options, OnClickOutsideOptions};
 pub fn DayForm(
     day: RwSignal<DayWithMealAndIngredients>,
     completed: WriteSignal<bool>,
-    meals: RwSignal<Vec<Meal>>,
+    meals: RwSignal<Vec<MealOverview>>,
     create_meal: RwSignal<bool>,
 ) -> impl IntoView {
     let date = Signal::derive(move || day.get().day.date);
@@ -23,7 +29,11 @@ pub fn DayForm(
         }
     });
     let meal_search = RwSignal::new(String::new());
-    let select_meal: RwSignal<Option<Meal>> = RwSignal::new(None);
+    let query = RwSignal::new(MealQuery::default());
+    let hits = use_meal_search(meal_search);
+    let dishes: RwSignal<Vec<(Meal, Course)>> = RwSignal::new(Vec::new());
+    // The course meals picked in the search are added as
+    let course = RwSignal::new(Course::Main);
     let search_active = RwSignal::new(false);
     let search_dropdown = NodeRef::<Div>::new();
     let _ = on_click_outside_with_options(
@@ -37,74 +47,167 @@ pub fn DayForm(
         OnClickOutsideOptions::default().ignore(["input"]),
     );
     Effect::watch(
-        move || day.get().meal,
-        move |meal, _, _| {
-            if let Some((meal, _)) = meal {
-                select_meal.set(Some(meal.clone()));
+        move || day.get().meals,
+        move |meals, _, _| {
+            dishes.set(meals.iter().map(|dish| (dish.meal.clone(), dish.course)).collect());
+            course.set(if meals.is_empty() { Course::Main } else { Course::Side });
+        },
+        true,
+    );
+    let leftovers_from = RwSignal::new(day.get_untracked().day.leftovers_from_day_id);
+    let days_resource = OnceResource::new(get_all_days_with_meals());
+    let cooked_days: RwSignal<Vec<(Day, Vec<Meal>)>> = RwSignal::new(Vec::new());
+    Effect::watch(
+        move || days_resource.get(),
+        move |r_days, _, _| {
+            if let Some(Ok(r_days)) = r_days {
+                cooked_days.set(r_days.clone());
             }
         },
         true,
     );
+    // Dinners of the week before that leftovers can be from
+    let leftover_options = move || {
+        let date = date.get();
+        let selected = leftovers_from.get();
+        cooked_days
+            .read()
+            .iter()
+            .filter(|(d, meals)| {
+                !meals.is_empty() && d.date < date && (date - d.date).num_days() <= 7
+            })
+            .map(|(d, meals)| {
+                let label = format!(
+                    "{} {:02}.{:02} - {}",
+                    d.date.weekday(),
+                    d.date.day(),
+                    d.date.month(),
+                    meals.iter().map(|m| m.name.as_str()).collect::<Vec<_>>().join(" + "),
+                );
+                view! {
+                    <option value=d.id.to_string() selected=selected == Some(d.id)>
+                        {label}
+                    </option>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    // Picking a meal again takes it off the day
+    let toggle_meal = move |meal: Meal| {
+        dishes.update(|dishes| {
+            if let Some(idx) = dishes.iter().position(|(m, _)| m.id == meal.id) {
+                dishes.remove(idx);
+            } else {
+                dishes.push((meal, course.get_untracked()));
+                if course.get_untracked() == Course::Main {
+                    course.set(Course::Side);
+                }
+            }
+        });
+    };
 
-    let meals_data = move || {
-        let meals_vec = meals.get();
-        let search_input = meal_search.get();
-        let filtered = if !search_input.is_empty() {
-            let search_input = search_input.to_lowercase();
-            meals_vec
+    let restrictions = use_context::<RestrictionList>().unwrap_or_default();
+    // Warn about the selected meals before they are saved
+    let conflicts = move || {
+        let dishes = dishes.read();
+        let meals = meals.read();
+        let day = day.read();
+        find_conflicts(
+            &restrictions.read(),
+            Member::ALL.into_iter().filter(|m| day.day.attends(*m)),
+            dishes
                 .iter()
-                .filter(|m| m.name.to_lowercase().contains(&search_input))
-                .cloned()
-                .collect::<Vec<_>>()
-        } else {
-            meals_vec.clone()
-        };
-        filtered
-            .iter()
-            .map(|meal| {
-                let meal = meal.clone();
+                .filter_map(|(meal, _)| meals.iter().find(|m| m.meal.id == meal.id))
+                .flat_map(|overview| overview.ingredients.iter()),
+        )
+    };
+    let course_options = move |selected: Course| {
+        Course::ALL
+            .into_iter()
+            .map(|c| {
                 view! {
-                    <li>
-                        <button
-                            type="button"
-                            class=format!(
-                                "inline-flex w-full px-4 py-2 hover:bg-gray-100 dark:hover:bg-gray-600 dark:hover:text-white {}",
-                                if let Some(s_meal) = select_meal.get() {
-                                    if meal.id == s_meal.id { "text-blue-500" } else { "" }
-                                } else {
-                                    ""
-                                },
-                            )
-                            on:click=move |_| {
-                                select_meal.set(Some(meal.clone()));
-                                search_active.set(false);
-                                meal_search.write().clear();
-                            }
-                        >
-                            {meal.name.clone()}
-                        </button>
-                    </li>
+                    <option value=c.as_str() selected=c == selected>
+                        {c.label()}
+                    </option>
                 }
             })
             .collect::<Vec<_>>()
     };
-    let add_day_action = Action::new(|day_form: &DayForm| {
-        let day_form = day_form.clone();
-        async move { upsert_day(day_form).await }
+
+    let meals_data = move || {
+        let today = Local::now().date_naive();
+        filter_meals(
+            &meals.read(),
+            hits.read().as_deref(),
+            &query.read(),
+            today,
+        )
+        .into_iter()
+        .map(|overview| {
+            // What the search found it by, or when it was last cooked
+            let note = hits
+                .read()
+                .iter()
+                .flatten()
+                .find(|hit| hit.meal_id == overview.meal.id)
+                .and_then(|hit| hit.matched.clone())
+                .or_else(|| {
+                    overview
+                        .last_cooked(today)
+                        .map(|date| date.format("%d.%m.%Y").to_string())
+                })
+                .unwrap_or_default();
+            let meal = overview.meal;
+            view! {
+                <li>
+                    <button
+                        type="button"
+                        class=format!(
+                            "inline-flex w-full px-4 py-2 hover:bg-gray-100 dark:hover:bg-gray-600 dark:hover:text-white {}",
+                            if dishes.read().iter().any(|(m, _)| m.id == meal.id) {
+                                "text-blue-500"
+                            } else {
+                                ""
+                            },
+                        )
+                        on:click=move |_| {
+                            toggle_meal(meal.clone());
+                            search_active.set(false);
+                            meal_search.write().clear();
+                        }
+                    >
+                        {meal.name.clone()}
+                        <span class="ml-auto ps-2 text-xs text-gray-400">{note}</span>
+                    </button>
+                </li>
+            }
+        })
+        .collect::<Vec<_>>()
+    };
+    let add_day_action = Action::new(|input: &(DayForm, Vec<DayMeal>)| {
+        let (day_form, meals) = input.clone();
+        async move { upsert_day(day_form, meals).await }
     });
 
     let on_submit = move |ev: leptos::ev::SubmitEvent| {
         ev.prevent_default();
-        if let Some(meal) = select_meal.get() {
-            let date = date.get();
-            let day_form = DayForm {
-                date,
-                meal_id: Some(meal.id),
-                week: date.iso_week().week() as i32,
-                year: date.year(),
-            };
-            add_day_action.dispatch(day_form);
-        }
+        let date = date.get();
+        let day_form = DayForm {
+            date,
+            week: date.iso_week().week() as i32,
+            year: date.year(),
+            leftovers_from_day_id: leftovers_from.get(),
+        };
+        let meals = dishes
+            .get()
+            .into_iter()
+            .map(|(meal, course)| DayMeal {
+                day_id: -1,
+                meal_id: meal.id,
+                course,
+            })
+            .collect();
+        add_day_action.dispatch((day_form, meals));
     };
 
     Effect::new(move || {
@@ -120,78 +223,151 @@ pub fn DayForm(
                 <h2 class="font-bold text-2xl mb-4 text-gray-900 dark:text-white text-center">
                     Update Day
                 </h2>
-                <h3 class=move || {
-                    format!(
-                        "font-bold text-xl mb-4 text-center {}",
-                        if select_meal.get().is_some() { "text-blue-500 " } else { "text-gray-500" },
-                    )
-                }>
+                <label class="flex items-center justify-center gap-2 text-sm text-gray-700 dark:text-gray-200">
+                    "Leftovers from"
+                    <select
+                        class="px-2 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 dark:bg-gray-800 dark:text-white"
+                        on:change:target=move |ev| {
+                            leftovers_from.set(ev.target().value().parse::<i32>().ok())
+                        }
+                    >
+                        <option value="" selected=move || leftovers_from.get().is_none()>
+                            "No, cooking"
+                        </option>
+                        {leftover_options}
+                    </select>
+                </label>
+                // The dishes of a leftovers day are the ones it was cooked with
+                <div class="space-y-6" class:hidden=move || leftovers_from.get().is_some()>
                     {move || {
-                        if let Some(meal) = select_meal.get() {
-                            meal.name
-                        } else {
-                            String::from("Select a meal...")
+                        if dishes.read().is_empty() {
+                            return view! {
+                                <h3 class="font-bold text-xl mb-4 text-center text-gray-500">
+                                    "Select meals..."
+                                </h3>
+                            }
+                                .into_any();
                         }
+                        view! {
+                            <ul class="space-y-2 w-80 mx-auto">
+                                {dishes
+                                    .get()
+                                    .into_iter()
+                                    .enumerate()
+                                    .map(|(idx, (meal, dish_course))| {
+                                        view! {
+                                            <li class="flex items-center gap-2">
+                                                <select
+                                                    class="px-2 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 dark:bg-gray-800 dark:text-white"
+                                                    on:change:target=move |ev| {
+                                                        if let Ok(course) = ev.target().value().parse::<Course>() {
+                                                            dishes.update(|dishes| dishes[idx].1 = course)
+                                                        }
+                                                    }
+                                                >
+                                                    {course_options(dish_course)}
+                                                </select>
+                                                <span class="flex-1 font-bold text-blue-500">
+                                                    {meal.name}
+                                                </span>
+                                                <button
+                                                    type="button"
+                                                    title="Remove meal"
+                                                    class="px-2 text-red-500 hover:text-red-600"
+                                                    on:click=move |_| {
+                                                        dishes
+                                                            .update(|dishes| {
+                                                                dishes.remove(idx);
+                                                            })
+                                                    }
+                                                >
+                                                    "✕"
+                                                </button>
+                                            </li>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()}
+                            </ul>
+                        }
+                            .into_any()
                     }}
-                </h3>
-                <div class="relative space-y-3 w-80 mx-auto" id="meal-search">
-                    <label
-                        for="default-search"
-                        class="mb-2 text-sm font-medium text-gray-900 sr-only dark:text-white"
-                    >
-                        Search
-                    </label>
-                    <div class="relative">
-                        <div class="absolute inset-y-0 start-0 flex items-center ps-3 pointer-events-none">
-                            <svg
-                                class="w-4 h-4 text-gray-500 dark:text-gray-400"
-                                aria-hidden="true"
-                                xmlns="http://www.w3.org/2000/svg"
-                                fill="none"
-                                viewBox="0 0 20 20"
+                    {move || view! { <AllergenWarning conflicts=conflicts() /> }}
+                    <div class="relative space-y-3 w-80 mx-auto" id="meal-search">
+                        <label class="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-200">
+                            "Add as"
+                            <select
+                                class="px-2 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 dark:bg-gray-800 dark:text-white"
+                                on:change:target=move |ev| {
+                                    if let Ok(picked) = ev.target().value().parse::<Course>() {
+                                        course.set(picked)
+                                    }
+                                }
                             >
-                                <path
-                                    stroke="currentColor"
-                                    stroke-linecap="round"
-                                    stroke-linejoin="round"
-                                    stroke-width="2"
-                                    d="m19 19-4-4m0-7A7 7 0 1 1 1 8a7 7 0 0 1 14 0Z"
-                                />
-                            </svg>
-                        </div>
-                        <input
-                            type="text"
-                            class="block w-full p-4 ps-10 text-sm text-gray-900 border border-gray-300 rounded-lg bg-gray-50 focus:ring-blue-500 focus:border-blue-500 dark:bg-gray-700 dark:border-gray-600 dark:placeholder-gray-400 dark:text-white dark:focus:ring-blue-500 dark:focus:border-blue-500"
-                            placeholder="Search meals..."
-                            prop:value=meal_search
-                            bind:value=meal_search
-                            on:focus=move |_| search_active.set(true)
-                            node_ref=input_ref
+                                {move || course_options(course.get())}
+                            </select>
+                        </label>
+                        <MealFilter
+                            tags=Signal::derive(move || all_tags(&meals.read()))
+                            query=query
                         />
-                    </div>
-                    <Show when=move || search_active.get() fallback=|| view! {}>
-                        <div
-                            class="absolute left-0 right-0 z-50 bg-white divide-y divide-gray-100 rounded-lg shadow-sm w-44 dark:bg-gray-700 overflow-y-auto h-50"
-                            node_ref=search_dropdown
+                        <label
+                            for="default-search"
+                            class="mb-2 text-sm font-medium text-gray-900 sr-only dark:text-white"
                         >
-                            <ul class="py-2 text-sm text-gray-700 dark:text-gray-200">
-                                <Transition fallback=move || {
-                                    view! { <span>"Loading..."</span> }
-                                }>
-                                    <ErrorBoundary fallback=error_list>{meals_data}</ErrorBoundary>
-                                </Transition>
-                            </ul>
+                            Search
+                        </label>
+                        <div class="relative">
+                            <div class="absolute inset-y-0 start-0 flex items-center ps-3 pointer-events-none">
+                                <svg
+                                    class="w-4 h-4 text-gray-500 dark:text-gray-400"
+                                    aria-hidden="true"
+                                    xmlns="http://www.w3.org/2000/svg"
+                                    fill="none"
+                                    viewBox="0 0 20 20"
+                                >
+                                    <path
+                                        stroke="currentColor"
+                                        stroke-linecap="round"
+                                        stroke-linejoin="round"
+                                        stroke-width="2"
+                                        d="m19 19-4-4m0-7A7 7 0 1 1 1 8a7 7 0 0 1 14 0Z"
+                                    />
+                                </svg>
+                            </div>
+                            <input
+                                type="text"
+                                class="block w-full p-4 ps-10 text-sm text-gray-900 border border-gray-300 rounded-lg bg-gray-50 focus:ring-blue-500 focus:border-blue-500 dark:bg-gray-700 dark:border-gray-600 dark:placeholder-gray-400 dark:text-white dark:focus:ring-blue-500 dark:focus:border-blue-500"
+                                placeholder="Search meals, ingredients or tags..."
+                                prop:value=meal_search
+                                bind:value=meal_search
+                                on:focus=move |_| search_active.set(true)
+                                node_ref=input_ref
+                            />
                         </div>
-                    </Show>
-                </div>
+                        <Show when=move || search_active.get() fallback=|| view! {}>
+                            <div
+                                class="absolute left-0 right-0 z-50 bg-white divide-y divide-gray-100 rounded-lg shadow-sm w-44 dark:bg-gray-700 overflow-y-auto h-50"
+                                node_ref=search_dropdown
+                            >
+                                <ul class="py-2 text-sm text-gray-700 dark:text-gray-200">
+                                    <Transition fallback=move || {
+                                        view! { <span>"Loading..."</span> }
+                                    }>
+                                        <ErrorBoundary fallback=error_list>{meals_data}</ErrorBoundary>
+                                    </Transition>
+                                </ul>
+                            </div>
+                        </Show>
+                    </div>
 
-                <button
-                    class="w-full py-2 bg-blue-100 text-blue-700 rounded-lg hover:bg-blue-200 transition mb-2 flex items-center justify-center font-semibold"
-                    on:click=move |_| { create_meal.set(true) }
-                    type="button"
-                >
-                    "+ Add Meal"
-                </button>
+                    <button
+                        class="w-full py-2 bg-blue-100 text-blue-700 rounded-lg hover:bg-blue-200 transition mb-2 flex items-center justify-center font-semibold"
+                        on:click=move |_| { create_meal.set(true) }
+                        type="button"
+                    >
+                        "+ Add Meal"
+                    </button>
+                </div>
                 <button
                     type="submit"
                     class="w-full py-2 bg-blue-500 text-white font-semibold rounded-lg hover:bg-blue-600 transition"
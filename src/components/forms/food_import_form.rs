@@ -0,0 +1,75 @@
+use crate::api::food::import_foods;
+use leptos::prelude::*;
+use web_sys::wasm_bindgen::JsCast;
+use web_sys::{FormData, HtmlFormElement, SubmitEvent};
+
+#[component]
+pub fn FoodImportForm() -> impl IntoView {
+    let import_action = Action::new_local(|data: &FormData| {
+        let data = data.clone();
+        async move { import_foods(data.into()).await }
+    });
+    let pending = import_action.pending();
+    let imported = import_action.value();
+
+    view! {
+        <div class="w-80 rounded-xl border border-gray-200 bg-white p-4 shadow-md">
+            <h3 class="text-lg font-semibold text-gray-900">"Import foods"</h3>
+            <p class="mt-1 text-sm text-gray-600">
+                "A food composition table, e.g. Matvaretabellen as JSON or CSV, for nutrition per meal."
+            </p>
+            <form
+                class="mt-3 space-y-3"
+                on:submit=move |ev: SubmitEvent| {
+                    ev.prevent_default();
+                    let target = ev.target().unwrap().unchecked_into::<HtmlFormElement>();
+                    let form_data = FormData::new_with_form(&target).unwrap();
+                    import_action.dispatch_local(form_data);
+                }
+            >
+                <input
+                    required
+                    type="file"
+                    name="foods"
+                    accept=".json,.csv,.txt,application/json,text/csv"
+                    class="block w-full text-sm text-gray-700
+                    file:mr-4 file:rounded-md file:border-0
+                    file:bg-gray-100 file:px-4 file:py-2
+                    file:text-sm file:font-medium
+                    file:text-gray-700
+                    hover:file:bg-gray-200
+                    focus:outline-none"
+                />
+                <button
+                    type="submit"
+                    class="w-full py-2 bg-blue-500 text-white font-semibold rounded-lg hover:bg-blue-600 transition disabled:cursor-not-allowed disabled:opacity-50"
+                    disabled=pending
+                >
+                    {move || if pending.get() { "Importing…" } else { "Import" }}
+                </button>
+            </form>
+            {move || {
+                imported
+                    .get()
+                    .map(|result| match result {
+                        Ok(import) => {
+                            view! {
+                                <p class="mt-2 text-sm text-green-600">
+                                    {format!(
+                                        "Imported {} foods and linked {} ingredients",
+                                        import.foods,
+                                        import.linked,
+                                    )}
+                                </p>
+                            }
+                                .into_any()
+                        }
+                        Err(e) => {
+                            view! { <p class="mt-2 text-sm text-red-600">{e.to_string()}</p> }
+                                .into_any()
+                        }
+                    })
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,124 @@
+use crate::api::meal::search_meals;
+use crate::models::meal::{MealQuery, MealSort};
+use crate::models::member::Member;
+use crate::models::search::MealHit;
+use leptos::prelude::*;
+use leptos_use::signal_debounced;
+
+/// Search results for what is typed in `query`, `None` while it is empty. Waits
+/// for a pause in typing, and keeps the last results until the next ones arrive.
+pub fn use_meal_search(query: RwSignal<String>) -> RwSignal<Option<Vec<MealHit>>> {
+    let debounced: Signal<String> = signal_debounced(query, 250.0);
+    let search = LocalResource::new(move || {
+        let query = debounced.get();
+        async move {
+            if query.trim().is_empty() {
+                Ok(vec![])
+            } else {
+                search_meals(query).await
+            }
+        }
+    });
+    let hits = RwSignal::new(None);
+    Effect::new(move || {
+        let found = search.get();
+        if query.read().trim().is_empty() {
+            hits.set(None);
+        } else if let Some(Ok(found)) = found {
+            if !debounced.read().trim().is_empty() {
+                hits.set(Some(found));
+            }
+        }
+    });
+    hits
+}
+
+/// Tag toggles, a favourites picker and a sort picker for lists of meals. A meal
+/// has to have every selected tag to be shown, see `filter_meals`.
+#[component]
+pub fn MealFilter(
+    #[prop(into)] tags: Signal<Vec<String>>,
+    query: RwSignal<MealQuery>,
+) -> impl IntoView {
+    let toggle = move |tag: String| {
+        query.update(|query| {
+            if let Some(idx) = query.tags.iter().position(|t| *t == tag) {
+                query.tags.remove(idx);
+            } else {
+                query.tags.push(tag);
+            }
+        })
+    };
+    let select_class = "text-xs text-gray-700 bg-transparent focus:outline-none dark:text-gray-200";
+
+    view! {
+        <div class="flex flex-wrap items-center gap-1 py-2">
+            {move || {
+                tags.get()
+                    .into_iter()
+                    .map(|tag| {
+                        let toggled = tag.clone();
+                        let active = query.read().tags.contains(&tag);
+                        view! {
+                            <button
+                                type="button"
+                                class=if active {
+                                    "px-2 py-0.5 rounded-full text-xs bg-blue-500 text-white"
+                                } else {
+                                    "px-2 py-0.5 rounded-full text-xs bg-gray-100 text-gray-700 dark:bg-gray-700 dark:text-gray-200"
+                                }
+                                on:click=move |_| toggle(toggled.clone())
+                            >
+                                {tag}
+                            </button>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+            <select
+                class=format!("ml-auto {select_class}")
+                on:change:target=move |ev| {
+                    let member = ev.target().value().parse::<Member>().ok();
+                    query.update(|query| query.favourite_of = member);
+                }
+            >
+                <option value="" selected=move || query.read().favourite_of.is_none()>
+                    "Everyone's meals"
+                </option>
+                {Member::ALL
+                    .into_iter()
+                    .map(|member| {
+                        view! {
+                            <option
+                                value=member.as_str()
+                                selected=move || query.read().favourite_of == Some(member)
+                            >
+                                {format!("{member}'s favourites")}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()}
+            </select>
+            <select
+                class=select_class
+                on:change:target=move |ev| {
+                    if let Ok(sort) = ev.target().value().parse() {
+                        query.update(|query| query.sort = sort);
+                    }
+                }
+            >
+                {MealSort::ALL
+                    .into_iter()
+                    .map(|value| {
+                        view! {
+                            <option value=value.as_str() selected=move || query.read().sort == value>
+                                {format!("Sort by {}", value.label().to_lowercase())}
+                            </option>
+                        }
+                    })
+                    .collect::<Vec<_>>()}
+            </select>
+        </div>
+    }
+}
+
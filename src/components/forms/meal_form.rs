@@ -1,19 +1,25 @@
-use crate::api::meal::{create_meal_with_ingredients, update_meal_with_ingredients};
+use crate::api::ingredient_catalog::get_ingredient_catalog;
+use crate::api::meal::{
+    create_meal_with_ingredients, get_meal_tags, update_meal_with_ingredients, upload_meal_image,
+};
 use crate::components::error_list;
 use crate::models::ingredient::IngredientForm;
 use crate::models::meal::{Meal, MealForm, MealWithIngredients};
-use leptos::html::Input;
+use crate::models::meal_tag::{parse_tags, SUGGESTED_TAGS};
+use leptos::html::{Form, Input};
 use leptos::prelude::*;
+use web_sys::FormData;
 
 #[component]
 pub fn UpdateMealForm(
     meal: RwSignal<MealWithIngredients>,
     completed: WriteSignal<bool>,
 ) -> impl IntoView {
-    let add_meal_action = Action::new(|input: &(Meal, Vec<IngredientForm>)| {
+    let add_meal_action = Action::new(|input: &(Meal, Vec<IngredientForm>, Vec<String>)| {
         let meal = input.0.clone();
         let ingredients = input.1.clone();
-        async move { update_meal_with_ingredients(meal, ingredients).await }
+        let tags = input.2.clone();
+        async move { update_meal_with_ingredients(meal, ingredients, tags).await }
     });
     Effect::new(move || {
         if let Some(Ok(new_meal)) = add_meal_action.value().get() {
@@ -22,17 +28,20 @@ pub fn UpdateMealForm(
         }
     });
     let on_cancel = move || completed.set(true);
-    let on_submit = move |meal_form: MealForm, ingredient_forms: Vec<IngredientForm>| {
-        add_meal_action.dispatch((
-            Meal {
-                id: meal.read().meal.id,
-                name: meal_form.name,
-                image: meal_form.image,
-                recipie_url: meal_form.recipie_url,
-            },
-            ingredient_forms,
-        ));
-    };
+    let on_submit =
+        move |meal_form: MealForm, ingredient_forms: Vec<IngredientForm>, tags: Vec<String>| {
+            add_meal_action.dispatch((
+                Meal {
+                    id: meal.read().meal.id,
+                    name: meal_form.name,
+                    image: meal_form.image,
+                    recipie_url: meal_form.recipie_url,
+                    servings: meal_form.servings,
+                },
+                ingredient_forms,
+                tags,
+            ));
+        };
     view! {
         <Transition fallback=move || {
             view! { <span>"Loading..."</span> }
@@ -54,10 +63,11 @@ pub fn CreateMealForm(
     meal: RwSignal<Option<MealWithIngredients>>,
     completed: WriteSignal<bool>,
 ) -> impl IntoView {
-    let add_meal_action = Action::new(|input: &(MealForm, Vec<IngredientForm>)| {
+    let add_meal_action = Action::new(|input: &(MealForm, Vec<IngredientForm>, Vec<String>)| {
         let meal_form = input.0.clone();
         let ingredients = input.1.clone();
-        async move { create_meal_with_ingredients(meal_form, ingredients).await }
+        let tags = input.2.clone();
+        async move { create_meal_with_ingredients(meal_form, ingredients, tags).await }
     });
 
     Effect::new(move || {
@@ -67,9 +77,10 @@ pub fn CreateMealForm(
         }
     });
 
-    let on_submit = move |meal_form: MealForm, ingredient_forms: Vec<IngredientForm>| {
-        add_meal_action.dispatch((meal_form, ingredient_forms));
-    };
+    let on_submit =
+        move |meal_form: MealForm, ingredient_forms: Vec<IngredientForm>, tags: Vec<String>| {
+            add_meal_action.dispatch((meal_form, ingredient_forms, tags));
+        };
     let on_cancel = move || completed.set(true);
     view! { <MealForm meal=None on_submit=on_submit on_cancel=on_cancel /> }
 }
@@ -81,7 +92,7 @@ pub fn MealForm<A, B>(
     on_cancel: B,
 ) -> impl IntoView
 where
-    A: Fn(MealForm, Vec<IngredientForm>) + 'static,
+    A: Fn(MealForm, Vec<IngredientForm>, Vec<String>) + 'static,
     B: Fn() + 'static,
 {
     let input_ref = NodeRef::<Input>::new();
@@ -93,11 +104,12 @@ where
         });
     }
     // Signals for meal fields
-    let (name, image, recipie_url, ingredients) = if let Some(meal) = meal.clone() {
+    let (name, image, recipie_url, servings, ingredients, tags) = if let Some(meal) = meal.clone() {
         (
             meal.meal.name,
             meal.meal.image,
             meal.meal.recipie_url.unwrap_or_default(),
+            meal.meal.servings,
             meal.ingredients
                 .iter()
                 .map(|ingredient| IngredientForm {
@@ -106,25 +118,88 @@ where
                     meal_id: ingredient.meal_id,
                 })
                 .collect(),
+            meal.tags.join(", "),
         )
     } else {
         (
             String::new(),
             String::new(),
             String::new(),
+            3,
             vec![IngredientForm {
                 name: String::from(""),
                 amount: 1,
                 meal_id: -1,
             }],
+            String::new(),
         )
     };
     let (name, set_name) = signal(name);
     let (image, set_image) = signal(image);
+    // The image file input belongs to its own form outside the meal form, as forms
+    // can't be nested, and is uploaded as soon as a picture is chosen
+    let upload_ref = NodeRef::<Form>::new();
+    let upload_action = Action::new_local(|data: &FormData| {
+        let data = data.clone();
+        async move { upload_meal_image(data.into()).await }
+    });
+    let uploading = upload_action.pending();
+    Effect::new(move || {
+        if let Some(Ok(url)) = upload_action.value().get() {
+            set_image(url);
+        }
+    });
+    let upload_image = move |_| {
+        if let Some(form) = upload_ref.get() {
+            if let Ok(data) = FormData::new_with_form(&form) {
+                upload_action.dispatch_local(data);
+            }
+        }
+    };
     let (recipie_url, set_recipie_url) = signal(recipie_url);
+    let servings = RwSignal::new(servings);
+    let tags = RwSignal::new(tags);
+    let tags_resource = OnceResource::new(get_meal_tags());
+    // Tags other meals use, and the suggested ones
+    let known_tags = move || {
+        let mut known: Vec<String> = SUGGESTED_TAGS.iter().map(|t| t.to_string()).collect();
+        for tag in tags_resource.get().and_then(Result::ok).unwrap_or_default() {
+            if !known.contains(&tag) {
+                known.push(tag);
+            }
+        }
+        known
+    };
+    let toggle_tag = move |tag: String| {
+        let mut current = parse_tags(&tags.get());
+        if let Some(idx) = current.iter().position(|t| *t == tag) {
+            current.remove(idx);
+        } else {
+            current.push(tag);
+        }
+        tags.set(current.join(", "));
+    };
 
     // Signals for dynamic ingredient fields
     let (ingredients, set_ingredients) = signal(ingredients);
+    let catalog_resource = OnceResource::new(get_ingredient_catalog());
+    let catalog = move || {
+        catalog_resource
+            .get()
+            .and_then(Result::ok)
+            .unwrap_or_default()
+    };
+    // Default unit of the catalog entry with this name, shown after the amount
+    let unit_for = move |name: &str| {
+        let name = name.trim().to_lowercase();
+        catalog()
+            .into_iter()
+            .find(|entry| {
+                entry.name.to_lowercase() == name
+                    || entry.alias_list().iter().any(|alias| alias.to_lowercase() == name)
+            })
+            .and_then(|entry| entry.unit)
+    };
 
     // Add new ingredient field
     let adding_ingredients = RwSignal::new(false);
@@ -165,10 +240,11 @@ where
             } else {
                 Some(recipie_url.get())
             },
+            servings: servings.get().max(1),
         };
         let ingredients_vec = ingredients.get();
         // Call your server function to save meal and ingredients here
-        on_submit(meal, ingredients_vec);
+        on_submit(meal, ingredients_vec, parse_tags(&tags.get()));
     };
 
     let action_name = {
@@ -226,6 +302,32 @@ where
                         class="w-full px-4 py-2 border rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-400 dark:bg-gray-800 dark:text-white"
                         prop:required=false
                     />
+                    <label class="block text-sm font-medium text-gray-700 dark:text-gray-200 text-left">
+                        {move || {
+                            if uploading.get() { "Uploading picture…" } else { "…or upload a picture" }
+                        }}
+                        <input
+                            type="file"
+                            name="image"
+                            form="meal-image-upload"
+                            accept="image/*"
+                            on:change=upload_image
+                            class="block w-full mt-1 text-sm text-gray-700 dark:text-gray-200
+                            file:mr-4 file:rounded-md file:border-0
+                            file:bg-gray-100 file:px-4 file:py-2
+                            file:text-sm file:font-medium
+                            file:text-gray-700
+                            hover:file:bg-gray-200
+                            focus:outline-none"
+                        />
+                    </label>
+                    {move || {
+                        upload_action
+                            .value()
+                            .get()
+                            .and_then(Result::err)
+                            .map(|e| view! { <p class="text-sm text-red-600">{e.to_string()}</p> })
+                    }}
                     <input
                         type="text"
                         placeholder="Recipe URL"
@@ -233,11 +335,79 @@ where
                         on:input=move |ev| set_recipie_url(event_target_value(&ev))
                         class="w-full px-4 py-2 border rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-400 dark:bg-gray-800 dark:text-white"
                     />
+                    <label class="flex items-center gap-2 text-sm text-gray-700 dark:text-gray-200">
+                        "Servings"
+                        <input
+                            type="number"
+                            min="1"
+                            prop:value=move || servings.get().to_string()
+                            on:input=move |ev| {
+                                if let Ok(value) = event_target_value(&ev).parse::<i32>() {
+                                    servings.set(value)
+                                }
+                            }
+                            class="w-20 px-3 py-1 border rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-400 dark:bg-gray-800 dark:text-white"
+                        />
+                    </label>
+                    <input
+                        type="text"
+                        placeholder="Tags, comma separated"
+                        bind:value=tags
+                        class="w-full px-4 py-2 border rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-400 dark:bg-gray-800 dark:text-white"
+                    />
+                    <Transition fallback=|| view! {}>
+                        <div class="flex flex-wrap gap-1">
+                            {move || {
+                                let current = parse_tags(&tags.get());
+                                known_tags()
+                                    .into_iter()
+                                    .map(|tag| {
+                                        let active = current.contains(&tag);
+                                        let toggled = tag.clone();
+                                        view! {
+                                            <button
+                                                type="button"
+                                                class=if active {
+                                                    "px-2 py-0.5 rounded-full text-xs bg-blue-500 text-white"
+                                                } else {
+                                                    "px-2 py-0.5 rounded-full text-xs bg-gray-100 text-gray-700 dark:bg-gray-700 dark:text-gray-200"
+                                                }
+                                                on:click=move |_| toggle_tag(toggled.clone())
+                                            >
+                                                {tag}
+                                            </button>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()
+                            }}
+                        </div>
+                    </Transition>
                 </div>
 
                 <h3 class="font-semibold text-lg mb-2 text-gray-900 dark:text-white">
                     Ingredients
                 </h3>
+                <Transition fallback=|| view! {}>
+                    <datalist id="ingredient-catalog">
+                        {move || {
+                            catalog()
+                                .into_iter()
+                                .flat_map(|entry| {
+                                    let aliases = entry
+                                        .alias_list()
+                                        .into_iter()
+                                        .map(|alias| (entry.name.clone(), Some(alias.to_owned())))
+                                        .collect::<Vec<_>>();
+                                    std::iter::once((entry.name.clone(), None))
+                                        .chain(aliases)
+                                })
+                                .map(|(name, alias)| {
+                                    view! { <option value=name label=alias></option> }
+                                })
+                                .collect::<Vec<_>>()
+                        }}
+                    </datalist>
+                </Transition>
                 <div class="space-y-3">
                     {move || {
                         ingredients
@@ -287,6 +457,7 @@ where
                                         <input
                                             type="text"
                                             placeholder="Ingredient name"
+                                            list="ingredient-catalog"
                                             prop:value=ing.name.clone()
                                             on:input:target=move |ev| {
                                                 set_ingredients
@@ -321,6 +492,14 @@ where
                                             <span class="px-3 py-2 border rounded-lg bg-white dark:bg-gray-700 dark:text-white min-w-[2.5rem] text-center font-semibold text-lg">
                                                 {ing.amount}
                                             </span>
+                                            {unit_for(&ing.name)
+                                                .map(|unit| {
+                                                    view! {
+                                                        <span class="text-sm text-gray-500 dark:text-gray-400">
+                                                            {unit}
+                                                        </span>
+                                                    }
+                                                })}
                                             <button
                                                 type="button"
                                                 class="w-8 h-8 flex items-center justify-center rounded-full border border-gray-300 bg-white text-blue-600 text-lg font-bold shadow hover:bg-blue-100 hover:text-blue-800 transition"
@@ -354,6 +533,7 @@ where
                     {action_name}
                 </button>
             </form>
+            <form id="meal-image-upload" node_ref=upload_ref></form>
         </div>
     }
 }
@@ -1,5 +1,10 @@
 pub mod day_form;
 pub mod meal_form;
+pub mod meal_filter;
 pub mod extra_item_form;
 pub mod receipt_upload_form;
 pub mod receipt_form;
+pub mod store_form;
+pub mod catalog_form;
+pub mod food_import_form;
+pub mod restriction_form;
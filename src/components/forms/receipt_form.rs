@@ -1,27 +1,27 @@
 use crate::api::day::get_all_days_with_meals;
-use crate::api::receipt::create_receipt_with_items;
+use crate::api::receipt::{create_receipt_with_items, CreateReceiptResult};
+use crate::api::product::get_product_names;
+use crate::api::store::get_stores;
 use crate::components::modal::Modal;
 use crate::models::day::Day;
 use crate::models::meal::Meal;
-use crate::models::receipt::{ReceiptForm, ReceiptItemForm, ReceiptWithItems};
+use crate::models::member::Member;
+use crate::models::money::Money;
+use crate::models::ocr_job::ScannedItem;
+use crate::models::receipt::{
+    attach_to_products, member_sums, split_shares, ItemKind, ReceiptForm, ReceiptItemForm,
+    ReceiptWithItems, SplitMode,
+};
+use crate::models::store::StoreForm;
 use chrono::Datelike;
 use leptos::prelude::*;
 
 #[component]
-fn DayPicker(matched_days: RwSignal<Vec<i32>>, open_modal: WriteSignal<bool>) -> impl IntoView {
-    let days_resource = OnceResource::new(get_all_days_with_meals());
-    let days_with_meals: RwSignal<Vec<(Day, Option<Meal>)>> = RwSignal::new(Vec::new());
-
-    Effect::watch(
-        move || days_resource.get(),
-        move |r_days, _, _| {
-            if let Some(Ok(r_days)) = r_days {
-                days_with_meals.set(r_days.clone());
-            }
-        },
-        true,
-    );
-
+fn DayPicker(
+    days_with_meals: RwSignal<Vec<(Day, Vec<Meal>)>>,
+    matched_days: RwSignal<Vec<i32>>,
+    open_modal: WriteSignal<bool>,
+) -> impl IntoView {
     view! {
         <div class="bg-white rounded-lg border border-gray-200 p-4">
             <h3 class="text-lg font-semibold text-gray-900 mb-4">"Select Days for Receipt"</h3>
@@ -30,7 +30,7 @@ fn DayPicker(matched_days: RwSignal<Vec<i32>>, open_modal: WriteSignal<bool>) ->
                     days_with_meals
                         .read()
                         .iter()
-                        .map(|(day, meal)| {
+                        .map(|(day, meals)| {
                             let is_selected = matched_days.read().contains(&day.id);
                             let bg_class = if is_selected {
                                 "bg-blue-100 border-blue-400"
@@ -43,7 +43,14 @@ fn DayPicker(matched_days: RwSignal<Vec<i32>>, open_modal: WriteSignal<bool>) ->
                                 day.date.day(),
                                 day.date.month(),
                             );
-                            let meal_name = meal.as_ref().map(|m| m.name.clone());
+                            let meal_name = (!meals.is_empty())
+                                .then(|| {
+                                    meals
+                                        .iter()
+                                        .map(|m| m.name.as_str())
+                                        .collect::<Vec<_>>()
+                                        .join(" + ")
+                                });
                             let day_id = day.id;
                             view! {
                                 <div
@@ -102,121 +109,136 @@ pub fn ReceiptForm(
     receipt_editing: WriteSignal<bool>,
     receipt_form: ReceiptForm,
     receipt_items_forms: Vec<ReceiptItemForm>,
+    /// What OCR saw for each item, kept in step with the items
+    mut item_scans: Vec<ScannedItem>,
+    /// The store the scan found, added when the receipt is saved unless a known
+    /// store is picked
+    new_store: Option<StoreForm>,
 ) -> impl IntoView {
+    let other_store = if new_store.is_some() {
+        "New store, added with the receipt"
+    } else {
+        "Other store"
+    };
     let add_receipt_action = Action::new(
-        |input: &(ReceiptForm, Vec<ReceiptItemForm>, Option<Vec<i32>>)| {
-            let receipt_form = input.0.clone();
-            let receipt_items_forms = input.1.clone();
-            let matched_days = input.2.clone();
+        move |input: &(ReceiptForm, Vec<ReceiptItemForm>, Option<Vec<i32>>, bool)| {
+            let (receipt_form, receipt_items_forms, matched_days, force) = input.clone();
+            let new_store = new_store.clone();
             async move {
-                create_receipt_with_items(receipt_form, receipt_items_forms, matched_days).await
+                create_receipt_with_items(
+                    receipt_form,
+                    receipt_items_forms,
+                    matched_days,
+                    new_store,
+                    force,
+                )
+                .await
             }
         },
     );
+    let duplicates: RwSignal<Option<Vec<ReceiptWithItems>>> = RwSignal::new(None);
 
-    Effect::new(move || {
-        if let Some(Ok(new_receipt)) = add_receipt_action.value().get() {
+    Effect::new(move || match add_receipt_action.value().get() {
+        Some(Ok(CreateReceiptResult::Created(new_receipt))) => {
             receipt.set(Some(new_receipt));
             receipt_editing.set(false)
         }
+        Some(Ok(CreateReceiptResult::Duplicate(found))) => duplicates.set(Some(found)),
+        _ => {}
     });
 
     let on_submit = move |receipt_form: ReceiptForm,
                           receipt_items_forms: Vec<ReceiptItemForm>,
-                          matched_days: Option<Vec<i32>>| {
-        add_receipt_action.dispatch((receipt_form, receipt_items_forms, matched_days));
+                          matched_days: Option<Vec<i32>>,
+                          force: bool| {
+        duplicates.set(None);
+        add_receipt_action.dispatch((receipt_form, receipt_items_forms, matched_days, force));
     };
     let on_cancel = move || receipt_editing.set(false);
 
-    let total = receipt_items_forms.iter().map(|i| i.price).sum::<f32>();
-    let anders_total = receipt_items_forms
-        .iter()
-        .filter(|i| i.anders_pay)
-        .map(|i| i.price / (i.anders_pay as u8 + i.andreas_pay as u8 + i.ac_pay as u8) as f32)
-        .sum::<f32>();
-    let andreas_total = receipt_items_forms
-        .iter()
-        .filter(|i| i.andreas_pay)
-        .map(|i| i.price / (i.anders_pay as u8 + i.andreas_pay as u8 + i.ac_pay as u8) as f32)
-        .sum::<f32>();
-    let ac_total = receipt_items_forms
-        .iter()
-        .filter(|i| i.ac_pay)
-        .map(|i| i.price / (i.anders_pay as u8 + i.andreas_pay as u8 + i.ac_pay as u8) as f32)
-        .sum::<f32>();
-
+    let shares = RwSignal::new(receipt_form.shares());
+    let split_mode = RwSignal::new(receipt_form.split_mode);
     let (store, set_store) = signal(receipt_form.store);
-    let (total, set_total) = signal(total);
-    let (anders_total, set_anders_total) = signal(anders_total);
-    let (andreas_total, set_andreas_total) = signal(andreas_total);
-    let (ac_total, set_ac_total) = signal(ac_total);
+    let store_id = RwSignal::new(receipt_form.store_id);
+    let stores_resource = OnceResource::new(get_stores());
+    let product_names_resource = OnceResource::new(get_product_names());
+    item_scans.resize(receipt_items_forms.len(), ScannedItem::default());
     let (items, set_items) = signal(receipt_items_forms);
+    let (scans, set_scans) = signal(item_scans);
     let matched_days: RwSignal<Vec<i32>> = RwSignal::new(Vec::new());
     let (show_day_picker, set_show_day_picker) = signal(false);
 
+    let days_resource = OnceResource::new(get_all_days_with_meals());
+    let days_with_meals: RwSignal<Vec<(Day, Vec<Meal>)>> = RwSignal::new(Vec::new());
     Effect::watch(
-        move || items.get(),
-        move |items, _, _| {
-            set_total.set(items.iter().map(|i| i.price).sum());
-            set_anders_total.set(
-                items
-                    .iter()
-                    .filter(|i| i.anders_pay)
-                    .map(|i| {
-                        i.price / (i.anders_pay as u8 + i.andreas_pay as u8 + i.ac_pay as u8) as f32
-                    })
-                    .sum(),
-            );
-            set_andreas_total.set(
-                items
-                    .iter()
-                    .filter(|i| i.andreas_pay)
-                    .map(|i| {
-                        i.price / (i.anders_pay as u8 + i.andreas_pay as u8 + i.ac_pay as u8) as f32
-                    })
-                    .sum(),
-            );
-            set_ac_total.set(
-                items
-                    .iter()
-                    .filter(|i| i.ac_pay)
-                    .map(|i| {
-                        i.price / (i.anders_pay as u8 + i.andreas_pay as u8 + i.ac_pay as u8) as f32
-                    })
-                    .sum(),
-            );
+        move || days_resource.get(),
+        move |r_days, _, _| {
+            if let Some(Ok(r_days)) = r_days {
+                days_with_meals.set(r_days.clone());
+            }
         },
-        false,
+        true,
     );
 
+    let total = move || items.read().iter().map(|i| i.price).sum::<Money>();
+    let sums = Memo::new(move |_| {
+        let matched = matched_days.read();
+        let days: Vec<Day> = days_with_meals
+            .read()
+            .iter()
+            .filter(|(day, _)| matched.contains(&day.id))
+            .map(|(day, _)| day.clone())
+            .collect();
+        member_sums(
+            attach_to_products(items.read().iter().map(|i| (i.kind, i.price, i.shares()))),
+            split_shares(split_mode.get(), shares.get(), &days),
+        )
+    });
+
     let add_item = move |_| {
         set_items.update(|ings| {
             ings.push(ReceiptItemForm {
                 receipt_id: 0,
                 name: String::from(""),
-                price: 0f32,
-                andreas_pay: true,
-                anders_pay: true,
-                ac_pay: true,
+                price: Money::ZERO,
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                kind: ItemKind::Product,
+                category_id: None,
+                raw_name: None,
+                product_id: None,
             });
         });
+        set_scans.update(|scans| scans.push(ScannedItem::default()));
     };
 
-    let form_submit = move |ev: leptos::ev::SubmitEvent| {
-        ev.prevent_default();
+    let image_hash = receipt_form.image_hash;
+    let submit = move |force: bool| {
+        let [anders_share, andreas_share, ac_share] = shares.get();
         let receipt = ReceiptForm {
             store: store.get(),
             datetime: receipt_form.datetime,
+            anders_share,
+            andreas_share,
+            ac_share,
+            split_mode: split_mode.get(),
+            image_hash,
+            store_id: store_id.get(),
         };
         // Call your server function to save meal and ingredients here
         let days = matched_days.get();
         let days_to_send = if days.is_empty() { None } else { Some(days) };
-        on_submit(receipt, items.get(), days_to_send);
+        on_submit(receipt, items.get(), days_to_send, force);
+    };
+    let form_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        submit(false);
     };
 
     view! {
         <Modal show=Signal::derive(show_day_picker)>
-            <DayPicker matched_days open_modal=set_show_day_picker />
+            <DayPicker days_with_meals matched_days open_modal=set_show_day_picker />
         </Modal>
         <div class="max-w-lg mx-auto my-6 rounded-xl border border-gray-200 bg-white p-6 shadow-md">
             // <!-- Receipt Header -->
@@ -233,44 +255,146 @@ pub fn ReceiptForm(
                         {format!("{}", receipt_form.datetime.format("%Y-%m-%d %H:%M"))}
                     </span>
                 </div>
+                <Transition fallback=|| view! {}>
+                    {move || {
+                        stores_resource
+                            .get()
+                            .and_then(Result::ok)
+                            .map(|stores| {
+                                let options = stores
+                                    .iter()
+                                    .map(|s| {
+                                        view! {
+                                            <option
+                                                value=s.id
+                                                selected=store_id.get_untracked() == Some(s.id)
+                                            >
+                                                {s.display_name()}
+                                            </option>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>();
+                                view! {
+                                    <select
+                                        class="mb-2 px-2 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 w-full"
+                                        on:change:target=move |ev| {
+                                            let id = ev.target().value().parse::<i32>().ok();
+                                            store_id.set(id);
+                                            if let Some(store) = stores.iter().find(|s| Some(s.id) == id) {
+                                                set_store(store.display_name());
+                                            }
+                                        }
+                                    >
+                                        <option value="">{other_store}</option>
+                                        {options}
+                                    </select>
+                                }
+                            })
+                    }}
+                </Transition>
 
                 <div class="grid grid-cols-3 gap-2 mb-2">
                     <div class="bg-blue-50 rounded-lg p-3 text-center border border-blue-200">
                         <div class="text-2xl font-bold text-blue-900">
-                            {move || format!("{:.2}", anders_total.get()).replace(".", ",")}
+                            {move || sums.get()[Member::Anders.index()].format_amount()}
                         </div>
                         <div class="text-xs font-semibold text-blue-700">"Anders"</div>
                     </div>
                     <div class="bg-green-50 rounded-lg p-3 text-center border border-green-200">
                         <div class="text-2xl font-bold text-green-900">
-                            {move || format!("{:.2}", andreas_total.get()).replace(".", ",")}
+                            {move || sums.get()[Member::Andreas.index()].format_amount()}
                         </div>
                         <div class="text-xs font-semibold text-green-700">"Andreas"</div>
                     </div>
                     <div class="bg-purple-50 rounded-lg p-3 text-center border border-purple-200">
                         <div class="text-2xl font-bold text-purple-900">
-                            {move || format!("{:.2}", ac_total.get()).replace(".", ",")}
+                            {move || sums.get()[Member::Ac.index()].format_amount()}
                         </div>
                         <div class="text-xs font-semibold text-purple-700">"AC"</div>
                     </div>
                 </div>
 
+                // <!-- Receipt wide weights, multiplied with each item's shares -->
+                <div class="grid grid-cols-3 gap-2 mb-2">
+                    {Member::ALL
+                        .into_iter()
+                        .map(|member| {
+                            view! {
+                                <label class="flex items-center justify-center gap-1 text-xs text-gray-600">
+                                    {format!("{} share", member.name())}
+                                    <input
+                                        type="number"
+                                        min="0"
+                                        step="1"
+                                        prop:value=move || shares.get()[member.index()]
+                                        class="w-12 px-1 py-0.5 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500"
+                                        on:input:target=move |ev| {
+                                            if let Ok(share) = ev.target().value().parse::<i32>() {
+                                                shares.update(|s| s[member.index()] = share.max(0));
+                                            }
+                                        }
+                                    />
+                                </label>
+                            }
+                        })
+                        .collect::<Vec<_>>()}
+                </div>
+
+                // <!-- Split mode toggle -->
+                <div class="grid grid-cols-2 gap-2 mb-2">
+                    {[(SplitMode::Equal, "Equal"), (SplitMode::Attendance, "By attendance")]
+                        .into_iter()
+                        .map(|(mode, label)| {
+                            view! {
+                                <button
+                                    type="button"
+                                    class=move || {
+                                        if split_mode.get() == mode {
+                                            "py-1 text-sm font-semibold rounded bg-blue-500 text-white transition"
+                                        } else {
+                                            "py-1 text-sm font-semibold rounded bg-gray-100 text-gray-700 hover:bg-gray-200 transition"
+                                        }
+                                    }
+                                    on:click=move |_| split_mode.set(mode)
+                                >
+                                    {label}
+                                </button>
+                            }
+                        })
+                        .collect::<Vec<_>>()}
+                </div>
+
                 <div class="text-center text-sm text-gray-500 pb-2 border-b">
                     "Total: "
                     <span class="font-semibold text-gray-700">
-                        {move || format!("{:.2},-", total.get())}
+                        {move || total().to_string()}
                     </span>
                 </div>
             </div>
 
             // <!-- Items List -->
             <form on:submit=form_submit class="space-y-2">
+                // <!-- Renamed items are remembered for the next scan -->
+                <Transition fallback=|| view! {}>
+                    <datalist id="product-names">
+                        {move || {
+                            product_names_resource
+                                .get()
+                                .and_then(Result::ok)
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|name| view! { <option value=name></option> })
+                                .collect::<Vec<_>>()
+                        }}
+                    </datalist>
+                </Transition>
                 <div class="space-y-1">
                     <div
                         class="grid gap-1 text-xs font-semibold text-gray-600 mb-1"
-                        style="grid-template-columns: 1fr 50px 30px 30px 30px 24px;"
+                        style="grid-template-columns: 1fr 44px 50px 34px 34px 34px 24px;"
                     >
                         <div>"Item"</div>
+                        <div>"Kind"</div>
                         <div>"Price"</div>
                         <div class="text-center">"An"</div>
                         <div class="text-center">"As"</div>
@@ -283,95 +407,154 @@ pub fn ReceiptForm(
                             .iter()
                             .enumerate()
                             .map(|(i, item)| {
-                                let price = format!("{:.2}", item.price);
+                                let price = item.price.to_decimal_string();
+                                let scan = scans.read().get(i).cloned().unwrap_or_default();
                                 view! {
-                                    <div
-                                        class="grid gap-1 items-center border-b border-gray-200 py-1"
-                                        style="grid-template-columns: 1fr 50px 30px 30px 30px 24px;"
-                                    >
-                                        <input
-                                            required
-                                            placeholder="Item"
-                                            prop:value=item.name.clone()
-                                            type="text"
-                                            class="px-1 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 min-w-0"
-                                            on:input:target=move |ev| {
-                                                set_items
-                                                    .update(|items| items[i].name = ev.target().value())
-                                            }
-                                        />
-                                        <input
-                                            required
-                                            value=price
-                                            type="number"
-                                            step="0.01"
-                                            class="px-1 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 w-full"
-                                            on:input:target=move |ev| {
-                                                if let Ok(price) = ev.target().value().parse::<f32>() {
-                                                    set_items.update(|items| items[i].price = price)
-                                                }
-                                            }
-                                        />
-                                        <div class="flex justify-center">
+                                    <div class=if scan.flagged {
+                                        "border-b border-gray-200 py-1 bg-yellow-50"
+                                    } else {
+                                        "border-b border-gray-200 py-1"
+                                    }>
+                                        <div
+                                            class="grid gap-1 items-center"
+                                            style="grid-template-columns: 1fr 44px 50px 34px 34px 34px 24px;"
+                                        >
                                             <input
-                                                type="checkbox"
-                                                checked=item.anders_pay
-                                                class="w-4 h-4 cursor-pointer"
+                                                required
+                                                placeholder="Item"
+                                                prop:value=item.name.clone()
+                                                type="text"
+                                                list="product-names"
+                                                title=scan
+                                                    .confidence
+                                                    .map(|c| format!("Read with {:.0}% confidence", c * 100.0))
+                                                class=if scan.flagged {
+                                                    "px-1 py-1 text-sm border border-yellow-400 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 min-w-0"
+                                                } else {
+                                                    "px-1 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 min-w-0"
+                                                }
                                                 on:input:target=move |ev| {
                                                     set_items
-                                                        .update(|items| items[i].anders_pay = ev.target().checked())
+                                                        .update(|items| items[i].name = ev.target().value())
                                                 }
                                             />
-                                        </div>
-                                        <div class="flex justify-center">
+                                            <select
+                                                title="Discounts and deposits are split like the item above"
+                                                class="px-0 py-1 text-xs border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 w-full"
+                                                on:change:target=move |ev| {
+                                                    if let Ok(kind) = ev.target().value().parse::<ItemKind>() {
+                                                        set_items.update(|items| items[i].kind = kind)
+                                                    }
+                                                }
+                                            >
+                                                {ItemKind::ALL
+                                                    .into_iter()
+                                                    .map(|kind| {
+                                                        view! {
+                                                            <option value=kind.as_str() selected=item.kind == kind>
+                                                                {kind.label()}
+                                                            </option>
+                                                        }
+                                                    })
+                                                    .collect::<Vec<_>>()}
+                                            </select>
                                             <input
-                                                type="checkbox"
-                                                checked=item.andreas_pay
-                                                class="w-4 h-4 cursor-pointer"
+                                                required
+                                                value=price
+                                                type="number"
+                                                step="0.01"
+                                                class="px-1 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 w-full"
                                                 on:input:target=move |ev| {
-                                                    set_items
-                                                        .update(|items| {
-                                                            items[i].andreas_pay = ev.target().checked();
-                                                        })
+                                                    if let Ok(price) = ev.target().value().parse::<Money>() {
+                                                        set_items.update(|items| items[i].price = price)
+                                                    }
                                                 }
                                             />
-                                        </div>
-                                        <div class="flex justify-center">
-                                            <input
-                                                type="checkbox"
-                                                checked=item.ac_pay
-                                                class="w-4 h-4 cursor-pointer"
-                                                on:input:target=move |ev| {
+                                            {Member::ALL
+                                                .into_iter()
+                                                .map(|member| {
+                                                    view! {
+                                                        <input
+                                                            type="number"
+                                                            min="0"
+                                                            step="1"
+                                                            value=item.shares()[member.index()]
+                                                            disabled=item.kind.follows_product()
+                                                            class="px-0.5 py-1 text-sm text-center border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 w-full disabled:bg-gray-100 disabled:text-gray-400"
+                                                            on:input:target=move |ev| {
+                                                                if let Ok(share) = ev.target().value().parse::<i32>() {
+                                                                    set_items
+                                                                        .update(|items| {
+                                                                            let share = share.max(0);
+                                                                            match member {
+                                                                                Member::Anders => items[i].anders_share = share,
+                                                                                Member::Andreas => items[i].andreas_share = share,
+                                                                                Member::Ac => items[i].ac_share = share,
+                                                                            }
+                                                                        })
+                                                                }
+                                                            }
+                                                        />
+                                                    }
+                                                })
+                                                .collect::<Vec<_>>()}
+                                            <button
+                                                type="button"
+                                                class="text-red-500 hover:text-red-700 hover:bg-red-50 rounded p-0.5 transition"
+                                                on:click=move |_| {
                                                     set_items
-                                                        .update(|items| items[i].ac_pay = ev.target().checked())
+                                                        .update(|items| {
+                                                            items.remove(i);
+                                                        });
+                                                    set_scans
+                                                        .update(|scans| {
+                                                            scans.remove(i);
+                                                        });
                                                 }
-                                            />
+                                            >
+                                                <svg
+                                                    xmlns="http://www.w3.org/2000/svg"
+                                                    fill="none"
+                                                    viewBox="0 0 24 24"
+                                                    stroke-width="1.5"
+                                                    stroke="currentColor"
+                                                    class="w-5 h-5"
+                                                >
+                                                    <path
+                                                        stroke-linecap="round"
+                                                        stroke-linejoin="round"
+                                                        d="M6 18 18 6M6 6l12 12"
+                                                    />
+                                                </svg>
+                                            </button>
                                         </div>
-                                        <button
-                                            type="button"
-                                            class="text-red-500 hover:text-red-700 hover:bg-red-50 rounded p-0.5 transition"
-                                            on:click=move |_| {
-                                                set_items
-                                                    .update(|items| {
-                                                        items.remove(i);
-                                                    });
+                                        {scan.flagged.then(|| {
+                                            view! {
+                                                <p class="text-xs text-yellow-800 mt-1">
+                                                    "Hard to read, check it against the receipt"
+                                                </p>
                                             }
-                                        >
-                                            <svg
-                                                xmlns="http://www.w3.org/2000/svg"
-                                                fill="none"
-                                                viewBox="0 0 24 24"
-                                                stroke-width="1.5"
-                                                stroke="currentColor"
-                                                class="w-5 h-5"
-                                            >
-                                                <path
-                                                    stroke-linecap="round"
-                                                    stroke-linejoin="round"
-                                                    d="M6 18 18 6M6 6l12 12"
+                                        })}
+                                        {item
+                                            .raw_name
+                                            .clone()
+                                            .filter(|raw_name| !raw_name.eq_ignore_ascii_case(&item.name))
+                                            .map(|raw_name| {
+                                                view! {
+                                                    <p class="text-xs text-gray-500 mt-1">
+                                                        {format!("Read as {raw_name}")}
+                                                    </p>
+                                                }
+                                            })}
+                                        {scan.image.map(|image| {
+                                            view! {
+                                                <img
+                                                    src=image
+                                                    alt="The line on the receipt"
+                                                    class="mt-1 max-h-10 max-w-full rounded border border-gray-200"
                                                 />
-                                            </svg>
-                                        </button>
+                                            }
+                                        })}
                                     </div>
                                 }
                             })
@@ -379,6 +562,44 @@ pub fn ReceiptForm(
                     }}
                 </div>
 
+                {move || {
+                    duplicates
+                        .get()
+                        .map(|found| {
+                            view! {
+                                <div class="p-3 rounded-lg border border-yellow-300 bg-yellow-50 text-sm text-yellow-900">
+                                    <p class="font-semibold">
+                                        "This looks like a receipt that is already saved:"
+                                    </p>
+                                    <ul class="list-disc list-inside my-1">
+                                        {found
+                                            .into_iter()
+                                            .map(|r| {
+                                                view! {
+                                                    <li>
+                                                        {format!(
+                                                            "{} {} - {}",
+                                                            r.receipt.store,
+                                                            r.receipt.datetime.format("%Y-%m-%d %H:%M"),
+                                                            r.total(),
+                                                        )}
+                                                    </li>
+                                                }
+                                            })
+                                            .collect::<Vec<_>>()}
+                                    </ul>
+                                    <button
+                                        type="button"
+                                        class="w-full py-2 text-sm bg-yellow-500 text-white font-semibold rounded hover:bg-yellow-600 transition"
+                                        on:click=move |_| submit(true)
+                                    >
+                                        "Save anyway"
+                                    </button>
+                                </div>
+                            }
+                        })
+                }}
+
                 <div class="space-y-1 pt-2">
                     <button
                         type="button"
@@ -1,5 +1,8 @@
-use crate::api::receipt::scan_receipt;
+use crate::api::receipt::{get_ocr_job, scan_receipt};
+use crate::app::OcrJobUpdateMap;
+use crate::models::ocr_job::{OcrJobStatus, ScannedItem};
 use crate::models::receipt::{ReceiptForm, ReceiptItemForm};
+use crate::models::store::StoreForm;
 use leptos::prelude::*;
 use web_sys::wasm_bindgen::JsCast;
 use web_sys::{FormData, HtmlFormElement, SubmitEvent};
@@ -8,6 +11,8 @@ use web_sys::{FormData, HtmlFormElement, SubmitEvent};
 pub fn ReceiptUpload(
     receipt_form: RwSignal<Option<ReceiptForm>>,
     receipt_items_forms: RwSignal<Option<Vec<ReceiptItemForm>>>,
+    item_scans: RwSignal<Vec<ScannedItem>>,
+    new_store: RwSignal<Option<StoreForm>>,
     receipt_editing: WriteSignal<bool>,
 ) -> impl IntoView {
     let upload_action = Action::new_local(|data: &FormData| {
@@ -19,18 +24,72 @@ pub fn ReceiptUpload(
     let pending = upload_action.pending();
     let upload = upload_action.value();
 
+    // The upload only queues the scan, later states arrive over the websocket and
+    // the result is fetched when it is done. The job is polled as well, in case the
+    // socket misses the update.
+    let ocr_job_updates = expect_context::<OcrJobUpdateMap>();
+    let fetch_action = Action::new_local(|job_id: &i32| get_ocr_job(*job_id));
+    let fetched = fetch_action.value();
+    let job = Signal::derive(move || match upload.get() {
+        Some(Ok(job)) => {
+            let fetched = fetched
+                .get()
+                .and_then(Result::ok)
+                .filter(|fetched| fetched.job_id == job.job_id);
+            let update = ocr_job_updates.read().get(&job.job_id).cloned();
+            let finished = fetched.clone().filter(|fetched| fetched.status.is_finished());
+            Some(Ok(finished.or(update).or(fetched).unwrap_or(job)))
+        }
+        Some(Err(e)) => Some(Err(e)),
+        None => None,
+    });
+    let waiting_for = Memo::new(move |_| match job.read().as_ref() {
+        Some(Ok(job)) if !job.status.is_finished() => Some(job.job_id),
+        _ => None,
+    });
+    let scanning = Memo::new(move |_| {
+        matches!(job.read().as_ref(), Some(Ok(job)) if job.status == OcrJobStatus::Running)
+    });
+    let busy = move || {
+        pending.get() || matches!(job.read().as_ref(), Some(Ok(job)) if !job.status.is_finished())
+    };
+
     let progress = RwSignal::new(0.0);
 
     Effect::new(move || {
-        if let Some(Ok((new_receipt_form, new_items_forms))) = upload_action.value().get() {
-            receipt_form.set(Some(new_receipt_form));
-            receipt_items_forms.set(Some(new_items_forms));
-            receipt_editing.set(true);
+        if let Some(Ok(job)) = job.get() {
+            if job.status == OcrJobStatus::Done && job.result.is_none() {
+                fetch_action.dispatch_local(job.job_id);
+            }
+            if let Some(scanned) = job.result {
+                receipt_form.set(Some(scanned.receipt));
+                receipt_items_forms.set(Some(scanned.items));
+                item_scans.set(scanned.item_scans);
+                new_store.set(scanned.new_store);
+                receipt_editing.set(true);
+            }
+        }
+    });
+
+    Effect::new(move || {
+        if let Some(job_id) = waiting_for.get() {
+            let interval = set_interval_with_handle(
+                move || {
+                    fetch_action.dispatch_local(job_id);
+                },
+                std::time::Duration::from_secs(5),
+            )
+            .ok();
+            on_cleanup(move || {
+                if let Some(handle) = interval {
+                    handle.clear();
+                }
+            });
         }
     });
 
     Effect::new(move || {
-        if pending.get() {
+        if scanning.get() {
             progress.set(0.0);
             let duration_ms = 25000.0; // 24 seconds
             let interval_ms = 100; // Update every 100ms
@@ -62,7 +121,7 @@ pub fn ReceiptUpload(
             <h3 class="text-xl font-semibold text-gray-900">Upload Receipt</h3>
 
             <p class="mt-1 text-sm text-gray-600">
-                Upload a receipt image or PDF to scan and extract details.
+                Upload a receipt photo (also HEIC), PDF or digital receipt (JSON) to extract details.
             </p>
 
             <form
@@ -74,12 +133,18 @@ pub fn ReceiptUpload(
                     upload_action.dispatch_local(form_data);
                 }
             >
+                // Before the file, the server reads it before preprocessing the photo
+                <label class="flex items-center gap-2 text-sm text-gray-600">
+                    <input type="checkbox" name="debug" value="true" />
+                    "Show preprocessing stages"
+                </label>
                 <label class="block">
                     <span class="sr-only">Choose file</span>
                     <input
                         required
                         type="file"
                         name="file_to_upload"
+                        accept="image/*,.heic,.heif,application/pdf,application/json"
                         class="block w-full text-sm text-gray-700
                         file:mr-4 file:rounded-md file:border-0
                         file:bg-gray-100 file:px-4 file:py-2
@@ -98,7 +163,7 @@ pub fn ReceiptUpload(
                     focus:outline-none focus:ring-2 focus:ring-blue-400
                     focus:ring-offset-2
                     disabled:cursor-not-allowed disabled:opacity-50"
-                    disabled=move || pending.get()
+                    disabled=busy
                 >
                     {move || if pending.get() { "Uploading…" } else { "Upload Receipt" }}
                 </button>
@@ -111,6 +176,14 @@ pub fn ReceiptUpload(
                         view! { <p class="text-gray-500">Select a file to begin.</p> }
                             .into_any()
                     } else if pending.get() {
+                        view! { <p class="text-blue-600">Uploading receipt</p> }.into_any()
+                    } else if matches!(
+                        job.read().as_ref(),
+                        Some(Ok(job)) if job.status == OcrJobStatus::Queued
+                    ) {
+                        view! { <p class="text-blue-600">Waiting for other scans to finish</p> }
+                            .into_any()
+                    } else if scanning.get() {
                         view! {
                             <div>
                                 <p class="text-blue-600 mb-2">Processing receipt</p>
@@ -126,13 +199,21 @@ pub fn ReceiptUpload(
                             </div>
                         }
                             .into_any()
-                    } else if let Some(Ok(_)) = upload.read().as_ref() {
-                        view! {
-                            // <Receipt receipt_with_items=value/>
-                            <p class="text-green-600 font-medium">Success</p>
+                    } else if let Some(Ok(job)) = job.read().as_ref() {
+                        match &job.error {
+                            Some(error) => {
+                                view! { <p class="text-red-600">{format!("Error: {error}")}</p> }
+                                    .into_any()
+                            }
+                            None => {
+                                view! {
+                                    // <Receipt receipt_with_items=value/>
+                                    <p class="text-green-600 font-medium">Success</p>
+                                }
+                                    .into_any()
+                            }
                         }
-                            .into_any()
-                    } else if let Some(Err(err)) = upload.read().as_ref() {
+                    } else if let Some(Err(err)) = job.read().as_ref() {
                         view! { <p class="text-red-600">{format!("Error: {:?}", err)}</p> }
                             .into_any()
                     } else {
@@ -140,6 +221,37 @@ pub fn ReceiptUpload(
                     }
                 }}
             </div>
+            {move || {
+                let stages = match upload.get() {
+                    Some(Ok(job)) => job.stages,
+                    _ => vec![],
+                };
+                (!stages.is_empty())
+                    .then(|| {
+                        view! {
+                            <div class="mt-4 space-y-3">
+                                {stages
+                                    .into_iter()
+                                    .enumerate()
+                                    .map(|(i, stage)| {
+                                        view! {
+                                            <figure>
+                                                <figcaption class="text-xs text-gray-500">
+                                                    {format!("{}. {}", i + 1, stage.name)}
+                                                </figcaption>
+                                                <img
+                                                    src=stage.image
+                                                    alt=stage.name
+                                                    class="w-full rounded border border-gray-200"
+                                                />
+                                            </figure>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()}
+                            </div>
+                        }
+                    })
+            }}
         </div>
     }
 }
@@ -0,0 +1,110 @@
+use crate::api::restriction::set_member_restrictions;
+use crate::app::RestrictionList;
+use crate::models::meal_tag::parse_tags;
+use crate::models::member::Member;
+use crate::models::restriction::COMMON_ALLERGENS;
+use leptos::prelude::*;
+
+/// Toggles the common allergens in a comma separated input.
+#[component]
+pub fn AllergenChips(allergens: RwSignal<String>) -> impl IntoView {
+    let toggle_allergen = move |allergen: String| {
+        let mut current = parse_tags(&allergens.get());
+        if let Some(idx) = current.iter().position(|a| *a == allergen) {
+            current.remove(idx);
+        } else {
+            current.push(allergen);
+        }
+        allergens.set(current.join(", "));
+    };
+
+    view! {
+        <div class="flex flex-wrap gap-1">
+            {move || {
+                let current = parse_tags(&allergens.get());
+                COMMON_ALLERGENS
+                    .into_iter()
+                    .map(|allergen| {
+                        let active = current.iter().any(|a| a == allergen);
+                        view! {
+                            <button
+                                type="button"
+                                class=if active {
+                                    "px-2 py-0.5 rounded-full text-xs bg-red-500 text-white"
+                                } else {
+                                    "px-2 py-0.5 rounded-full text-xs bg-gray-100 text-gray-700 dark:bg-gray-700 dark:text-gray-200"
+                                }
+                                on:click=move |_| toggle_allergen(allergen.to_string())
+                            >
+                                {allergen}
+                            </button>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+        </div>
+    }
+}
+
+#[component]
+pub fn MemberRestrictionsForm(member: Member, restrictions: RestrictionList) -> impl IntoView {
+    let set_restrictions_action = Action::new(move |allergens: &String| {
+        let allergens = allergens.clone();
+        async move { set_member_restrictions(member, allergens).await }
+    });
+    let allergens = RwSignal::new(String::new());
+    // Fill in the input once the restrictions are loaded
+    Effect::watch(
+        move || {
+            restrictions
+                .read()
+                .iter()
+                .filter(|r| r.member == member)
+                .map(|r| r.allergen.clone())
+                .collect::<Vec<_>>()
+        },
+        move |current, _, _| allergens.set(current.join(", ")),
+        true,
+    );
+    Effect::new(move || {
+        if let Some(Ok(saved)) = set_restrictions_action.value().get() {
+            restrictions.update(|restrictions| {
+                restrictions.retain(|r| r.member != member);
+                restrictions.extend(saved);
+            });
+        }
+    });
+
+    let form_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        set_restrictions_action.dispatch(allergens.get());
+    };
+
+    view! {
+        <div class="w-80 rounded-xl border border-gray-200 bg-white p-4 shadow-md dark:bg-gray-900 dark:border-gray-700">
+            <form on:submit=form_submit class="space-y-3">
+                <h3 class="text-lg font-semibold text-gray-900 dark:text-white">{member.name()}</h3>
+                <input
+                    type="text"
+                    placeholder="Can't eat, comma separated"
+                    bind:value=allergens
+                    class="px-3 py-2 w-full border rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-400 dark:bg-gray-700 dark:text-white"
+                />
+                <AllergenChips allergens />
+                <button
+                    type="submit"
+                    class="w-full py-2 bg-blue-500 text-white font-semibold rounded-lg hover:bg-blue-600 transition"
+                >
+                    "Save"
+                </button>
+            </form>
+            {move || {
+                set_restrictions_action
+                    .value()
+                    .get()
+                    .and_then(Result::err)
+                    .map(|e| view! { <p class="mt-2 text-sm text-red-600">{e.to_string()}</p> })
+            }}
+        </div>
+    }
+}
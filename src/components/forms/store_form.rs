@@ -0,0 +1,83 @@
+use crate::api::store::update_store;
+use crate::models::store::Store;
+use leptos::prelude::*;
+
+/// `None` for blank input, so optional fields can be cleared.
+fn non_empty(value: String) -> Option<String> {
+    (!value.trim().is_empty()).then_some(value)
+}
+
+#[component]
+pub fn EditStoreForm(store: Store, saved: WriteSignal<Option<Store>>) -> impl IntoView {
+    let update_store_action = Action::new(|store: &Store| {
+        let store = store.clone();
+        async move { update_store(store).await }
+    });
+    Effect::new(move || {
+        if let Some(Ok(store)) = update_store_action.value().get() {
+            saved.set(Some(store));
+        }
+    });
+
+    let id = store.id;
+    let chain = RwSignal::new(store.chain);
+    let name = RwSignal::new(store.name);
+    let address = RwSignal::new(store.address.unwrap_or_default());
+    let org_number = RwSignal::new(store.org_number.unwrap_or_default());
+    let aisles = RwSignal::new(store.aisles);
+
+    let form_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        update_store_action.dispatch(Store {
+            id,
+            chain: chain.get(),
+            name: name.get(),
+            address: non_empty(address.get()),
+            org_number: non_empty(org_number.get()),
+            aisles: aisles.get(),
+        });
+    };
+
+    let input_class = "px-3 py-2 w-full border rounded-lg focus:outline-none focus:ring-2 focus:ring-blue-400 dark:bg-gray-700 dark:text-white";
+    view! {
+        <div class="w-full max-w-md sm:max-w-lg mx-auto p-6 bg-white dark:bg-gray-900 rounded-xl shadow-lg">
+            <form on:submit=form_submit class="space-y-3">
+                <input
+                    type="text"
+                    placeholder="Chain"
+                    bind:value=chain
+                    class=input_class
+                    required
+                />
+                <input type="text" placeholder="Branch" bind:value=name class=input_class />
+                <input type="text" placeholder="Address" bind:value=address class=input_class />
+                <input
+                    type="text"
+                    inputmode="numeric"
+                    placeholder="Org. number"
+                    bind:value=org_number
+                    class=input_class
+                />
+                <textarea
+                    placeholder="Aisles in walking order, one per line"
+                    rows="5"
+                    bind:value=aisles
+                    class=input_class
+                ></textarea>
+                <button
+                    type="submit"
+                    class="w-full py-2 bg-blue-500 text-white font-semibold rounded-lg hover:bg-blue-600 transition"
+                >
+                    "Update Store"
+                </button>
+            </form>
+            {move || {
+                update_store_action
+                    .value()
+                    .get()
+                    .and_then(Result::err)
+                    .map(|e| view! { <p class="mt-2 text-sm text-red-600">{e.to_string()}</p> })
+            }}
+        </div>
+    }
+}
@@ -1,12 +1,16 @@
-use crate::api::meal::get_meals_ordered;
+use crate::api::meal::get_meal_overviews;
+use crate::app::RestrictionList;
 use crate::components::buttons::attendance::Attendance;
+use crate::components::buttons::rating::MealRatings;
 use crate::components::forms::day_form::DayForm;
 use crate::components::forms::meal_form::CreateMealForm;
 use crate::components::modal::Modal;
 use crate::components::models::ingredient::DayIngredient;
 use crate::components::models::receipt::Receipt;
+use crate::components::models::restriction::AllergenWarning;
+use crate::models::day::Day as DayModel;
 use crate::models::days_ingredients::DayWithMealAndIngredients;
-use crate::models::meal::{Meal, MealWithIngredients};
+use crate::models::meal::{MealOverview, MealWithIngredients};
 use chrono::{Datelike, Local};
 use leptos::{either::Either, html::Div, prelude::*};
 use leptos_use::math::use_not;
@@ -41,9 +45,10 @@ pub fn Day(day: DayWithMealAndIngredients) -> impl IntoView {
     let create_meal_completed = RwSignal::new(true);
     let show_create_meal = RwSignal::new(false);
     let new_meal: RwSignal<Option<MealWithIngredients>> = RwSignal::new(None);
-    let meals_resource = OnceResource::new(get_meals_ordered());
-    let meals: RwSignal<Vec<Meal>> = RwSignal::new(Vec::new());
+    let meals_resource = OnceResource::new(get_meal_overviews());
+    let meals: RwSignal<Vec<MealOverview>> = RwSignal::new(Vec::new());
     let view_receipts = RwSignal::new(false);
+    let restrictions = use_context::<RestrictionList>().unwrap_or_default();
 
     Effect::watch(
         move || show_create_meal.get(),
@@ -64,7 +69,7 @@ pub fn Day(day: DayWithMealAndIngredients) -> impl IntoView {
         move || new_meal.get(),
         move |new_meal, _, _| {
             if let Some(new_meal) = new_meal {
-                meals.write().push(new_meal.meal.clone())
+                meals.write().push(MealOverview::from(new_meal))
             }
         },
         false,
@@ -134,8 +139,45 @@ pub fn Day(day: DayWithMealAndIngredients) -> impl IntoView {
                 day.day.date.day(),
                 day.day.date.month(),
             );
-            match day.meal {
-                Some((meal, ingredients)) => {
+            // Ask for ratings once the dinner has been eaten
+            let ratings = day
+                .main_meal()
+                .map(|meal| meal.id)
+                .filter(|_| day.day.date <= today)
+                .map(|meal_id| {
+                    view! {
+                        <div class="border-t border-gray-200 dark:border-gray-700 px-4 py-2 space-y-1">
+                            <MealRatings
+                                day=day.day.clone()
+                                meal_id=meal_id
+                                ratings=day.ratings.clone()
+                            />
+                        </div>
+                    }
+                });
+            let cost = day
+                .cost()
+                .map(|cost| {
+                    view! {
+                        <p class="text-sm text-gray-500 dark:text-gray-400" title="Cost from receipts">
+                            {cost.to_string()}
+                        </p>
+                    }
+                });
+            // Shown by its first dish, a day may be planned with only sides
+            match day.meals.first().map(|dish| dish.meal.clone()) {
+                Some(meal) => {
+                    let thumbnail = meal.thumbnail();
+                    let conflicts = day.conflicts(&restrictions.read());
+                    // The other dishes, the image and recipe are of the first one
+                    let other_dishes = day
+                        .meals
+                        .iter()
+                        .skip(1)
+                        .map(|dish| format!("{}: {}", dish.course.label(), dish.meal.name))
+                        .collect::<Vec<_>>()
+                        .join(" · ");
+                    let ingredients = day.ingredients().cloned().collect::<Vec<_>>();
                     Either::Left(
                         view! {
                             <div
@@ -232,11 +274,25 @@ pub fn Day(day: DayWithMealAndIngredients) -> impl IntoView {
                                     <h5 class="text-xl font-bold text-blue-700 dark:text-blue-400 font-underline">
                                         {meal.name.clone()}
                                     </h5>
+                                    {(!other_dishes.is_empty())
+                                        .then(|| {
+                                            view! {
+                                                <p class="text-sm text-gray-600 dark:text-gray-300">
+                                                    {other_dishes}
+                                                </p>
+                                            }
+                                        })}
+                                    {cost}
+                                    <Leftovers
+                                        from=day.leftovers_from.clone()
+                                        on=day.leftovers_on.clone()
+                                    />
+                                    <AllergenWarning conflicts />
                                 </div>
                                 // Image
                                 <img
                                     class="w-full h-48 object-cover rounded-b-none rounded-t-lg"
-                                    src=meal.image
+                                    src=thumbnail
                                     alt=meal.name
                                 />
                                 // Footer: Ingredients
@@ -256,6 +312,7 @@ pub fn Day(day: DayWithMealAndIngredients) -> impl IntoView {
                                 <div class="border-t border-gray-200 dark:border-gray-700 flex justify-center items-center flex-nowrap gap-3 py-1">
                                     <Attendance day=&day.day />
                                 </div>
+                                {ratings}
                             </div>
                         },
                     )
@@ -273,6 +330,11 @@ pub fn Day(day: DayWithMealAndIngredients) -> impl IntoView {
                                     <h4 class="text-lg font-semibold text-gray-900 dark:text-white">
                                         {header}
                                     </h4>
+                                    {cost}
+                                    <Leftovers
+                                        from=day.leftovers_from.clone()
+                                        on=day.leftovers_on.clone()
+                                    />
                                 </div>
                                 // Image area with big "+" button
                                 <div
@@ -310,3 +372,38 @@ pub fn Day(day: DayWithMealAndIngredients) -> impl IntoView {
         }}
     }
 }
+
+/// Links between a day cooked with extra portions and the days eating them.
+#[component]
+fn Leftovers(from: Option<DayModel>, on: Vec<DayModel>) -> impl IntoView {
+    let link = |day: &DayModel| {
+        view! {
+            <a href=format!("#day-{}", day.id) class="underline hover:text-amber-800 dark:hover:text-amber-300">
+                {day.date.weekday().to_string()}
+            </a>
+        }
+    };
+    let from = from
+        .map(|day| {
+            view! {
+                <p class="text-sm text-amber-700 dark:text-amber-400">"Leftovers from " {link(&day)}</p>
+            }
+        });
+    let on = (!on.is_empty())
+        .then(|| {
+            view! {
+                <p class="text-sm text-amber-700 dark:text-amber-400">
+                    "Extra portions for "
+                    {on
+                        .iter()
+                        .enumerate()
+                        .map(|(i, day)| view! { {(i > 0).then_some(", ")}{link(day)} })
+                        .collect::<Vec<_>>()}
+                </p>
+            }
+        });
+    view! {
+        {from}
+        {on}
+    }
+}
@@ -0,0 +1,110 @@
+use crate::app::{CategoryList, FoodList};
+use crate::components::forms::catalog_form::EditCatalogIngredientForm;
+use crate::components::modal::Modal;
+use crate::models::ingredient_catalog::CatalogIngredient;
+use leptos::prelude::*;
+
+#[component]
+pub fn CatalogEntry(entry: CatalogIngredient) -> impl IntoView {
+    let entry = RwSignal::new(entry);
+    let saved: RwSignal<Option<CatalogIngredient>> = RwSignal::new(None);
+    let editing = RwSignal::new(false);
+    Effect::watch(
+        move || saved.get(),
+        move |saved, _, _| {
+            if let Some(saved) = saved {
+                entry.set(saved.clone());
+                editing.set(false);
+            }
+        },
+        false,
+    );
+    let categories = use_context::<CategoryList>().unwrap_or_default();
+    let foods = use_context::<FoodList>().unwrap_or_default();
+    let food_name = move || {
+        let food_id = entry.read().food_id?;
+        foods
+            .read()
+            .iter()
+            .find(|f| f.id == food_id)
+            .map(|f| f.name.clone())
+    };
+    let category_name = move || {
+        let category_id = entry.read().category_id?;
+        categories
+            .read()
+            .iter()
+            .find(|c| c.id == category_id)
+            .map(|c| c.name.clone())
+    };
+
+    view! {
+        <Modal show=editing.into()>
+            {move || {
+                view! { <EditCatalogIngredientForm entry=entry.get() saved=saved.write_only() /> }
+            }}
+        </Modal>
+        <div class="w-80 rounded-xl border border-gray-200 bg-white p-4 shadow-md">
+            <div class="flex justify-between items-center">
+                <h3 class="text-lg font-semibold text-gray-900">
+                    {move || entry.read().name.clone()}
+                    {move || {
+                        entry
+                            .read()
+                            .unit
+                            .clone()
+                            .map(|unit| {
+                                view! {
+                                    <span class="ml-2 text-sm font-normal text-gray-500">{unit}</span>
+                                }
+                            })
+                    }}
+                </h3>
+                <button
+                    type="button"
+                    class="text-sm text-blue-600 hover:underline"
+                    on:click=move |_| editing.set(true)
+                >
+                    "Edit"
+                </button>
+            </div>
+            {move || {
+                category_name()
+                    .map(|name| view! { <p class="text-sm text-gray-500">{name}</p> })
+            }}
+            {move || {
+                food_name()
+                    .map(|name| {
+                        view! { <p class="text-xs text-gray-500">{format!("Nutrition from {name}")}</p> }
+                    })
+            }}
+            {move || {
+                let aliases = entry.read().alias_list().join(", ");
+                (!aliases.is_empty())
+                    .then(|| {
+                        view! { <p class="mt-2 text-xs text-gray-500">{format!("Also {aliases}")}</p> }
+                    })
+            }}
+            {move || {
+                let allergens = entry.read().allergen_list();
+                (!allergens.is_empty())
+                    .then(|| {
+                        view! {
+                            <div class="mt-2 flex flex-wrap gap-1">
+                                {allergens
+                                    .into_iter()
+                                    .map(|allergen| {
+                                        view! {
+                                            <span class="px-2 py-0.5 rounded-full text-xs bg-red-100 text-red-700">
+                                                {allergen}
+                                            </span>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()}
+                            </div>
+                        }
+                    })
+            }}
+        </div>
+    }
+}
@@ -4,7 +4,7 @@ use leptos_use::math::use_not;
 use crate::{
     api::meal::delete_meal,
     components::{forms::meal_form::UpdateMealForm, modal::Modal},
-    models::meal::MealWithIngredients,
+    models::meal::{MealListing, MealWithIngredients},
 };
 
 #[component]
@@ -18,6 +18,7 @@ pub fn Meal(meal: MealWithIngredients) -> impl IntoView {
     let update_completed = RwSignal::new(true);
     let show_update = use_not(update_completed);
     let show_full = RwSignal::new(false);
+    let cost_per_serving = Memo::new(move |_| meal.read().cost_per_serving());
     // let meal_clone = meal.clone();
     view! {
         <Show when=move || !deleted.get() fallback=|| view! {}>
@@ -51,9 +52,25 @@ pub fn Meal(meal: MealWithIngredients) -> impl IntoView {
                 </span>
                 // </A>
                 {move || {
+                    let tags = meal.read().tags.clone();
+                    let rating = meal
+                        .read()
+                        .average_rating()
+                        .map(|average| {
+                            format!("★ {average:.1} from {} ratings", meal.read().ratings.len())
+                        });
+                    let favourite_of = meal
+                        .read()
+                        .favourite_of()
+                        .iter()
+                        .map(|member| member.name())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let nutrition = meal.read().nutrition.clone();
                     let meal = &meal.read().meal;
                     let meal_id = meal.id;
                     let meal_name = meal.name.clone();
+                    let cost_per_serving = cost_per_serving.get();
                     view! {
                         <span
                             class="absolute top-2 right-2 z-10"
@@ -82,6 +99,59 @@ pub fn Meal(meal: MealWithIngredients) -> impl IntoView {
                             <h5 class="text-xl font-bold text-blue-700 dark:text-blue-400 font-underline">
                                 {meal_name}
                             </h5>
+                            {cost_per_serving
+                                .map(|cost| {
+                                    view! {
+                                        <p class="text-sm text-gray-500 dark:text-gray-400">
+                                            {format!("{cost} per serving")}
+                                        </p>
+                                    }
+                                })}
+                            {nutrition
+                                .map(|nutrition| {
+                                    let missing = (!nutrition.missing.is_empty())
+                                        .then(|| format!("Without {}", nutrition.missing.join(", ")));
+                                    view! {
+                                        <p
+                                            class="text-sm text-gray-500 dark:text-gray-400"
+                                            title=missing
+                                        >
+                                            {format!(
+                                                "{} per serving, {} servings",
+                                                nutrition.per_serving(),
+                                                nutrition.servings,
+                                            )}
+                                        </p>
+                                    }
+                                })}
+                            {rating
+                                .map(|rating| {
+                                    view! {
+                                        <p class="text-sm text-yellow-600 dark:text-yellow-400">
+                                            {rating}
+                                        </p>
+                                    }
+                                })}
+                            {(!favourite_of.is_empty())
+                                .then(|| {
+                                    view! {
+                                        <p class="text-sm text-gray-500 dark:text-gray-400">
+                                            {format!("Favourite of {favourite_of}")}
+                                        </p>
+                                    }
+                                })}
+                            <div class="flex flex-wrap gap-1 mt-1">
+                                {tags
+                                    .into_iter()
+                                    .map(|tag| {
+                                        view! {
+                                            <span class="px-2 py-0.5 rounded-full text-xs bg-gray-100 text-gray-700 dark:bg-gray-700 dark:text-gray-200">
+                                                {tag}
+                                            </span>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>()}
+                            </div>
                         </div>
                     }
                 }}
@@ -96,7 +166,7 @@ pub fn Meal(meal: MealWithIngredients) -> impl IntoView {
                                     // Image
                                     <img
                                         class="w-full h-48 object-cover rounded-b-none rounded-t-lg"
-                                        src=meal.meal.image.clone()
+                                        src=meal.meal.thumbnail()
                                         alt=meal.meal.name.clone()
                                     />
                                     // Footer: Ingredients
@@ -125,6 +195,39 @@ pub fn Meal(meal: MealWithIngredients) -> impl IntoView {
                                                 .collect::<Vec<_>>()}
                                         </div>
                                     </div>
+                                    // History
+                                    {(!meal.history.is_empty())
+                                        .then(|| {
+                                            view! {
+                                                <div class="p-4 border-t border-gray-200 dark:border-gray-700">
+                                                    <h6 class="text-md font-semibold text-gray-900 dark:text-white mb-2">
+                                                        History
+                                                    </h6>
+                                                    <ul class="text-sm text-gray-700 dark:text-gray-300">
+                                                        {meal
+                                                            .history
+                                                            .clone()
+                                                            .into_iter()
+                                                            .map(|serving| {
+                                                                view! {
+                                                                    <li class="flex justify-between">
+                                                                        <span>
+                                                                            {serving.day.date.format("%d.%m.%Y").to_string()}
+                                                                        </span>
+                                                                        <span>
+                                                                            {serving
+                                                                                .cost
+                                                                                .map(|cost| cost.to_string())
+                                                                                .unwrap_or_else(|| "-".to_string())}
+                                                                        </span>
+                                                                    </li>
+                                                                }
+                                                            })
+                                                            .collect::<Vec<_>>()}
+                                                    </ul>
+                                                </div>
+                                            }
+                                        })}
                                     <button
                                         on:click=move |_| show_full.set(false)
                                         class="flex justify-center items-center"
@@ -3,3 +3,6 @@ pub mod ingredient;
 pub mod meal;
 pub mod extra_item;
 pub mod receipt;
+pub mod store;
+pub mod ingredient_catalog;
+pub mod restriction;
@@ -1,7 +1,53 @@
 use leptos::prelude::*;
+use leptos_router::components::A;
 use web_sys::window;
 
-use crate::models::receipt::ReceiptWithItems;
+use crate::api::category::set_item_category;
+use crate::app::{CategoryList, RouteUrl};
+use crate::models::money::Money;
+use crate::models::receipt::{ItemKind, ReceiptWithItems, SplitMode};
+
+/// Category picker for a saved item, left out where no `CategoryList` is provided.
+/// The choice is remembered for later items with the same name.
+#[component]
+fn ItemCategory(item_id: i32, category_id: Option<i32>) -> impl IntoView {
+    let category_id = RwSignal::new(category_id);
+    let set_category_action = Action::new(move |category_id: &Option<i32>| {
+        let category_id = *category_id;
+        async move { set_item_category(item_id, category_id).await }
+    });
+
+    use_context::<CategoryList>().map(|categories| {
+        view! {
+            <select
+                class="block max-w-full text-xs text-gray-500 bg-transparent focus:outline-none"
+                on:change:target=move |ev| {
+                    let id = ev.target().value().parse::<i32>().ok();
+                    category_id.set(id);
+                    set_category_action.dispatch(id);
+                }
+            >
+                <option value="" selected=move || category_id.get().is_none()>
+                    "No category"
+                </option>
+                {move || {
+                    categories
+                        .get()
+                        .into_iter()
+                        .map(|category| {
+                            let id = category.id;
+                            view! {
+                                <option value=id selected=move || category_id.get() == Some(id)>
+                                    {category.name}
+                                </option>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </select>
+        }
+    })
+}
 
 #[component]
 pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
@@ -9,16 +55,22 @@ pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
     let anders_sum = receipt_with_items.anders_sum();
     let andreas_sum = receipt_with_items.andreas_sum();
     let ac_sum = receipt_with_items.ac_sum();
+    let split_shares = receipt_with_items.split_shares();
     let receipt = receipt_with_items.receipt;
     let items = receipt_with_items.items;
     let days = receipt_with_items.days;
 
     let copied_signal = RwSignal::new(None::<&str>);
 
-    let format_price = |value: f32| format!("{:.2}", value).replace(".", ",");
+    // Plain equal splits are shown as a cross, weighted ones with their share
+    let format_share = |share: i32| match share {
+        0 => String::new(),
+        1 => String::from("X"),
+        s => s.to_string(),
+    };
 
-    let copy_to_clipboard = move |name: &'static str, value: f32| {
-        let value_str = format_price(value);
+    let copy_to_clipboard = move |name: &'static str, value: Money| {
+        let value_str = value.format_amount();
         if let Some(win) = window() {
             let _ = win.navigator().clipboard().write_text(&value_str);
             copied_signal.set(Some(name));
@@ -34,7 +86,19 @@ pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
             // <!-- Receipt Header -->
             <div class="mb-4">
                 <div class="flex justify-between items-center mb-2">
-                    <h3 class="text-lg font-semibold text-gray-900">{receipt.store.clone()}</h3>
+                    <h3 class="text-lg font-semibold text-gray-900">
+                        {match receipt.store_id {
+                            Some(store_id) => {
+                                view! {
+                                    <A href=format!("{}?store_id={store_id}", RouteUrl::ReceiptList)>
+                                        {receipt.store.clone()}
+                                    </A>
+                                }
+                                    .into_any()
+                            }
+                            None => receipt.store.clone().into_any(),
+                        }}
+                    </h3>
                     <span class="text-sm text-gray-500">
                         {format!("{}", receipt.datetime.format("%Y-%m-%d %H:%M"))}
                     </span>
@@ -52,7 +116,7 @@ pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
                         on:click=move |_| copy_to_clipboard("anders", anders_sum)
                     >
                         <div class="text-2xl font-bold text-blue-900">
-                            {format_price(anders_sum)}
+                            {anders_sum.format_amount()}
                         </div>
                         <div class="text-xs font-semibold text-blue-700">
                             {move || {
@@ -75,7 +139,7 @@ pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
                         on:click=move |_| copy_to_clipboard("andreas", andreas_sum)
                     >
                         <div class="text-2xl font-bold text-green-900">
-                            {format_price(andreas_sum)}
+                            {andreas_sum.format_amount()}
                         </div>
                         <div class="text-xs font-semibold text-green-700">
                             {move || {
@@ -98,7 +162,7 @@ pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
                         on:click=move |_| copy_to_clipboard("ac", ac_sum)
                     >
                         <div class="text-2xl font-bold text-purple-900">
-                            {format_price(ac_sum)}
+                            {ac_sum.format_amount()}
                         </div>
                         <div class="text-xs font-semibold text-purple-700">
                             {move || {
@@ -110,7 +174,15 @@ pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
 
                 <div class="text-center text-sm text-gray-500 pb-2 border-b">
                     "Total: "
-                    <span class="font-semibold text-gray-700">{format!("{:.2},-", total)}</span>
+                    <span class="font-semibold text-gray-700">{total.to_string()}</span>
+                    {(receipt.split_mode == SplitMode::Attendance || split_shares != [1, 1, 1])
+                        .then(|| {
+                            let label = match receipt.split_mode {
+                                SplitMode::Equal => "split",
+                                SplitMode::Attendance => "by attendance",
+                            };
+                            format!(" ({label} {})", split_shares.map(|s| s.to_string()).join("/"))
+                        })}
                 </div>
             </div>
 
@@ -155,25 +227,39 @@ pub fn Receipt(receipt_with_items: ReceiptWithItems) -> impl IntoView {
                     .iter()
                     .map(|item| {
                         let name = item.name.clone();
-                        let price = format!("{:.2}", item.price);
-                        let anders = item.anders_pay;
-                        let andreas = item.andreas_pay;
-                        let ac = item.ac_pay;
+                        let price = item.price.format_amount();
+                        // Discounts and deposits are split like the item above them
+                        let [anders, andreas, ac] = if item.kind.follows_product() {
+                            ["↑", "↑", "↑"].map(String::from)
+                        } else {
+                            item.shares().map(format_share)
+                        };
+                        let kind = (item.kind != ItemKind::Product)
+                            .then(|| {
+                                view! {
+                                    <span class="ml-1 px-1 text-xs text-gray-600 bg-gray-100 rounded">
+                                        {item.kind.label()}
+                                    </span>
+                                }
+                            });
                         view! {
                             <div
                                 class="grid gap-1 items-center border-b border-gray-200 py-1"
                                 style="grid-template-columns: 1fr 70px 45px 45px 45px;"
                             >
-                                <span class="px-1 py-1 text-sm text-gray-900 truncate">{name}</span>
+                                <div class="px-1 py-1 min-w-0">
+                                    <div class="text-sm text-gray-900 truncate">{name} {kind}</div>
+                                    <ItemCategory item_id=item.id category_id=item.category_id />
+                                </div>
                                 <span class="px-1 py-1 text-sm text-gray-900">{price}</span>
                                 <span class="text-center text-sm text-gray-700">
-                                    {if anders { "X" } else { "" }}
+                                    {anders}
                                 </span>
                                 <span class="text-center text-sm text-gray-700">
-                                    {if andreas { "X" } else { "" }}
+                                    {andreas}
                                 </span>
                                 <span class="text-center text-sm text-gray-700">
-                                    {if ac { "X" } else { "" }}
+                                    {ac}
                                 </span>
                             </div>
                         }
@@ -0,0 +1,34 @@
+use crate::models::restriction::Conflict;
+use leptos::prelude::*;
+
+/// Lists the ingredients attending members can't eat, nothing when there are none.
+#[component]
+pub fn AllergenWarning(conflicts: Vec<Conflict>) -> impl IntoView {
+    (!conflicts.is_empty())
+        .then(|| {
+            view! {
+                <div class="flex items-start gap-2 rounded-lg bg-red-50 px-3 py-2 text-sm text-red-700 dark:bg-red-900 dark:text-red-200">
+                    <svg
+                        xmlns="http://www.w3.org/2000/svg"
+                        fill="none"
+                        viewBox="0 0 24 24"
+                        stroke-width="1.5"
+                        stroke="currentColor"
+                        class="size-5 shrink-0"
+                    >
+                        <path
+                            stroke-linecap="round"
+                            stroke-linejoin="round"
+                            d="M12 9v3.75m-9.303 3.376c-.866 1.5.217 3.374 1.948 3.374h14.71c1.73 0 2.813-1.874 1.948-3.374L13.949 3.378c-.866-1.5-3.032-1.5-3.898 0L2.697 16.126ZM12 15.75h.007v.008H12v-.008Z"
+                        />
+                    </svg>
+                    <ul>
+                        {conflicts
+                            .into_iter()
+                            .map(|conflict| view! { <li>{conflict.to_string()}</li> })
+                            .collect::<Vec<_>>()}
+                    </ul>
+                </div>
+            }
+        })
+}
@@ -0,0 +1,73 @@
+use crate::app::RouteUrl;
+use crate::components::forms::store_form::EditStoreForm;
+use crate::components::modal::Modal;
+use crate::models::store::{Store, StoreSpending};
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+#[component]
+pub fn Store(spending: StoreSpending) -> impl IntoView {
+    let store = RwSignal::new(spending.store);
+    let saved: RwSignal<Option<Store>> = RwSignal::new(None);
+    let editing = RwSignal::new(false);
+    Effect::watch(
+        move || saved.get(),
+        move |saved, _, _| {
+            if let Some(saved) = saved {
+                store.set(saved.clone());
+                editing.set(false);
+            }
+        },
+        false,
+    );
+    let receipts_href = move || format!("{}?store_id={}", RouteUrl::ReceiptList, store.read().id);
+
+    view! {
+        <Modal show=editing.into()>
+            {move || view! { <EditStoreForm store=store.get() saved=saved.write_only() /> }}
+        </Modal>
+        <div class="w-80 rounded-xl border border-gray-200 bg-white p-4 shadow-md">
+            <div class="flex justify-between items-center">
+                <h3 class="text-lg font-semibold text-gray-900">
+                    {move || store.read().display_name()}
+                </h3>
+                <button
+                    type="button"
+                    class="text-sm text-blue-600 hover:underline"
+                    on:click=move |_| editing.set(true)
+                >
+                    "Edit"
+                </button>
+            </div>
+            {move || {
+                store
+                    .read()
+                    .address
+                    .clone()
+                    .map(|address| view! { <p class="text-sm text-gray-500">{address}</p> })
+            }}
+            {move || {
+                store
+                    .read()
+                    .org_number
+                    .clone()
+                    .map(|org| {
+                        view! { <p class="text-xs text-gray-400">{format!("Org. nr. {org}")}</p> }
+                    })
+            }}
+            <div class="flex justify-between items-center mt-2 text-sm text-gray-700">
+                <A href=receipts_href>
+                    <span class="text-blue-600 hover:underline">
+                        {format!("{} receipts", spending.receipt_count)}
+                    </span>
+                </A>
+                <span class="font-semibold">{spending.total.to_string()}</span>
+            </div>
+            {move || {
+                let aisles = store.read().aisle_list().join(" → ");
+                (!aisles.is_empty())
+                    .then(|| view! { <p class="mt-2 text-xs text-gray-500">{aisles}</p> })
+            }}
+        </div>
+    }
+}
@@ -54,14 +54,7 @@ pub fn Notifications() -> impl IntoView {
             if let Some(Ok(days)) = days {
                 week_ingredients_count.set(
                     days.iter()
-                        .filter(|day| {
-                            if let Some((_, ingredients)) = day.meal.as_ref() {
-                                if ingredients.iter().any(|i| !i.bought) {
-                                    return true;
-                                }
-                            }
-                            false
-                        })
+                        .filter(|day| day.ingredients().any(|i| !i.bought))
                         .count(),
                 );
             }
@@ -0,0 +1,388 @@
+pub mod provider;
+
+use std::io::Cursor;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+
+use axum::extract::Path;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use leptos::logging::{error, log};
+use sha2::{Digest, Sha256};
+use url::{Host, Url};
+
+use crate::api::ssr::*;
+use crate::db::Db;
+use crate::models::meal::{is_local_image, thumbnail_url, IMAGE_PATH};
+use crate::ocr::normalize::max_upload_bytes;
+
+/// Meal images are scaled down so their longest side is at most this many pixels.
+pub const MAX_SIDE: u32 = 1200;
+/// Longest side of the thumbnails shown on cards.
+pub const THUMBNAIL_SIDE: u32 = 400;
+
+/// Where meal images are stored, from `IMAGE_DIR`.
+pub fn image_dir() -> PathBuf {
+    std::env::var_os("IMAGE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("images"))
+}
+
+/// Names stored files after their content. The hash must stay the same across
+/// builds, or images stored earlier would be stored again.
+fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Decodes the image, turns it upright from its EXIF orientation and saves it
+/// scaled down as JPEG together with a thumbnail. The file is named after the
+/// content, so the same image is only stored once. Returns the URL of the image,
+/// see `thumbnail_url` for the thumbnail.
+pub fn store_image(bytes: &[u8]) -> Result<String, String> {
+    let unreadable = |e: image::ImageError| format!("Could not read the image: {e}");
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Could not read the image: {e}"))?
+        .into_decoder()
+        .map_err(unreadable)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(unreadable)?;
+    image.apply_orientation(orientation);
+
+    let url = format!("{IMAGE_PATH}/{}.jpg", content_hash(bytes));
+    let dir = image_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Could not create image folder: {e}"))?;
+    for (url, side) in [
+        (url.clone(), MAX_SIDE),
+        (thumbnail_url(&url), THUMBNAIL_SIDE),
+    ] {
+        let path = dir.join(file_name(&url).unwrap_or_default());
+        if path.exists() {
+            continue;
+        }
+        let resized = if image.width().max(image.height()) > side {
+            image.resize(side, side, FilterType::Lanczos3)
+        } else {
+            image.clone()
+        };
+        // JPEG has no alpha channel
+        DynamicImage::ImageRgb8(resized.to_rgb8())
+            .save_with_format(&path, ImageFormat::Jpeg)
+            .map_err(|e| format!("Could not save image: {e}"))?;
+    }
+    Ok(url)
+}
+
+/// Redirects followed when downloading an image, each is checked like the first URL.
+const MAX_REDIRECTS: usize = 5;
+
+/// Whether the server may fetch from this address. Meal image URLs come from
+/// users, so nothing on the server itself or its network may be reached.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // Shared address space of carrier-grade NAT
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local and link-local
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// A client for fetching `url`, after checking that it is http(s) and only
+/// resolves to public addresses. The client is pinned to the checked addresses,
+/// so the host can't resolve to another one when fetching.
+async fn checked_client(url: &Url) -> Result<reqwest::Client, String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "Only http and https images can be downloaded, not {url}"
+        ));
+    }
+    let port = url.port_or_known_default().unwrap_or(80);
+    let (domain, addrs) = match url.host() {
+        Some(Host::Domain(domain)) => {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| format!("Could not look up {domain}: {e}"))?
+                .collect();
+            (Some(domain), addrs)
+        }
+        Some(Host::Ipv4(ip)) => (None, vec![SocketAddr::new(ip.into(), port)]),
+        Some(Host::Ipv6(ip)) => (None, vec![SocketAddr::new(ip.into(), port)]),
+        None => return Err(format!("{url} has no host")),
+    };
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_public(addr.ip())) {
+        return Err(format!("{url} is not on a public address"));
+    }
+    let mut client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+    if let Some(domain) = domain {
+        client = client.resolve_to_addrs(domain, &addrs);
+    }
+    client
+        .build()
+        .map_err(|e| format!("Could not download {url}: {e}"))
+}
+
+/// Downloads the image at `url` and stores it, see `store_image`. Only public
+/// http(s) addresses are fetched, redirects included.
+pub async fn download_image(url: &str) -> Result<String, String> {
+    let mut url = Url::parse(url).map_err(|e| format!("{url} is not a valid URL: {e}"))?;
+    let mut response = None;
+    for _ in 0..=MAX_REDIRECTS {
+        let fetched = checked_client(&url)
+            .await?
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Could not download {url}: {e}"))?;
+        if !fetched.status().is_redirection() {
+            response = Some(
+                fetched
+                    .error_for_status()
+                    .map_err(|e| format!("Could not download {url}: {e}"))?,
+            );
+            break;
+        }
+        let location = fetched
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| format!("Could not download {url}: redirect without a location"))?;
+        url = url
+            .join(location)
+            .map_err(|e| format!("Could not download {url}: {e}"))?;
+    }
+    let Some(mut response) = response else {
+        return Err(format!("Could not download {url}: too many redirects"));
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length as usize > max_upload_bytes())
+    {
+        return Err(format!("The image at {url} is too large"));
+    }
+    // The length header may be missing or wrong, so the limit is checked while reading
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Could not download {url}: {e}"))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > max_upload_bytes() {
+            return Err(format!("The image at {url} is too large"));
+        }
+    }
+    tokio::task::spawn_blocking(move || store_image(&bytes))
+        .await
+        .unwrap_or_else(|e| Err(format!("Could not store image: {e}")))
+}
+
+/// Stores remote images locally, keeping the remote URL if the download fails so
+/// saving the meal still works.
+pub async fn localize_image(image: String) -> String {
+    if image.is_empty() || is_local_image(&image) {
+        return image;
+    }
+    match download_image(&image).await {
+        Ok(local) => local,
+        Err(e) => {
+            error!("{e}");
+            image
+        }
+    }
+}
+
+/// Downloads the meal images that are still hot-linked, for meals saved before
+/// images were stored locally. Meals whose image can't be downloaded keep it, and
+/// the URL is remembered so it isn't tried again on the next start.
+pub async fn cache_remote_images(pool: Db) {
+    use crate::schema::image_download_failures;
+    use std::collections::HashSet;
+
+    let loaded = pool.get().map(|mut db| {
+        let meals = Meal::get_all(&mut db)?;
+        let failed: HashSet<String> = image_download_failures::table
+            .select(image_download_failures::url)
+            .load::<String>(&mut db)?
+            .into_iter()
+            .collect();
+        Ok::<_, Error>((meals, failed))
+    });
+    let (meals, failed) = match loaded {
+        Ok(Ok(loaded)) => loaded,
+        Ok(Err(e)) => return error!("Could not get meals to cache images for: {e}"),
+        Err(e) => return error!("Could not get DB connection: {e}"),
+    };
+    let mut cached = 0;
+    for meal in meals {
+        if meal.image.is_empty() || is_local_image(&meal.image) || failed.contains(&meal.image) {
+            continue;
+        }
+        let image = match download_image(&meal.image).await {
+            Ok(image) => image,
+            Err(e) => {
+                error!("Could not cache image of meal {}: {e}", meal.id);
+                let recorded = pool.get().map_err(|e| e.to_string()).and_then(|mut db| {
+                    insert_into(image_download_failures::table)
+                        .values((
+                            image_download_failures::url.eq(&meal.image),
+                            image_download_failures::error.eq(&e),
+                        ))
+                        .on_conflict_do_nothing()
+                        .execute(&mut db)
+                        .map_err(|e| e.to_string())
+                });
+                if let Err(e) = recorded {
+                    error!("Could not remember failed image of meal {}: {e}", meal.id);
+                }
+                continue;
+            }
+        };
+        let updated = pool.get().map_err(|e| e.to_string()).and_then(|mut db| {
+            update(meals::table.find(meal.id))
+                .set(meals::image.eq(&image))
+                .execute(&mut db)
+                .map_err(|e| e.to_string())
+        });
+        match updated {
+            Ok(_) => cached += 1,
+            Err(e) => error!("Could not save cached image of meal {}: {e}", meal.id),
+        }
+    }
+    if cached > 0 {
+        log!("Cached {cached} meal images");
+    }
+}
+
+/// The file name in `image_dir` of a local image URL. Only names `store_image`
+/// creates are accepted, so nothing outside the folder can be read.
+fn file_name(url: &str) -> Option<&str> {
+    let name = url.strip_prefix(IMAGE_PATH)?.strip_prefix('/')?;
+    let valid = !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    valid.then_some(name)
+}
+
+/// Serves stored images. Their names change with the content, so browsers may keep
+/// them forever.
+pub async fn serve_image(Path(name): Path<String>) -> impl IntoResponse {
+    let Some(name) = file_name(&format!("{IMAGE_PATH}/{name}")).map(str::to_owned) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let content_type = if name.ends_with(".svg") {
+        "image/svg+xml"
+    } else {
+        "image/jpeg"
+    };
+    let bytes = tokio::fs::read(image_dir().join(name))
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        ],
+        bytes,
+    ))
+}
+
+/// Tests share one image folder, as they run in parallel and `IMAGE_DIR` is global.
+#[cfg(test)]
+pub(crate) fn test_image_dir() -> &'static std::path::Path {
+    static DIR: once_cell::sync::Lazy<tempfile::TempDir> = once_cell::sync::Lazy::new(|| {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("IMAGE_DIR", dir.path());
+        dir
+    });
+    DIR.path()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_store_image() {
+        let dir = test_image_dir();
+        let mut png = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(1600, 800, Rgb([200, 100, 50])))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let url = store_image(&png).unwrap();
+        assert!(url.starts_with("/images/") && url.ends_with(".jpg"));
+        // The same image is stored once
+        assert_eq!(store_image(&png).unwrap(), url);
+        let image = image::open(dir.join(file_name(&url).unwrap())).unwrap();
+        assert_eq!((image.width(), image.height()), (MAX_SIDE, MAX_SIDE / 2));
+        let thumbnail = image::open(dir.join(file_name(&thumbnail_url(&url)).unwrap())).unwrap();
+        assert_eq!(thumbnail.width(), THUMBNAIL_SIDE);
+
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(store_image(b"not an image").is_err());
+        assert_eq!(file_name("/images/../db.sqlite3"), None);
+        assert_eq!(file_name("/images/.env"), None);
+    }
+
+    #[tokio::test]
+    async fn test_download_only_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "192.168.1.10",
+            "169.254.169.254",
+            "100.64.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        assert!(is_public("93.184.215.14".parse().unwrap()));
+        assert!(is_public(
+            "2606:2800:21f:cb07:6820:80da:af6b:8b2c".parse().unwrap()
+        ));
+
+        for url in [
+            "http://127.0.0.1:3000/api",
+            "http://localhost/image.jpg",
+            "http://[::1]/image.jpg",
+            "http://169.254.169.254/latest/meta-data",
+            "file:///etc/passwd",
+            "not a url",
+        ] {
+            assert!(download_image(url).await.is_err(), "{url}");
+        }
+    }
+}
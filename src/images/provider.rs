@@ -0,0 +1,256 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use leptos::logging::error;
+
+use super::{content_hash, image_dir};
+use crate::models::meal::IMAGE_PATH;
+
+pub type ImageFuture<'a> = Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>>;
+
+/// Somewhere to find an image for a meal that has none.
+pub trait ImageProvider: Send + Sync {
+    /// Used in `IMAGE_PROVIDERS` and in logs.
+    fn name(&self) -> &'static str;
+    /// The URL of an image for the meal.
+    fn find_image<'a>(&'a self, meal_name: &'a str) -> ImageFuture<'a>;
+}
+
+/// Google Custom Search, needs `GOOGLE_KEY` and `GOOGLE_SEARCH_ENGINE_ID`.
+pub struct GoogleImages {
+    key: String,
+    engine_id: String,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct GoogleImageResult {
+    #[serde(default)]
+    items: Vec<ImageItem>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct ImageItem {
+    link: String,
+}
+
+impl GoogleImages {
+    pub fn from_env() -> Option<Self> {
+        Some(GoogleImages {
+            key: std::env::var("GOOGLE_KEY").ok()?,
+            engine_id: std::env::var("GOOGLE_SEARCH_ENGINE_ID").ok()?,
+        })
+    }
+}
+
+impl ImageProvider for GoogleImages {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn find_image<'a>(&'a self, meal_name: &'a str) -> ImageFuture<'a> {
+        Box::pin(async move {
+            let url = format!(
+                "https://customsearch.googleapis.com/customsearch/v1?key={}&cx={}&q={}&searchType=image&num=1",
+                self.key,
+                self.engine_id,
+                urlencoding::encode(meal_name)
+            );
+            let data = reqwest::get(&url)
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| e.to_string())?
+                .json::<GoogleImageResult>()
+                .await
+                .map_err(|e| e.to_string())?;
+            data.items
+                .into_iter()
+                .next()
+                .map(|item| item.link)
+                .ok_or_else(|| format!("No images found for {meal_name}"))
+        })
+    }
+}
+
+/// Any image search over HTTP. `IMAGE_SEARCH_URL` has `{query}` where the meal
+/// name goes. With `IMAGE_SEARCH_FIELD`, a JSON pointer like `/results/0/url`, the
+/// image URL is read from the JSON response. Without it the URL is expected to
+/// respond with an image, and the URL it ends up at after redirects is used.
+pub struct HttpImages {
+    url: String,
+    field: Option<String>,
+}
+
+impl HttpImages {
+    pub fn from_env() -> Option<Self> {
+        Some(HttpImages {
+            url: std::env::var("IMAGE_SEARCH_URL").ok()?,
+            field: std::env::var("IMAGE_SEARCH_FIELD").ok(),
+        })
+    }
+}
+
+impl ImageProvider for HttpImages {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    fn find_image<'a>(&'a self, meal_name: &'a str) -> ImageFuture<'a> {
+        Box::pin(async move {
+            let url = self.url.replace("{query}", &urlencoding::encode(meal_name));
+            let response = reqwest::get(&url)
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| e.to_string())?;
+            match &self.field {
+                Some(field) => {
+                    let json = response
+                        .json::<serde_json::Value>()
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    url_at(&json, field)
+                        .ok_or_else(|| format!("No image URL at {field} for {meal_name}"))
+                }
+                None => {
+                    let is_image = response
+                        .headers()
+                        .get(reqwest::header::CONTENT_TYPE)
+                        .and_then(|t| t.to_str().ok())
+                        .is_some_and(|t| t.starts_with("image/"));
+                    if is_image {
+                        Ok(response.url().to_string())
+                    } else {
+                        Err(format!("{url} did not respond with an image"))
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// The string at the JSON pointer, if it is a URL.
+fn url_at(json: &serde_json::Value, pointer: &str) -> Option<String> {
+    json.pointer(pointer)
+        .and_then(serde_json::Value::as_str)
+        .filter(|url| url.starts_with("http"))
+        .map(str::to_owned)
+}
+
+/// Draws the initials of the meal on a colour picked from its name, so it works
+/// without network access or keys.
+pub struct Placeholder;
+
+impl ImageProvider for Placeholder {
+    fn name(&self) -> &'static str {
+        "placeholder"
+    }
+
+    fn find_image<'a>(&'a self, meal_name: &'a str) -> ImageFuture<'a> {
+        Box::pin(async move {
+            let svg = placeholder_svg(meal_name);
+            let name = format!("placeholder-{}.svg", content_hash(svg.as_bytes()));
+            let dir = image_dir();
+            std::fs::create_dir_all(&dir)
+                .and_then(|_| std::fs::write(dir.join(&name), svg))
+                .map_err(|e| format!("Could not save placeholder image: {e}"))?;
+            Ok(format!("{IMAGE_PATH}/{name}"))
+        })
+    }
+}
+
+/// Up to two letters, from the first two words of the name.
+fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+fn placeholder_svg(meal_name: &str) -> String {
+    let hue = u32::from_str_radix(&content_hash(meal_name.as_bytes())[..4], 16).unwrap_or(0) % 360;
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="400" height="300" viewBox="0 0 400 300"><rect width="400" height="300" fill="hsl({hue}, 55%, 45%)"/><text x="200" y="150" dominant-baseline="central" text-anchor="middle" font-family="sans-serif" font-size="120" font-weight="bold" fill="#fff">{}</text></svg>"##,
+        initials(meal_name)
+    )
+}
+
+/// The providers to try, in the order of `IMAGE_PROVIDERS` (comma separated
+/// names, by default `google,http`). Providers that aren't configured are left
+/// out, and the placeholder always comes last so there is always an image.
+pub fn providers() -> Vec<Box<dyn ImageProvider>> {
+    let order = std::env::var("IMAGE_PROVIDERS").unwrap_or_else(|_| String::from("google,http"));
+    let mut providers: Vec<Box<dyn ImageProvider>> = Vec::new();
+    for name in order.split(',').map(str::trim) {
+        let provider: Option<Box<dyn ImageProvider>> = match name {
+            "google" => GoogleImages::from_env().map(|p| Box::new(p) as _),
+            "http" => HttpImages::from_env().map(|p| Box::new(p) as _),
+            "placeholder" => Some(Box::new(Placeholder)),
+            "" => None,
+            _ => {
+                error!("Unknown image provider {name}");
+                None
+            }
+        };
+        providers.extend(provider);
+    }
+    if !providers.iter().any(|p| p.name() == "placeholder") {
+        providers.push(Box::new(Placeholder));
+    }
+    providers
+}
+
+/// Asks the providers in turn for an image for the meal, giving up with no image
+/// if they all fail.
+pub async fn find_image(providers: &[Box<dyn ImageProvider>], meal_name: &str) -> String {
+    for provider in providers {
+        match provider.find_image(meal_name).await {
+            Ok(url) => return url,
+            Err(e) => error!(
+                "Image provider {} failed for {meal_name}: {e}",
+                provider.name()
+            ),
+        }
+    }
+    String::new()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Failing;
+
+    impl ImageProvider for Failing {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn find_image<'a>(&'a self, _: &'a str) -> ImageFuture<'a> {
+            Box::pin(async { Err(String::from("quota exhausted")) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_image() {
+        assert_eq!(initials("taco fredag"), "TF");
+        assert_eq!(initials("  (vegetar) lasagne med salat"), "VL");
+        assert_eq!(initials("øllaks"), "Ø");
+        assert_eq!(initials(""), "");
+        assert_eq!(placeholder_svg("Taco"), placeholder_svg("Taco"));
+        assert!(placeholder_svg("Taco").contains(">T</text>"));
+
+        let json = serde_json::json!({ "results": [{ "url": "https://example.com/taco.jpg" }] });
+        assert_eq!(
+            url_at(&json, "/results/0/url").as_deref(),
+            Some("https://example.com/taco.jpg")
+        );
+        assert_eq!(url_at(&json, "/results/1/url"), None);
+
+        let dir = crate::images::test_image_dir();
+        let providers: Vec<Box<dyn ImageProvider>> = vec![Box::new(Failing), Box::new(Placeholder)];
+        let url = find_image(&providers, "Taco").await;
+        assert!(url.starts_with("/images/placeholder-") && url.ends_with(".svg"));
+        assert!(dir.join(&url["/images/".len()..]).exists());
+        assert_eq!(find_image(&providers[..1], "Taco").await, "");
+    }
+}
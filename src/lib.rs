@@ -1,4 +1,5 @@
 #![allow(clippy::unused_unit)]
+#![recursion_limit = "256"]
 
 pub mod api;
 pub mod app;
@@ -11,6 +12,11 @@ pub mod routes;
 
 #[cfg(feature = "ssr")]
 pub mod db;
+
+#[cfg(feature = "ssr")]
+pub mod images;
+#[cfg(feature = "ssr")]
+pub mod ocr;
 #[cfg(feature = "ssr")]
 pub mod schema;
 
@@ -1,4 +1,5 @@
 #![allow(clippy::unused_unit)]
+#![recursion_limit = "256"]
 
 pub mod models;
 pub mod utils;
@@ -10,12 +11,19 @@ pub mod schema;
 #[cfg(feature = "ssr")]
 pub mod db;
 
+#[cfg(feature = "ssr")]
+pub mod images;
+
+#[cfg(feature = "ssr")]
+pub mod ocr;
+
 #[cfg(feature = "ssr")]
 pub mod api;
 
 #[cfg(feature = "ssr")]
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
+    use crate::ocr::worker::OcrQueue;
     use crate::ws::server::{create_channel, ws_handler};
     use axum::{routing::get, Extension, Router};
     use dinner_planner::app::*;
@@ -37,6 +45,16 @@ async fn main() {
                 error!("Could not run migrations: {e}");
                 return;
             }
+            match models::ingredient_catalog::CatalogIngredient::normalize_keys(&mut con) {
+                Ok(0) => {}
+                Ok(count) => log!("Normalized {count} ingredient catalog keys"),
+                Err(e) => error!("Could not normalize ingredient catalog keys: {e}"),
+            }
+            match models::product::Product::link_catalog(&mut con) {
+                Ok(0) => {}
+                Ok(count) => log!("Linked {count} products to the ingredient catalog"),
+                Err(e) => error!("Could not link products to the ingredient catalog: {e}"),
+            }
         }
         Err(e) => {
             error!("Could not get DB pool: {e}");
@@ -45,9 +63,16 @@ async fn main() {
     }
 
     let broadcast_tx = create_channel();
+    let ocr_queue = OcrQueue::start(pool.clone(), broadcast_tx.clone());
+    if !ocr::normalize::heic_supported() {
+        error!("heif-convert from libheif is not installed, HEIC uploads will be refused");
+    }
+    // Meals saved before images were stored locally still link to other sites
+    tokio::spawn(images::cache_remote_images(pool.clone()));
 
     let app = Router::new()
         .route("/ws", get(ws_handler))
+        .route("/images/{file}", get(images::serve_image))
         .leptos_routes_with_context(
             &leptos_options,
             routes,
@@ -57,6 +82,7 @@ async fn main() {
                     provide_context(pool.clone());
                     provide_context(0 as utils::NotificationCount);
                     provide_context(broadcast_tx.clone());
+                    provide_context(ocr_queue.clone());
                 }
             },
             {
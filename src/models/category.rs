@@ -0,0 +1,227 @@
+use crate::models::money::Money;
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::models::receipt::ReceiptWithItems;
+#[cfg(feature = "ssr")]
+use crate::schema::{categories, category_rules};
+
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable, Identifiable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::categories))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct Category {
+    pub id: i32,
+    pub name: String,
+}
+
+#[cfg(feature = "ssr")]
+impl Category {
+    pub fn get_all(db: &mut DbConn) -> Result<Vec<Category>, Error> {
+        categories::table.order_by(categories::name).load(db)
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ssr", derive(Insertable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::category_rules))]
+pub struct CategoryRuleForm {
+    pub keyword: String,
+    pub category_id: i32,
+    pub manual: bool,
+}
+
+#[cfg(feature = "ssr")]
+impl CategoryRuleForm {
+    pub fn insert(&self, db: &mut DbConn) -> Result<CategoryRule, Error> {
+        insert_into(category_rules::table)
+            .values(self)
+            .get_result(db)
+    }
+}
+
+/// Gives items a category by their name, see `categorize`.
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable, Identifiable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::category_rules))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct CategoryRule {
+    pub id: i32,
+    /// Lowercase, a whole item name for manual rules
+    pub keyword: String,
+    pub category_id: i32,
+    /// Made by recategorising an item by hand
+    pub manual: bool,
+}
+
+#[cfg(feature = "ssr")]
+impl CategoryRule {
+    pub fn get_all(db: &mut DbConn) -> Result<Vec<CategoryRule>, Error> {
+        category_rules::table
+            .order_by(category_rules::keyword)
+            .load(db)
+    }
+
+    pub fn delete(db: &mut DbConn, id: i32) -> Result<usize, Error> {
+        delete(category_rules::table.find(id)).execute(db)
+    }
+
+    /// Remembers a category picked by hand for every later item with the same
+    /// name. `None` forgets it, so the keyword rules apply again.
+    pub fn remember(
+        db: &mut DbConn,
+        item_name: &str,
+        category_id: Option<i32>,
+    ) -> Result<(), Error> {
+        let keyword = normalize_name(item_name);
+        delete(
+            category_rules::table
+                .filter(category_rules::keyword.eq(&keyword))
+                .filter(category_rules::manual.eq(true)),
+        )
+        .execute(db)?;
+        if let Some(category_id) = category_id {
+            insert_into(category_rules::table)
+                .values(&CategoryRuleForm {
+                    keyword,
+                    category_id,
+                    manual: true,
+                })
+                .execute(db)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase with single spaces, so OCR spacing doesn't matter.
+#[cfg(feature = "ssr")]
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// The category for an item name. A manual rule for the exact name wins, otherwise
+/// the longest keyword that a word in the name starts or ends with, so "løk"
+/// matches "rødløk" but "ris" doesn't match "barista".
+#[cfg(feature = "ssr")]
+pub fn categorize(rules: &[CategoryRule], name: &str) -> Option<i32> {
+    let name = normalize_name(name);
+    if let Some(rule) = rules.iter().find(|r| r.manual && r.keyword == name) {
+        return Some(rule.category_id);
+    }
+    let padded = format!(" {name} ");
+    rules
+        .iter()
+        .filter(|r| !r.manual && !r.keyword.is_empty())
+        .filter(|r| {
+            padded.contains(&format!(" {}", r.keyword))
+                || padded.contains(&format!("{} ", r.keyword))
+        })
+        .max_by_key(|r| r.keyword.chars().count())
+        .map(|r| r.category_id)
+}
+
+/// What was spent on one category in one month.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct CategorySpending {
+    pub year: i32,
+    pub month: u32,
+    /// `None` for items without a category
+    pub category_id: Option<i32>,
+    pub total: Money,
+    /// In the order of `Member::ALL`
+    pub member_totals: [Money; 3],
+}
+
+/// Spending per category per month, newest month first and the biggest category
+/// first within a month. Discounts and deposits count towards the category of the
+/// item they belong to, and every item is split between the members like on the
+/// receipt.
+#[cfg(feature = "ssr")]
+pub fn category_report(receipts: &[ReceiptWithItems]) -> Vec<CategorySpending> {
+    use crate::models::member::Member;
+    use crate::models::receipt::{attach_to_products, item_weights, ItemKind};
+    use chrono::Datelike;
+    use std::collections::HashMap;
+
+    let mut spending: HashMap<(i32, u32, Option<i32>), [Money; 3]> = HashMap::new();
+    for receipt in receipts {
+        let date = receipt.receipt.datetime.date();
+        let receipt_shares = receipt.split_shares();
+        let attached =
+            attach_to_products(receipt.items.iter().map(|i| (i.kind, i.price, i.shares())));
+        let mut product_category = None;
+        for (item, (price, shares)) in receipt.items.iter().zip(attached) {
+            if item.kind == ItemKind::Product {
+                product_category = item.category_id;
+            }
+            let category_id = match item.kind.follows_product() {
+                true => product_category.or(item.category_id),
+                false => item.category_id,
+            };
+            let split = price.split(&item_weights(shares, receipt_shares));
+            let totals = spending
+                .entry((date.year(), date.month(), category_id))
+                .or_default();
+            for member in Member::ALL {
+                totals[member.index()] += split[member.index()];
+            }
+        }
+    }
+
+    let mut report: Vec<CategorySpending> = spending
+        .into_iter()
+        .map(
+            |((year, month, category_id), member_totals)| CategorySpending {
+                year,
+                month,
+                category_id,
+                total: member_totals.iter().sum(),
+                member_totals,
+            },
+        )
+        .collect();
+    report.sort_by(|a, b| {
+        (b.year, b.month)
+            .cmp(&(a.year, a.month))
+            .then(b.total.cmp(&a.total))
+            .then(a.category_id.cmp(&b.category_id))
+    });
+    report
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rule(keyword: &str, category_id: i32, manual: bool) -> CategoryRule {
+        CategoryRule {
+            id: 0,
+            keyword: String::from(keyword),
+            category_id,
+            manual,
+        }
+    }
+
+    #[test]
+    fn test_categorize() {
+        let rules = [
+            rule("løk", 1, false),
+            rule("ris", 2, false),
+            rule("melk", 3, false),
+            rule("sjokolade", 4, false),
+            rule("barista havredrikk", 3, true),
+        ];
+        assert_eq!(categorize(&rules, "RØDLØK 3PK"), Some(1));
+        assert_eq!(categorize(&rules, "Jasmin RIS 1kg"), Some(2));
+        assert_eq!(categorize(&rules, "Melkesjokolade"), Some(4));
+        assert_eq!(categorize(&rules, "BARISTA  HAVREDRIKK"), Some(3));
+        assert_eq!(categorize(&rules, "BARISTA"), None);
+    }
+}
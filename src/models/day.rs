@@ -1,6 +1,9 @@
+use crate::models::member::Member;
 use chrono::NaiveDate;
 #[cfg(feature = "ssr")]
 use diesel::prelude::*;
+#[cfg(feature = "ssr")]
+use std::collections::HashMap;
 
 #[cfg(feature = "ssr")]
 use crate::api::ssr::*;
@@ -11,9 +14,9 @@ use crate::api::ssr::*;
 #[cfg_attr(feature = "ssr", diesel(treat_none_as_null = true))]
 pub struct DayForm {
     pub date: NaiveDate,
-    pub meal_id: Option<i32>,
     pub week: i32,
     pub year: i32,
+    pub leftovers_from_day_id: Option<i32>,
 }
 
 #[cfg(feature = "ssr")]
@@ -28,23 +31,35 @@ impl DayForm {
     }
 }
 
-#[cfg_attr(
-    feature = "ssr",
-    derive(Queryable, Selectable, Identifiable, Associations)
-)]
-#[cfg_attr(feature = "ssr", diesel(belongs_to(crate::models::meal::Meal)))]
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable, Identifiable))]
 #[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::days))]
 #[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
 pub struct Day {
     pub id: i32,
     pub date: NaiveDate,
-    pub meal_id: Option<i32>,
     pub week: i32,
     pub year: i32,
     pub anders_attend: bool,
     pub ac_attend: bool,
     pub andreas_attend: bool,
+    /// The day the dinner was cooked, this day eats the leftovers
+    pub leftovers_from_day_id: Option<i32>,
+}
+
+impl Day {
+    pub fn attends(&self, member: Member) -> bool {
+        match member {
+            Member::Anders => self.anders_attend,
+            Member::Andreas => self.andreas_attend,
+            Member::Ac => self.ac_attend,
+        }
+    }
+
+    /// How many are eating dinner this day.
+    pub fn attendees(&self) -> i64 {
+        Member::ALL.into_iter().filter(|m| self.attends(*m)).count() as i64
+    }
 }
 
 #[cfg(feature = "ssr")]
@@ -63,8 +78,39 @@ impl Day {
             .load(db)
     }
 
+    /// The days the meal is one of the dishes.
     pub fn get_for_meal(db: &mut DbConn, id: i32) -> Result<Vec<Day>, Error> {
-        days::table.filter(days::meal_id.eq(id)).load(db)
+        use crate::schema::day_meals;
+
+        days::table
+            .inner_join(day_meals::table)
+            .filter(day_meals::meal_id.eq(id))
+            .select(Day::as_select())
+            .load(db)
+    }
+
+    /// The days eating leftovers of the dinner cooked this day.
+    pub fn get_leftover_days(db: &mut DbConn, id: i32) -> Result<Vec<Day>, Error> {
+        days::table
+            .filter(days::leftovers_from_day_id.eq(id))
+            .order_by(days::date)
+            .select(Day::as_select())
+            .load(db)
+    }
+
+    /// Portions of leftovers eaten from each day that has any, by day id.
+    pub fn leftover_portions(db: &mut DbConn) -> Result<HashMap<i32, i64>, Error> {
+        let leftover_days = days::table
+            .filter(days::leftovers_from_day_id.is_not_null())
+            .select(Day::as_select())
+            .load(db)?;
+        let mut portions = HashMap::new();
+        for day in leftover_days {
+            if let Some(source_id) = day.leftovers_from_day_id {
+                *portions.entry(source_id).or_default() += day.attendees();
+            }
+        }
+        Ok(portions)
     }
 
     pub fn get_by_receipt(db: &mut DbConn, receipt_id: i32) -> Result<Option<Vec<Day>>, Error> {
@@ -0,0 +1,149 @@
+use crate::models::text_enum_sql;
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::models::money::Money;
+#[cfg(feature = "ssr")]
+use crate::schema::day_meals;
+#[cfg(feature = "ssr")]
+use std::collections::HashMap;
+
+/// What a dish is in the dinner. Dishes are listed in this order.
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[cfg_attr(feature = "ssr", derive(diesel::AsExpression, diesel::FromSqlRow))]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum Course {
+    #[default]
+    Main,
+    Side,
+    Dessert,
+}
+
+impl Course {
+    pub const ALL: [Course; 3] = [Course::Main, Course::Side, Course::Dessert];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Course::Main => "main",
+            Course::Side => "side",
+            Course::Dessert => "dessert",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Course::Main => "Main",
+            Course::Side => "Side",
+            Course::Dessert => "Dessert",
+        }
+    }
+}
+
+impl std::str::FromStr for Course {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Course::ALL
+            .into_iter()
+            .find(|c| c.as_str() == s)
+            .ok_or_else(|| format!("Unknown course {s}"))
+    }
+}
+
+text_enum_sql!(Course);
+
+/// A dish served on a day. From the client `day_id` is ignored, the day is set
+/// when saving it.
+#[cfg_attr(
+    feature = "ssr",
+    derive(Queryable, Selectable, Insertable, Identifiable, Associations)
+)]
+#[cfg_attr(feature = "ssr", diesel(belongs_to(crate::models::day::Day)))]
+#[cfg_attr(feature = "ssr", diesel(belongs_to(crate::models::meal::Meal)))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::day_meals))]
+#[cfg_attr(feature = "ssr", diesel(primary_key(day_id, meal_id)))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct DayMeal {
+    pub day_id: i32,
+    pub meal_id: i32,
+    pub course: Course,
+}
+
+#[cfg(feature = "ssr")]
+impl DayMeal {
+    /// Ordered by course, and in the order they were added.
+    pub fn get_for_day(db: &mut DbConn, day_id: i32) -> Result<Vec<DayMeal>, Error> {
+        let mut dishes: Vec<DayMeal> = day_meals::table
+            .filter(day_meals::day_id.eq(day_id))
+            .order_by(diesel::dsl::sql::<diesel::sql_types::Integer>("rowid"))
+            .select(DayMeal::as_select())
+            .load(db)?;
+        dishes.sort_by_key(|dish| dish.course);
+        Ok(dishes)
+    }
+
+    /// Replaces the dishes of the day. A meal added twice is kept once, with the
+    /// first course it was given.
+    pub fn set_for_day(
+        db: &mut DbConn,
+        day_id: i32,
+        dishes: &[DayMeal],
+    ) -> Result<Vec<DayMeal>, Error> {
+        delete(day_meals::table.filter(day_meals::day_id.eq(day_id))).execute(db)?;
+        let mut kept: Vec<DayMeal> = Vec::new();
+        for dish in dishes {
+            if !kept.iter().any(|k| k.meal_id == dish.meal_id) {
+                kept.push(DayMeal {
+                    day_id,
+                    ..dish.clone()
+                });
+            }
+        }
+        insert_into(day_meals::table).values(&kept).execute(db)?;
+        kept.sort_by_key(|dish| dish.course);
+        Ok(kept)
+    }
+
+    /// What each dish cost, by day and meal id. The receipts of a day are charged to
+    /// its main course, split evenly if there are several, so sides and desserts
+    /// don't count the day again.
+    pub fn costs_by_dish(db: &mut DbConn) -> Result<HashMap<(i32, i32), Money>, Error> {
+        use crate::models::receipt::ReceiptDay;
+
+        let day_costs = ReceiptDay::costs_by_day(db)?;
+        let mains: Vec<DayMeal> = day_meals::table
+            .filter(day_meals::course.eq(Course::Main))
+            .order_by((day_meals::day_id, day_meals::meal_id))
+            .select(DayMeal::as_select())
+            .load(db)?;
+        let mut meal_ids: HashMap<i32, Vec<i32>> = HashMap::new();
+        for dish in mains {
+            meal_ids.entry(dish.day_id).or_default().push(dish.meal_id);
+        }
+        let mut costs = HashMap::new();
+        for (day_id, meal_ids) in meal_ids {
+            let Some(cost) = day_costs.get(&day_id) else {
+                continue;
+            };
+            for (meal_id, cost) in meal_ids.iter().zip(cost.split(&vec![1; meal_ids.len()])) {
+                costs.insert((day_id, *meal_id), cost);
+            }
+        }
+        Ok(costs)
+    }
+}
@@ -1,7 +1,11 @@
 use crate::models::{
     day::Day,
+    day_meal::Course,
     ingredient::Ingredient,
-    meal::Meal, receipt::ReceiptWithItems,
+    food::{MealNutrition, Nutrition}, meal::Meal, meal_rating::MealRating, member::Member,
+    money::Money,
+    receipt::ReceiptWithItems,
+    restriction::{find_conflicts, Conflict, MemberRestriction},
 };
 #[cfg(feature = "ssr")]
 use diesel::prelude::*;
@@ -12,8 +16,160 @@ use crate::api::ssr::*;
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct DayWithMealAndIngredients {
     pub day: Day,
-    pub meal: Option<(Meal, Vec<IngredientWithBought>)>,
-    pub receipts: Option<Vec<ReceiptWithItems>>
+    /// The dishes served, ordered by course
+    pub meals: Vec<DayMealWithIngredients>,
+    pub receipts: Option<Vec<ReceiptWithItems>>,
+    /// Ratings of the main dish served this day
+    pub ratings: Vec<MealRating>,
+    /// The day the dinner was cooked, when this day eats leftovers. The dishes are
+    /// the ones of that day, bought for by it.
+    pub leftovers_from: Option<Day>,
+    /// The days eating leftovers of this dinner, its ingredients are scaled up for
+    /// them
+    pub leftovers_on: Vec<Day>,
+}
+
+/// A dish of the day with the ingredients to buy for it.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct DayMealWithIngredients {
+    pub meal: Meal,
+    pub course: Course,
+    pub ingredients: Vec<IngredientWithBought>,
+    /// What the dish is made of when the day eats leftovers of it. Only for
+    /// checking allergens, nothing is bought for them.
+    #[serde(default)]
+    pub leftover_ingredients: Vec<Ingredient>,
+    pub nutrition: Option<MealNutrition>,
+}
+
+impl DayWithMealAndIngredients {
+    /// What the dinner cost, from the receipts linked to the day.
+    pub fn cost(&self) -> Option<Money> {
+        self.receipts
+            .as_ref()
+            .map(|receipts| receipts.iter().map(|r| r.cost_for_day(self.day.id)).sum())
+    }
+
+    /// The dish the dinner is rated by, the first main course.
+    pub fn main_meal(&self) -> Option<&Meal> {
+        self.meals
+            .iter()
+            .find(|dish| dish.course == Course::Main)
+            .map(|dish| &dish.meal)
+    }
+
+    /// The names of the dishes, like "Taco + Salad".
+    pub fn meal_names(&self) -> String {
+        self.meals
+            .iter()
+            .map(|dish| dish.meal.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /// The ingredients of every dish.
+    pub fn ingredients(&self) -> impl Iterator<Item = &IngredientWithBought> {
+        self.meals.iter().flat_map(|dish| dish.ingredients.iter())
+    }
+
+    /// Ingredients someone eating the dinner can't have. The leftovers are eaten
+    /// by the attendees of the days in `leftovers_on` as well.
+    pub fn conflicts(&self, restrictions: &[MemberRestriction]) -> Vec<Conflict> {
+        let eats = |member: &Member| {
+            self.day.attends(*member) || self.leftovers_on.iter().any(|day| day.attends(*member))
+        };
+        let ingredients = self.ingredients().map(|i| &i.ingredient).chain(
+            self.meals
+                .iter()
+                .flat_map(|dish| dish.leftover_ingredients.iter()),
+        );
+        find_conflicts(restrictions, Member::ALL.into_iter().filter(eats), ingredients)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl DayWithMealAndIngredients {
+    pub fn load(db: &mut DbConn, day: Day) -> Result<DayWithMealAndIngredients, Error> {
+        use crate::models::day_meal::DayMeal;
+
+        let leftovers_from = day
+            .leftovers_from_day_id
+            .map(|id| Day::get(db, id))
+            .transpose()?;
+        let leftovers_on = Day::get_leftover_days(db, day.id)?;
+        let extra_portions = leftovers_on.iter().map(Day::attendees).sum();
+        // Leftover days have no shopping of their own
+        let day_ingredients = DayIngredient::belonging_to(&day)
+            .inner_join(ingredients::table.inner_join(ingredient_catalog::table))
+            .select((DayIngredient::as_select(), Ingredient::as_select()))
+            .load::<(DayIngredient, Ingredient)>(db)?;
+        let cooked_on = leftovers_from.as_ref().map_or(day.id, |source| source.id);
+        let mut meals = Vec::new();
+        for dish in DayMeal::get_for_day(db, cooked_on)? {
+            let meal = Meal::get(db, dish.meal_id)?;
+            let ingredients = day_ingredients
+                .iter()
+                .filter(|(_, ingredient)| ingredient.meal_id == meal.id)
+                .map(|(di, ingredient)| IngredientWithBought {
+                    day_id: di.day_id,
+                    ingredient: Ingredient {
+                        amount: scale_amount(ingredient.amount, meal.servings, extra_portions),
+                        ..ingredient.clone()
+                    },
+                    bought: di.bought,
+                })
+                .collect();
+            let leftover_ingredients = if leftovers_from.is_some() {
+                Ingredient::get_for_meal(db, meal.id)?
+            } else {
+                vec![]
+            };
+            meals.push(DayMealWithIngredients {
+                nutrition: MealNutrition::for_meal(db, &meal)?,
+                meal,
+                course: dish.course,
+                ingredients,
+                leftover_ingredients,
+            });
+        }
+        let ratings = match meals.iter().find(|dish| dish.course == Course::Main) {
+            Some(main) => MealRating::get_for_day(db, day.id, main.meal.id)?,
+            None => vec![],
+        };
+        let receipts = ReceiptWithItems::get_by_day(db, day.id)?;
+        Ok(DayWithMealAndIngredients {
+            day,
+            meals,
+            receipts,
+            ratings,
+            leftovers_from,
+            leftovers_on,
+        })
+    }
+}
+
+/// The amount of an ingredient for the meal's servings and `extra` more portions,
+/// rounded up.
+#[cfg(feature = "ssr")]
+pub fn scale_amount(amount: i32, servings: i32, extra: i64) -> i32 {
+    if extra <= 0 || servings <= 0 {
+        return amount;
+    }
+    let servings = i64::from(servings);
+    let scaled = (i64::from(amount) * (servings + extra) + servings - 1) / servings;
+    scaled as i32
+}
+
+/// What one person eating every dinner of the days gets, from the dishes with
+/// nutrition.
+pub fn nutrition_per_person(days: &[DayWithMealAndIngredients]) -> Option<Nutrition> {
+    let servings: Vec<Nutrition> = days
+        .iter()
+        .flat_map(|day| day.meals.iter())
+        .filter_map(|dish| dish.nutrition.as_ref())
+        .map(|nutrition| nutrition.per_serving())
+        .collect();
+    (!servings.is_empty()).then(|| servings.into_iter().sum())
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
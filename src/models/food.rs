@@ -0,0 +1,548 @@
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+#[cfg(feature = "ssr")]
+use serde::Deserialize;
+#[cfg(feature = "ssr")]
+use std::collections::HashMap;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::schema::foods;
+
+/// An entry in the imported food composition table, with values per 100 g.
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable, Identifiable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::foods))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct Food {
+    pub id: i32,
+    /// The id in the imported table, e.g. "06.530" in Matvaretabellen
+    pub source_id: String,
+    pub name: String,
+    pub kcal: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbs: f64,
+}
+
+impl Food {
+    pub fn per_100g(&self) -> Nutrition {
+        Nutrition {
+            kcal: self.kcal,
+            protein: self.protein,
+            fat: self.fat,
+            carbs: self.carbs,
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Insertable, AsChangeset, Clone, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::foods)]
+pub struct FoodForm {
+    pub source_id: String,
+    pub name: String,
+    pub kcal: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbs: f64,
+}
+
+#[cfg(feature = "ssr")]
+impl FoodForm {
+    /// Inserts the foods, updating the ones imported before.
+    pub fn upsert_all(db: &mut DbConn, foods: &[FoodForm]) -> Result<usize, Error> {
+        db.transaction(|db| {
+            for food in foods {
+                insert_into(foods::table)
+                    .values(food)
+                    .on_conflict(foods::source_id)
+                    .do_update()
+                    .set(food)
+                    .execute(db)?;
+            }
+            Ok(foods.len())
+        })
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl Food {
+    pub fn get(db: &mut DbConn, id: i32) -> Result<Food, Error> {
+        foods::table.find(id).first(db)
+    }
+
+    /// Foods with every word of the query in the name, shortest names first since
+    /// they tend to be the plain ingredient.
+    pub fn search(db: &mut DbConn, query: &str, limit: i64) -> Result<Vec<Food>, Error> {
+        use diesel::dsl::sql;
+
+        let mut search = foods::table.select(Food::as_select()).into_boxed();
+        for word in query.split_whitespace() {
+            search = search.filter(foods::name.like(format!("%{word}%")));
+        }
+        search
+            .order(sql::<diesel::sql_types::Integer>("length(name)"))
+            .limit(limit)
+            .load(db)
+    }
+
+    /// The foods catalog ingredients are linked to.
+    pub fn get_linked(db: &mut DbConn) -> Result<Vec<Food>, Error> {
+        foods::table
+            .inner_join(ingredient_catalog::table)
+            .select(Food::as_select())
+            .distinct()
+            .load(db)
+    }
+
+    /// Links catalog ingredients without a food to the food named the same, so
+    /// "Løk" gets "Løk, rå". Returns how many were linked.
+    pub fn link_catalog(db: &mut DbConn) -> Result<usize, Error> {
+        use crate::models::category::normalize_name;
+
+        let mut by_name: HashMap<String, Food> = HashMap::new();
+        for food in foods::table.select(Food::as_select()).load(db)? {
+            let key = normalize_name(food.name.split(',').next().unwrap_or_default());
+            let shorter = by_name
+                .get(&key)
+                .is_none_or(|other| food.name.len() < other.name.len());
+            if shorter {
+                by_name.insert(key, food);
+            }
+        }
+        let unlinked: Vec<(i32, String)> = ingredient_catalog::table
+            .filter(ingredient_catalog::food_id.is_null())
+            .select((ingredient_catalog::id, ingredient_catalog::key))
+            .load(db)?;
+        let mut linked = 0;
+        for (id, key) in unlinked {
+            if let Some(food) = by_name.get(&key) {
+                linked += update(ingredient_catalog::table.find(id))
+                    .set(ingredient_catalog::food_id.eq(food.id))
+                    .execute(db)?;
+            }
+        }
+        Ok(linked)
+    }
+}
+
+/// Energy and macros, in kcal and grams.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Nutrition {
+    pub kcal: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbs: f64,
+}
+
+impl Nutrition {
+    pub fn scaled(&self, factor: f64) -> Nutrition {
+        Nutrition {
+            kcal: self.kcal * factor,
+            protein: self.protein * factor,
+            fat: self.fat * factor,
+            carbs: self.carbs * factor,
+        }
+    }
+}
+
+impl std::ops::Add for Nutrition {
+    type Output = Nutrition;
+
+    fn add(self, other: Nutrition) -> Nutrition {
+        Nutrition {
+            kcal: self.kcal + other.kcal,
+            protein: self.protein + other.protein,
+            fat: self.fat + other.fat,
+            carbs: self.carbs + other.carbs,
+        }
+    }
+}
+
+impl std::iter::Sum for Nutrition {
+    fn sum<I: Iterator<Item = Nutrition>>(iter: I) -> Nutrition {
+        iter.fold(Nutrition::default(), |sum, n| sum + n)
+    }
+}
+
+impl std::fmt::Display for Nutrition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.0} kcal · {:.0} g protein · {:.0} g fat · {:.0} g carbs",
+            self.kcal, self.protein, self.fat, self.carbs
+        )
+    }
+}
+
+/// Grams of `amount` of an ingredient, from what one unit weighs or from the unit
+/// itself for weights and volumes. Liquids are counted as water.
+#[cfg(feature = "ssr")]
+pub fn grams(amount: i32, unit: Option<&str>, grams_per_unit: Option<f64>) -> Option<f64> {
+    let per_unit = match (grams_per_unit, unit.map(|u| u.trim().to_lowercase())) {
+        (Some(grams), _) => grams,
+        (None, Some(unit)) => match unit.as_str() {
+            "g" | "ml" => 1.0,
+            "cl" => 10.0,
+            "dl" => 100.0,
+            "kg" | "l" => 1000.0,
+            _ => return None,
+        },
+        (None, None) => return None,
+    };
+    Some(amount as f64 * per_unit)
+}
+
+/// What a meal adds up to from the foods its ingredients are linked to.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct MealNutrition {
+    pub total: Nutrition,
+    pub servings: i32,
+    /// Ingredients without a food or a known weight, so not counted
+    pub missing: Vec<String>,
+}
+
+impl MealNutrition {
+    pub fn per_serving(&self) -> Nutrition {
+        self.total.scaled(1.0 / self.servings.max(1) as f64)
+    }
+}
+
+/// An ingredient's meal, name, amount, unit, grams per unit and food.
+#[cfg(feature = "ssr")]
+type IngredientFoodRow = (i32, String, i32, Option<String>, Option<f64>, Option<Food>);
+
+/// An ingredient of a meal with what is needed for its nutrition.
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug)]
+pub struct IngredientFood {
+    pub name: String,
+    pub amount: i32,
+    pub unit: Option<String>,
+    pub grams_per_unit: Option<f64>,
+    pub food: Option<Food>,
+}
+
+#[cfg(feature = "ssr")]
+impl MealNutrition {
+    /// Nothing when none of the ingredients could be counted.
+    pub fn from_ingredients(
+        servings: i32,
+        ingredients: &[IngredientFood],
+    ) -> Option<MealNutrition> {
+        let mut total = Nutrition::default();
+        let mut missing = Vec::new();
+        for ingredient in ingredients {
+            let grams = grams(
+                ingredient.amount,
+                ingredient.unit.as_deref(),
+                ingredient.grams_per_unit,
+            );
+            match (&ingredient.food, grams) {
+                (Some(food), Some(grams)) => total = total + food.per_100g().scaled(grams / 100.0),
+                _ => missing.push(ingredient.name.clone()),
+            }
+        }
+        (missing.len() < ingredients.len()).then_some(MealNutrition {
+            total,
+            servings,
+            missing,
+        })
+    }
+
+    /// The nutrition of each of the meals that has any.
+    pub fn for_meals(
+        db: &mut DbConn,
+        meals: &[Meal],
+    ) -> Result<HashMap<i32, MealNutrition>, Error> {
+        let meal_ids: Vec<i32> = meals.iter().map(|m| m.id).collect();
+        let rows: Vec<IngredientFoodRow> = ingredients::table
+            .inner_join(ingredient_catalog::table.left_join(foods::table))
+            .filter(ingredients::meal_id.eq_any(&meal_ids))
+            .select((
+                ingredients::meal_id,
+                ingredient_catalog::name,
+                ingredients::amount,
+                ingredient_catalog::unit,
+                ingredient_catalog::grams_per_unit,
+                Option::<Food>::as_select(),
+            ))
+            .load(db)?;
+        let mut by_meal: HashMap<i32, Vec<IngredientFood>> = HashMap::new();
+        for (meal_id, name, amount, unit, grams_per_unit, food) in rows {
+            by_meal.entry(meal_id).or_default().push(IngredientFood {
+                name,
+                amount,
+                unit,
+                grams_per_unit,
+                food,
+            });
+        }
+        Ok(meals
+            .iter()
+            .filter_map(|meal| {
+                let ingredients = by_meal.get(&meal.id)?;
+                let nutrition = MealNutrition::from_ingredients(meal.servings, ingredients)?;
+                Some((meal.id, nutrition))
+            })
+            .collect())
+    }
+
+    pub fn for_meal(db: &mut DbConn, meal: &Meal) -> Result<Option<MealNutrition>, Error> {
+        Ok(Self::for_meals(db, std::slice::from_ref(meal))?.remove(&meal.id))
+    }
+}
+
+/// A food composition table as exported by Matvaretabellen, e.g.
+///
+/// ```json
+/// {
+///   "foods": [
+///     {
+///       "foodId": "06.530",
+///       "foodName": "Løk, rå",
+///       "calories": { "quantity": 36, "unit": "kcal" },
+///       "constituents": [
+///         { "nutrientId": "Protein", "quantity": 1.2, "unit": "g" },
+///         { "nutrientId": "Fett", "quantity": 0.1, "unit": "g" },
+///         { "nutrientId": "Karbo", "quantity": 6.5, "unit": "g" }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Constituents without a quantity count as 0.
+#[cfg(feature = "ssr")]
+#[derive(Debug, Deserialize)]
+struct FoodTable {
+    foods: Vec<TableFood>,
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TableFood {
+    food_id: String,
+    food_name: String,
+    calories: Option<Quantity>,
+    #[serde(default)]
+    constituents: Vec<Constituent>,
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Constituent {
+    nutrient_id: String,
+    quantity: Option<f64>,
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Debug, Deserialize)]
+struct Quantity {
+    quantity: Option<f64>,
+}
+
+#[cfg(feature = "ssr")]
+impl TableFood {
+    fn constituent(&self, ids: &[&str]) -> f64 {
+        self.constituents
+            .iter()
+            .find(|c| ids.iter().any(|id| c.nutrient_id.eq_ignore_ascii_case(id)))
+            .and_then(|c| c.quantity)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Reads a food composition table, either Matvaretabellen's JSON (see `FoodTable`)
+/// or a CSV with a header row. The CSV may be separated by semicolons, commas or
+/// tabs and use decimal commas, and needs columns named like `Matvare ID`,
+/// `Matvare`, `Kilokalorier`, `Protein`, `Fett` and `Karbohydrat` or their
+/// English names.
+#[cfg(feature = "ssr")]
+pub fn parse_foods(text: &str) -> Result<Vec<FoodForm>, String> {
+    let text = text.trim_start_matches('\u{feff}').trim();
+    if text.starts_with('{') {
+        let table: FoodTable =
+            serde_json::from_str(text).map_err(|e| format!("Not a valid food table: {e}"))?;
+        return Ok(table
+            .foods
+            .into_iter()
+            .map(|food| FoodForm {
+                kcal: food
+                    .calories
+                    .as_ref()
+                    .and_then(|c| c.quantity)
+                    .unwrap_or(0.0),
+                protein: food.constituent(&["Protein"]),
+                fat: food.constituent(&["Fett", "Fat"]),
+                carbs: food.constituent(&["Karbo", "Carbohydrates"]),
+                source_id: food.food_id,
+                name: food.food_name,
+            })
+            .collect());
+    }
+    parse_foods_csv(text)
+}
+
+#[cfg(feature = "ssr")]
+fn parse_foods_csv(text: &str) -> Result<Vec<FoodForm>, String> {
+    const ID: [&str; 4] = ["matvare id", "matvareid", "food id", "id"];
+    const NAME: [&str; 4] = ["matvare", "food name", "food", "name"];
+    const KCAL: [&str; 4] = ["kilokalorier", "kcal", "energy (kcal)", "calories"];
+    const PROTEIN: [&str; 1] = ["protein"];
+    const FAT: [&str; 2] = ["fett", "fat"];
+    const CARBS: [&str; 4] = ["karbohydrat", "karbo", "carbohydrates", "carbs"];
+
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = lines.next().ok_or("The file is empty")?;
+    let delimiter = [';', '\t', ',']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d).count())
+        .unwrap_or(';');
+    let header: Vec<String> = split_csv_line(header, delimiter)
+        .into_iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| header.iter().position(|h| h == name))
+            .ok_or_else(|| format!("Missing a column named {}", names[0]))
+    };
+    let (id, name) = (column(&ID)?, column(&NAME)?);
+    let (kcal, protein, fat, carbs) = (
+        column(&KCAL)?,
+        column(&PROTEIN)?,
+        column(&FAT)?,
+        column(&CARBS)?,
+    );
+
+    lines
+        .enumerate()
+        .map(|(row, line)| {
+            let fields = split_csv_line(line, delimiter);
+            let text = |i: usize| fields.get(i).map(String::as_str).unwrap_or_default();
+            // Missing values are blank or marked, e.g. "M" or "-"
+            let number = |i: usize| text(i).replace(',', ".").parse::<f64>().unwrap_or(0.0);
+            if text(id).is_empty() || text(name).is_empty() {
+                return Err(format!("Row {} has no id or name", row + 2));
+            }
+            Ok(FoodForm {
+                source_id: text(id).to_owned(),
+                name: text(name).to_owned(),
+                kcal: number(kcal),
+                protein: number(protein),
+                fat: number(fat),
+                carbs: number(carbs),
+            })
+        })
+        .collect()
+}
+
+/// Fields of a CSV line, without quotes and surrounding spaces.
+#[cfg(feature = "ssr")]
+fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields.into_iter().map(|f| f.trim().to_owned()).collect()
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_foods() {
+        let json = r#"{"foods": [{
+            "foodId": "06.530",
+            "foodName": "Løk, rå",
+            "calories": {"quantity": 36, "unit": "kcal"},
+            "constituents": [
+                {"nutrientId": "Protein", "quantity": 1.2, "unit": "g"},
+                {"nutrientId": "Fett", "quantity": 0.1, "unit": "g"},
+                {"nutrientId": "Karbo", "quantity": 6.5, "unit": "g"},
+                {"nutrientId": "Fiber", "unit": "g"}
+            ]
+        }]}"#;
+        assert_eq!(
+            parse_foods(json).unwrap(),
+            [FoodForm {
+                source_id: String::from("06.530"),
+                name: String::from("Løk, rå"),
+                kcal: 36.0,
+                protein: 1.2,
+                fat: 0.1,
+                carbs: 6.5,
+            }]
+        );
+
+        let csv = "\u{feff}Matvare ID;Matvare;Kilokalorier;Fett;Karbohydrat;Protein\n\
+            04.142;\"Laks, oppdrett, rå\";199;13,4;0;20,2\n\
+            \n\
+            05.023;Ris, hvit, kokt;130;0,3;28,9;M\n";
+        let foods = parse_foods(csv).unwrap();
+        assert_eq!(foods.len(), 2);
+        assert_eq!(foods[0].name, "Laks, oppdrett, rå");
+        assert_eq!(foods[0].fat, 13.4);
+        assert_eq!(foods[1].protein, 0.0);
+
+        let csv = "id,name,kcal,protein,fat,carbs\nx1,Tortilla,310,8.5,7,52";
+        assert_eq!(parse_foods(csv).unwrap()[0].carbs, 52.0);
+        assert!(parse_foods("Matvare;Kilokalorier\nLøk;36").is_err());
+    }
+
+    #[test]
+    fn test_meal_nutrition() {
+        assert_eq!(grams(2, Some("stk"), Some(120.0)), Some(240.0));
+        assert_eq!(grams(400, Some("g"), None), Some(400.0));
+        assert_eq!(grams(3, Some("dl"), None), Some(300.0));
+        assert_eq!(grams(2, Some("stk"), None), None);
+
+        let food = |kcal, protein| Food {
+            id: 1,
+            source_id: String::new(),
+            name: String::new(),
+            kcal,
+            protein,
+            fat: 0.0,
+            carbs: 0.0,
+        };
+        let ingredient = |name: &str, amount, unit: &str, food| IngredientFood {
+            name: String::from(name),
+            amount,
+            unit: Some(String::from(unit)),
+            grams_per_unit: None,
+            food,
+        };
+        let nutrition = MealNutrition::from_ingredients(
+            2,
+            &[
+                ingredient("Kjøttdeig", 400, "g", Some(food(200.0, 20.0))),
+                ingredient("Tortilla", 8, "stk", Some(food(300.0, 8.0))),
+                ingredient("Ris", 3, "dl", Some(food(100.0, 2.0))),
+            ],
+        )
+        .unwrap();
+        assert_eq!(nutrition.total.kcal, 1100.0);
+        assert_eq!(nutrition.per_serving().protein, 43.0);
+        assert_eq!(nutrition.missing, ["Tortilla"]);
+        assert!(MealNutrition::from_ingredients(3, &[ingredient("Salt", 1, "g", None)]).is_none());
+    }
+}
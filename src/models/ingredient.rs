@@ -3,20 +3,42 @@ use diesel::prelude::*;
 
 #[cfg(feature = "ssr")]
 use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::models::ingredient_catalog::CatalogIngredient;
+#[cfg(feature = "ssr")]
+use crate::schema::ingredient_catalog;
 
+/// An ingredient as typed in the meal form, the name is looked up in the catalog
+/// when it is saved.
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
-#[cfg_attr(feature = "ssr", derive(Insertable))]
-#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::ingredients))]
 pub struct IngredientForm {
     pub name: String,
     pub amount: i32,
     pub meal_id: i32,
 }
 
+#[cfg(feature = "ssr")]
+#[derive(Insertable)]
+#[diesel(table_name = crate::schema::ingredients)]
+struct NewIngredient {
+    amount: i32,
+    meal_id: i32,
+    catalog_id: i32,
+}
+
 #[cfg(feature = "ssr")]
 impl IngredientForm {
     pub fn insert(&self, db: &mut DbConn) -> Result<Ingredient, Error> {
-        insert_into(ingredients::table).values(self).get_result(db)
+        let entry = CatalogIngredient::resolve(db, &self.name)?;
+        let id = insert_into(ingredients::table)
+            .values(NewIngredient {
+                amount: self.amount,
+                meal_id: self.meal_id,
+                catalog_id: entry.id,
+            })
+            .returning(ingredients::id)
+            .get_result(db)?;
+        Ingredient::get(db, id)
     }
 }
 
@@ -30,9 +52,16 @@ impl IngredientForm {
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct Ingredient {
     pub id: i32,
+    /// From the catalog, so queries have to join `ingredient_catalog`
+    #[cfg_attr(feature = "ssr", diesel(select_expression = ingredient_catalog::name))]
     pub name: String,
     pub amount: i32,
     pub meal_id: i32,
+    #[cfg_attr(feature = "ssr", diesel(select_expression = ingredient_catalog::id))]
+    pub catalog_id: i32,
+    /// From the catalog, comma separated
+    #[cfg_attr(feature = "ssr", diesel(select_expression = ingredient_catalog::allergens))]
+    pub allergens: String,
 }
 
 #[cfg(feature = "ssr")]
@@ -43,12 +72,12 @@ impl Ingredient {
             .execute(db)
     }
     pub fn get_all(db: &mut DbConn) -> Result<Vec<Ingredient>, Error> {
-        ingredients::table.select(Ingredient::as_select()).load(db)
+        ingredients::table.inner_join(ingredient_catalog::table).select(Ingredient::as_select()).load(db)
     }
     pub fn get(db: &mut DbConn, id: i32) -> Result<Ingredient, Error> {
-        ingredients::table.filter(ingredients::id.eq(id)).select(Ingredient::as_select()).first(db)
+        ingredients::table.inner_join(ingredient_catalog::table).filter(ingredients::id.eq(id)).select(Ingredient::as_select()).first(db)
     }
     pub fn get_for_meal(db: &mut DbConn, meal_id: i32) -> Result<Vec<Ingredient>, Error> {
-        ingredients::table.filter(ingredients::meal_id.eq(meal_id)).select(Ingredient::as_select()).load(db)
+        ingredients::table.inner_join(ingredient_catalog::table).filter(ingredients::meal_id.eq(meal_id)).select(Ingredient::as_select()).load(db)
     }
 }
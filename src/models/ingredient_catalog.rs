@@ -0,0 +1,187 @@
+use crate::models::meal_tag::parse_tags;
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::models::category::normalize_name;
+#[cfg(feature = "ssr")]
+use crate::schema::{ingredient_catalog, products};
+
+/// An ingredient every meal shares, so "Løk" in one meal is the same as "løk" in
+/// another. Meal ingredients only hold the amount, see `Ingredient`.
+#[cfg_attr(
+    feature = "ssr",
+    derive(Queryable, Selectable, Identifiable, AsChangeset)
+)]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::ingredient_catalog))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[cfg_attr(feature = "ssr", diesel(treat_none_as_null = true))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct CatalogIngredient {
+    pub id: i32,
+    pub name: String,
+    /// The name lowercase with single spaces, kept up to date by the server
+    pub key: String,
+    /// Other names for the same thing, one per line
+    pub aliases: String,
+    /// e.g. `stk`, `g` or `dl`
+    pub unit: Option<String>,
+    pub category_id: Option<i32>,
+    /// Comma separated, see `allergen_list`
+    pub allergens: String,
+    /// The entry in the food composition table, for nutrition
+    pub food_id: Option<i32>,
+    /// What one `unit` weighs, not needed for grams and litres
+    pub grams_per_unit: Option<f64>,
+}
+
+impl CatalogIngredient {
+    pub fn alias_list(&self) -> Vec<&str> {
+        self.aliases
+            .lines()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .collect()
+    }
+
+    pub fn allergen_list(&self) -> Vec<String> {
+        parse_tags(&self.allergens)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl CatalogIngredient {
+    pub fn get_all(db: &mut DbConn) -> Result<Vec<CatalogIngredient>, Error> {
+        ingredient_catalog::table
+            .order_by(ingredient_catalog::key)
+            .load(db)
+    }
+
+    /// The entry with this name or alias, ignoring case and spacing.
+    pub fn find(db: &mut DbConn, name: &str) -> Result<Option<CatalogIngredient>, Error> {
+        let key = normalize_name(name);
+        let by_name = ingredient_catalog::table
+            .filter(ingredient_catalog::key.eq(&key))
+            .first(db)
+            .optional()?;
+        if by_name.is_some() {
+            return Ok(by_name);
+        }
+        let with_aliases: Vec<CatalogIngredient> = ingredient_catalog::table
+            .filter(ingredient_catalog::aliases.ne(""))
+            .load(db)?;
+        Ok(with_aliases.into_iter().find(|entry| {
+            entry
+                .alias_list()
+                .iter()
+                .any(|alias| normalize_name(alias) == key)
+        }))
+    }
+
+    /// Finds the entry for a name typed in the meal form, adding it to the
+    /// catalog the first time it is used.
+    pub fn resolve(db: &mut DbConn, name: &str) -> Result<CatalogIngredient, Error> {
+        use crate::models::category::{categorize, CategoryRule};
+
+        if let Some(entry) = Self::find(db, name)? {
+            return Ok(entry);
+        }
+        let rules = CategoryRule::get_all(db)?;
+        let entry: CatalogIngredient = insert_into(ingredient_catalog::table)
+            .values((
+                ingredient_catalog::name.eq(name.trim()),
+                ingredient_catalog::key.eq(normalize_name(name)),
+                ingredient_catalog::category_id.eq(categorize(&rules, name)),
+            ))
+            .get_result(db)?;
+        // A product bought before it was cooked with
+        update(products::table.filter(products::catalog_id.is_null()))
+            .filter(products::name.eq(name.trim()))
+            .set(products::catalog_id.eq(entry.id))
+            .execute(db)?;
+        Ok(entry)
+    }
+
+    /// Redoes every key with `normalize_name`. The migration that made the catalog
+    /// could only lowercase ASCII letters and æøå in SQL, so a name like "Crème
+    /// fraîche" got a key the app never looks up. Entries that turn out to be the
+    /// same are merged. Returns how many keys were redone, run at startup.
+    pub fn normalize_keys(db: &mut DbConn) -> Result<usize, Error> {
+        db.transaction(|db| {
+            let stale: Vec<CatalogIngredient> = ingredient_catalog::table
+                .order_by(ingredient_catalog::id)
+                .load::<CatalogIngredient>(db)?
+                .into_iter()
+                .filter(|entry| entry.key != normalize_name(&entry.name))
+                .collect();
+            // Out of the way first, as a stale key may be the right key of another
+            for entry in &stale {
+                update(ingredient_catalog::table.find(entry.id))
+                    .set(ingredient_catalog::key.eq(format!("#{}", entry.id)))
+                    .execute(db)?;
+            }
+            for entry in &stale {
+                let key = normalize_name(&entry.name);
+                let same = ingredient_catalog::table
+                    .filter(ingredient_catalog::key.eq(&key))
+                    .first::<CatalogIngredient>(db)
+                    .optional()?;
+                match same {
+                    Some(same) => {
+                        same.absorb(db, entry)?;
+                    }
+                    None => {
+                        update(ingredient_catalog::table.find(entry.id))
+                            .set(ingredient_catalog::key.eq(&key))
+                            .execute(db)?;
+                    }
+                }
+            }
+            Ok(stale.len())
+        })
+    }
+
+    /// Moves the meal ingredients and products of `other` to this entry and deletes
+    /// it, keeping what was set on either.
+    fn absorb(
+        &self,
+        db: &mut DbConn,
+        other: &CatalogIngredient,
+    ) -> Result<CatalogIngredient, Error> {
+        update(ingredients::table.filter(ingredients::catalog_id.eq(other.id)))
+            .set(ingredients::catalog_id.eq(self.id))
+            .execute(db)?;
+        update(products::table.filter(products::catalog_id.eq(other.id)))
+            .set(products::catalog_id.eq(self.id))
+            .execute(db)?;
+        delete(ingredient_catalog::table.find(other.id)).execute(db)?;
+        let mut aliases = self.alias_list();
+        for alias in other.alias_list() {
+            if !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
+        }
+        CatalogIngredient {
+            aliases: aliases.join("\n"),
+            unit: self.unit.clone().or_else(|| other.unit.clone()),
+            category_id: self.category_id.or(other.category_id),
+            allergens: format!("{}, {}", self.allergens, other.allergens),
+            food_id: self.food_id.or(other.food_id),
+            grams_per_unit: self.grams_per_unit.or(other.grams_per_unit),
+            ..self.clone()
+        }
+        .update(db)
+    }
+
+    pub fn update(&self, db: &mut DbConn) -> Result<CatalogIngredient, Error> {
+        CatalogIngredient {
+            name: self.name.trim().to_owned(),
+            key: normalize_name(&self.name),
+            allergens: parse_tags(&self.allergens).join(", "),
+            ..self.clone()
+        }
+        .save_changes(db)
+    }
+}
@@ -1,4 +1,14 @@
-use crate::models::ingredient::Ingredient;
+#[cfg(feature = "ssr")]
+use crate::models::day_meal::DayMeal;
+use crate::models::food::MealNutrition;
+use crate::models::meal_rating::{average_score, favourites, MealRating};
+use crate::models::member::Member;
+use crate::models::search::MealHit;
+use crate::models::{day::Day, ingredient::Ingredient, money::Money};
+use chrono::NaiveDate;
+use std::cmp::Reverse;
+#[cfg(feature = "ssr")]
+use std::collections::HashMap;
 
 #[cfg(feature = "ssr")]
 use diesel::prelude::*;
@@ -11,6 +21,254 @@ pub struct MealWithIngredients {
     #[serde(flatten)]
     pub meal: Meal,
     pub ingredients: Vec<Ingredient>,
+    pub history: Vec<MealServing>,
+    pub tags: Vec<String>,
+    pub ratings: Vec<MealRating>,
+    pub nutrition: Option<MealNutrition>,
+}
+
+impl MealWithIngredients {
+    /// Average cost per person eating, over the days that have receipts linked.
+    /// Leftovers eaten later count as servings of the day they were cooked.
+    pub fn cost_per_serving(&self) -> Option<Money> {
+        let (cost, servings) = self
+            .history
+            .iter()
+            .filter(|s| s.servings > 0)
+            .filter_map(|s| Some((s.cost?, s.servings)))
+            .fold((Money::ZERO, 0), |(cost, servings), (c, s)| {
+                (cost + c, servings + s)
+            });
+        (servings > 0)
+            .then(|| Money::from_ore((cost.ore() as f64 / servings as f64).round() as i64))
+    }
+}
+
+/// A meal with what the meal picker filters and sorts by.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct MealOverview {
+    #[serde(flatten)]
+    pub meal: Meal,
+    pub tags: Vec<String>,
+    /// Every day the meal is planned for, including future days
+    pub served: Vec<NaiveDate>,
+    pub ratings: Vec<MealRating>,
+    /// For warning about allergens when picking the meal
+    pub ingredients: Vec<Ingredient>,
+}
+
+impl From<&MealWithIngredients> for MealOverview {
+    fn from(meal: &MealWithIngredients) -> Self {
+        MealOverview {
+            meal: meal.meal.clone(),
+            tags: meal.tags.clone(),
+            served: meal.history.iter().map(|s| s.day.date).collect(),
+            ratings: meal.ratings.clone(),
+            ingredients: meal.ingredients.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MealSort {
+    #[default]
+    Name,
+    /// Longest since last time first, never cooked before that
+    LastCooked,
+    /// Most cooked first
+    TimesCooked,
+    /// Best average rating first, unrated last
+    Rating,
+}
+
+impl MealSort {
+    pub const ALL: [MealSort; 4] = [
+        MealSort::Name,
+        MealSort::LastCooked,
+        MealSort::TimesCooked,
+        MealSort::Rating,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MealSort::Name => "name",
+            MealSort::LastCooked => "last-cooked",
+            MealSort::TimesCooked => "times-cooked",
+            MealSort::Rating => "rating",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MealSort::Name => "Name",
+            MealSort::LastCooked => "Last cooked",
+            MealSort::TimesCooked => "Times cooked",
+            MealSort::Rating => "Rating",
+        }
+    }
+}
+
+impl std::str::FromStr for MealSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MealSort::ALL
+            .into_iter()
+            .find(|sort| sort.as_str() == s)
+            .ok_or_else(|| format!("Unknown meal sort {s}"))
+    }
+}
+
+/// The parts of a meal the meal list and the meal picker filter and sort by.
+pub trait MealListing {
+    fn meal(&self) -> &Meal;
+    fn tags(&self) -> &[String];
+    fn served(&self) -> Vec<NaiveDate>;
+    fn ratings(&self) -> &[MealRating];
+
+    /// The latest day up to `today` the meal was served.
+    fn last_cooked(&self, today: NaiveDate) -> Option<NaiveDate> {
+        self.served().into_iter().filter(|d| *d <= today).max()
+    }
+
+    fn times_cooked(&self, today: NaiveDate) -> usize {
+        self.served().into_iter().filter(|d| *d <= today).count()
+    }
+
+    fn average_rating(&self) -> Option<f32> {
+        average_score(self.ratings())
+    }
+
+    fn favourite_of(&self) -> Vec<Member> {
+        favourites(self.ratings())
+    }
+}
+
+impl MealListing for MealWithIngredients {
+    fn meal(&self) -> &Meal {
+        &self.meal
+    }
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+    fn served(&self) -> Vec<NaiveDate> {
+        self.history.iter().map(|s| s.day.date).collect()
+    }
+    fn ratings(&self) -> &[MealRating] {
+        &self.ratings
+    }
+}
+
+impl MealListing for MealOverview {
+    fn meal(&self) -> &Meal {
+        &self.meal
+    }
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+    fn served(&self) -> Vec<NaiveDate> {
+        self.served.clone()
+    }
+    fn ratings(&self) -> &[MealRating] {
+        &self.ratings
+    }
+}
+
+/// Every tag of the given meals, once each and sorted.
+pub fn all_tags<T: MealListing>(meals: &[T]) -> Vec<String> {
+    let mut tags: Vec<String> = meals.iter().flat_map(|m| m.tags().to_vec()).collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// How the meal list and the meal picker are filtered and sorted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MealQuery {
+    /// Meals have to have every one of these
+    pub tags: Vec<String>,
+    pub favourite_of: Option<Member>,
+    pub sort: MealSort,
+}
+
+/// Meals matching the `query`, sorted. With search `hits` only the meals found
+/// are kept, best match first.
+pub fn filter_meals<T: MealListing + Clone>(
+    meals: &[T],
+    hits: Option<&[MealHit]>,
+    query: &MealQuery,
+    today: NaiveDate,
+) -> Vec<T> {
+    let mut filtered: Vec<T> = meals
+        .iter()
+        .filter(|m| query.tags.iter().all(|tag| m.tags().contains(tag)))
+        .filter(|m| {
+            query
+                .favourite_of
+                .is_none_or(|member| m.favourite_of().contains(&member))
+        })
+        .cloned()
+        .collect();
+    if let Some(hits) = hits {
+        let rank = |m: &T| hits.iter().position(|hit| hit.meal_id == m.meal().id);
+        filtered.retain(|m| rank(m).is_some());
+        filtered.sort_by_key(rank);
+        return filtered;
+    }
+    // Stable, so meals that compare equal stay ordered by name
+    filtered.sort_by_key(|m| m.meal().name.to_lowercase());
+    match query.sort {
+        MealSort::Name => {}
+        MealSort::LastCooked => filtered.sort_by_key(|m| m.last_cooked(today)),
+        MealSort::TimesCooked => filtered.sort_by_key(|m| Reverse(m.times_cooked(today))),
+        MealSort::Rating => filtered.sort_by(|a, b| {
+            let (a, b) = (a.average_rating(), b.average_rating());
+            // The other way around for best first, which also puts `None` last
+            b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+        }),
+    }
+    filtered
+}
+
+/// A day the meal was served, and what it cost if any receipts are linked to it.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct MealServing {
+    pub day: Day,
+    pub cost: Option<Money>,
+    /// The people attending, and those eating leftovers of it on later days
+    pub servings: i64,
+}
+
+#[cfg(feature = "ssr")]
+impl MealServing {
+    /// Newest first. `costs` are by day and meal id, see `DayMeal::costs_by_dish`.
+    pub fn from_days(
+        mut days: Vec<(DayMeal, Day)>,
+        costs: &HashMap<(i32, i32), Money>,
+        leftover_portions: &HashMap<i32, i64>,
+    ) -> Vec<MealServing> {
+        days.sort_by(|(_, a), (_, b)| b.date.cmp(&a.date));
+        days.into_iter()
+            .map(|(dish, day)| MealServing {
+                cost: costs.get(&(day.id, dish.meal_id)).copied(),
+                servings: day.attendees() + leftover_portions.get(&day.id).copied().unwrap_or(0),
+                day,
+            })
+            .collect()
+    }
+
+    pub fn get_for_meal(db: &mut DbConn, meal_id: i32) -> Result<Vec<MealServing>, Error> {
+        use crate::schema::day_meals;
+
+        let costs = DayMeal::costs_by_dish(db)?;
+        let leftover_portions = Day::leftover_portions(db)?;
+        let days = day_meals::table
+            .inner_join(days::table)
+            .filter(day_meals::meal_id.eq(meal_id))
+            .select((DayMeal::as_select(), Day::as_select()))
+            .load(db)?;
+        Ok(Self::from_days(days, &costs, &leftover_portions))
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
@@ -20,6 +278,8 @@ pub struct MealForm {
     pub name: String,
     pub image: String,
     pub recipie_url: Option<String>,
+    /// How many the ingredients are for
+    pub servings: i32,
 }
 
 #[cfg(feature = "ssr")]
@@ -43,6 +303,7 @@ pub struct Meal {
     pub name: String,
     pub image: String,
     pub recipie_url: Option<String>,
+    pub servings: i32,
 }
 
 #[cfg(feature = "ssr")]
@@ -71,3 +332,141 @@ impl Meal {
         self.save_changes(db)
     }
 }
+
+impl Meal {
+    /// The smaller version of the image for cards, remote images have none.
+    pub fn thumbnail(&self) -> String {
+        thumbnail_url(&self.image)
+    }
+}
+
+/// Where the server serves stored meal images from.
+pub const IMAGE_PATH: &str = "/images";
+
+/// Whether the image is stored by the server rather than hot-linked.
+pub fn is_local_image(image: &str) -> bool {
+    image.starts_with(IMAGE_PATH)
+}
+
+/// The URL of the thumbnail stored next to a local image.
+pub fn thumbnail_url(image: &str) -> String {
+    match image.strip_suffix(".jpg") {
+        Some(stem) if is_local_image(image) => format!("{stem}-thumb.jpg"),
+        _ => image.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn overview(id: i32, name: &str, tags: &[&str], served: &[(i32, u32, u32)]) -> MealOverview {
+        MealOverview {
+            meal: Meal {
+                id,
+                name: String::from(name),
+                image: String::new(),
+                recipie_url: None,
+                servings: 3,
+            },
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            served: served
+                .iter()
+                .map(|(y, m, d)| NaiveDate::from_ymd_opt(*y, *m, *d).unwrap())
+                .collect(),
+            ratings: vec![],
+            ingredients: vec![],
+        }
+    }
+
+    fn rating(member: Member, score: i32) -> MealRating {
+        MealRating {
+            id: 0,
+            member,
+            meal_id: 0,
+            day_id: 0,
+            score,
+            comment: None,
+        }
+    }
+
+    fn names(meals: Vec<MealOverview>) -> Vec<String> {
+        meals.into_iter().map(|m| m.meal.name).collect()
+    }
+
+    #[test]
+    fn test_filter_meals() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let mut meals = vec![
+            overview(
+                1,
+                "taco",
+                &["quick", "kid-friendly"],
+                &[(2025, 2, 1), (2025, 2, 20)],
+            ),
+            overview(2, "Laks", &["fish", "quick"], &[(2025, 1, 10)]),
+            overview(3, "Pizza", &["weekend", "kid-friendly"], &[]),
+            // Only planned, not cooked yet
+            overview(4, "Dal", &["vegetarian"], &[(2025, 3, 5)]),
+        ];
+        meals[1].ratings = vec![rating(Member::Anders, 5), rating(Member::Ac, 3)];
+        meals[2].ratings = vec![rating(Member::Anders, 4), rating(Member::Anders, 5)];
+        meals[3].ratings = vec![rating(Member::Andreas, 2)];
+        let sorted = |tags: &[&str], sort| {
+            let query = MealQuery {
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                sort,
+                ..Default::default()
+            };
+            names(filter_meals(&meals, None, &query, today))
+        };
+
+        assert_eq!(
+            sorted(&[], MealSort::Name),
+            ["Dal", "Laks", "Pizza", "taco"]
+        );
+        assert_eq!(sorted(&["quick"], MealSort::Name), ["Laks", "taco"]);
+        assert_eq!(sorted(&["quick", "kid-friendly"], MealSort::Name), ["taco"]);
+        assert_eq!(
+            sorted(&[], MealSort::LastCooked),
+            ["Dal", "Pizza", "Laks", "taco"]
+        );
+        assert_eq!(
+            sorted(&[], MealSort::TimesCooked),
+            ["taco", "Laks", "Dal", "Pizza"]
+        );
+        assert_eq!(
+            sorted(&[], MealSort::Rating),
+            ["Pizza", "Laks", "Dal", "taco"]
+        );
+        let favourites = |member| {
+            let query = MealQuery {
+                favourite_of: Some(member),
+                ..Default::default()
+            };
+            names(filter_meals(&meals, None, &query, today))
+        };
+        assert_eq!(favourites(Member::Anders), ["Laks", "Pizza"]);
+        assert!(favourites(Member::Andreas).is_empty());
+
+        // Search results keep their order, and tags still apply
+        let hits: Vec<MealHit> = [4, 1, 2]
+            .into_iter()
+            .map(|meal_id| MealHit {
+                meal_id,
+                score: 100,
+                matched: None,
+            })
+            .collect();
+        let found = |tags: &[String]| {
+            let query = MealQuery {
+                tags: tags.to_vec(),
+                sort: MealSort::TimesCooked,
+                ..Default::default()
+            };
+            names(filter_meals(&meals, Some(&hits), &query, today))
+        };
+        assert_eq!(found(&[]), ["Dal", "taco", "Laks"]);
+        assert_eq!(found(&[String::from("quick")]), ["taco", "Laks"]);
+    }
+}
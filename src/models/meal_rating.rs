@@ -0,0 +1,103 @@
+use crate::models::member::Member;
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::schema::meal_ratings;
+
+/// A member's own average for a meal has to be at least this for it to be one of
+/// their favourites.
+pub const FAVOURITE_SCORE: f32 = 4.5;
+
+/// What a member thought of a dinner, from 1 to 5.
+#[cfg_attr(
+    feature = "ssr",
+    derive(Queryable, Selectable, Identifiable, Associations)
+)]
+#[cfg_attr(feature = "ssr", diesel(belongs_to(crate::models::meal::Meal)))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::meal_ratings))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct MealRating {
+    pub id: i32,
+    pub member: Member,
+    pub meal_id: i32,
+    pub day_id: i32,
+    pub score: i32,
+    pub comment: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+#[cfg_attr(feature = "ssr", derive(Insertable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::meal_ratings))]
+pub struct MealRatingForm {
+    pub member: Member,
+    pub meal_id: i32,
+    pub day_id: i32,
+    pub score: i32,
+    pub comment: Option<String>,
+}
+
+#[cfg(feature = "ssr")]
+impl MealRatingForm {
+    /// Replaces the member's earlier rating of the same dinner.
+    pub fn upsert(&self, db: &mut DbConn) -> Result<MealRating, Error> {
+        insert_into(meal_ratings::table)
+            .values(self)
+            .on_conflict((meal_ratings::member, meal_ratings::day_id))
+            .do_update()
+            .set((
+                meal_ratings::meal_id.eq(self.meal_id),
+                meal_ratings::score.eq(self.score),
+                meal_ratings::comment.eq(&self.comment),
+            ))
+            .get_result(db)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl MealRating {
+    pub fn get_for_meal(db: &mut DbConn, meal_id: i32) -> Result<Vec<MealRating>, Error> {
+        meal_ratings::table
+            .filter(meal_ratings::meal_id.eq(meal_id))
+            .select(MealRating::as_select())
+            .load(db)
+    }
+
+    /// Ratings of the meal served on the day, not of meals it had before.
+    pub fn get_for_day(
+        db: &mut DbConn,
+        day_id: i32,
+        meal_id: i32,
+    ) -> Result<Vec<MealRating>, Error> {
+        meal_ratings::table
+            .filter(meal_ratings::day_id.eq(day_id))
+            .filter(meal_ratings::meal_id.eq(meal_id))
+            .select(MealRating::as_select())
+            .load(db)
+    }
+
+    pub fn delete(db: &mut DbConn, id: i32) -> Result<usize, Error> {
+        delete(meal_ratings::table.filter(meal_ratings::id.eq(id))).execute(db)
+    }
+}
+
+pub fn average_score<'a>(ratings: impl IntoIterator<Item = &'a MealRating>) -> Option<f32> {
+    let (sum, count) = ratings
+        .into_iter()
+        .fold((0, 0), |(sum, count), r| (sum + r.score, count + 1));
+    (count > 0).then(|| sum as f32 / count as f32)
+}
+
+/// The members the meal is a favourite of, see `FAVOURITE_SCORE`.
+pub fn favourites(ratings: &[MealRating]) -> Vec<Member> {
+    Member::ALL
+        .into_iter()
+        .filter(|member| {
+            average_score(ratings.iter().filter(|r| r.member == *member))
+                .is_some_and(|score| score >= FAVOURITE_SCORE)
+        })
+        .collect()
+}
@@ -0,0 +1,76 @@
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::schema::meal_tags;
+
+/// Offered in the meal form before any meal has them.
+pub const SUGGESTED_TAGS: [&str; 5] = ["vegetarian", "fish", "quick", "weekend", "kid-friendly"];
+
+/// Splits comma separated tags into lowercase names with single spaces, without
+/// blanks or duplicates.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',') {
+        let tag = tag
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Queryable, Selectable, Identifiable, Associations, Clone, Debug)]
+#[diesel(belongs_to(crate::models::meal::Meal))]
+#[diesel(table_name = crate::schema::meal_tags)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct MealTag {
+    pub id: i32,
+    pub meal_id: i32,
+    pub name: String,
+}
+
+#[cfg(feature = "ssr")]
+impl MealTag {
+    pub fn get_for_meal(db: &mut DbConn, meal_id: i32) -> Result<Vec<String>, Error> {
+        meal_tags::table
+            .filter(meal_tags::meal_id.eq(meal_id))
+            .order_by(meal_tags::name)
+            .select(meal_tags::name)
+            .load(db)
+    }
+
+    /// Every tag used by a meal, once each.
+    pub fn get_names(db: &mut DbConn) -> Result<Vec<String>, Error> {
+        meal_tags::table
+            .select(meal_tags::name)
+            .distinct()
+            .order_by(meal_tags::name)
+            .load(db)
+    }
+
+    /// Replaces the tags of a meal, returning them as stored.
+    pub fn set_for_meal(
+        db: &mut DbConn,
+        meal_id: i32,
+        tags: &[String],
+    ) -> Result<Vec<String>, Error> {
+        delete(meal_tags::table.filter(meal_tags::meal_id.eq(meal_id))).execute(db)?;
+        let tags = parse_tags(&tags.join(","));
+        let rows: Vec<_> = tags
+            .iter()
+            .map(|tag| (meal_tags::meal_id.eq(meal_id), meal_tags::name.eq(tag)))
+            .collect();
+        if !rows.is_empty() {
+            insert_into(meal_tags::table).values(&rows).execute(db)?;
+        }
+        Self::get_for_meal(db, meal_id)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::models::text_enum_sql;
+
+/// The people sharing dinners and grocery costs.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ssr", derive(diesel::AsExpression, diesel::FromSqlRow))]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum Member {
+    Anders,
+    Andreas,
+    Ac,
+}
+
+/// One weight per member, in the order of `Member::ALL`.
+pub type Shares = [i32; 3];
+
+impl Member {
+    pub const ALL: [Member; 3] = [Member::Anders, Member::Andreas, Member::Ac];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Member::Anders => "Anders",
+            Member::Andreas => "Andreas",
+            Member::Ac => "AC",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Member::Anders => "anders",
+            Member::Andreas => "andreas",
+            Member::Ac => "ac",
+        }
+    }
+
+    /// Position of the member in `Member::ALL` and in `Shares`.
+    pub fn index(&self) -> usize {
+        match self {
+            Member::Anders => 0,
+            Member::Andreas => 1,
+            Member::Ac => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for Member {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Member {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Member::ALL
+            .into_iter()
+            .find(|m| m.as_str() == s)
+            .ok_or_else(|| format!("Unknown member {s}"))
+    }
+}
+
+text_enum_sql!(Member);
@@ -1,6 +1,51 @@
+/// Stores a fieldless enum in a TEXT column through its `as_str` method and `FromStr`
+/// implementation. The enum also needs to derive `diesel::AsExpression` and
+/// `diesel::FromSqlRow` with `#[diesel(sql_type = diesel::sql_types::Text)]`.
+macro_rules! text_enum_sql {
+    ($ty:ty) => {
+        #[cfg(feature = "ssr")]
+        impl diesel::serialize::ToSql<diesel::sql_types::Text, diesel::sqlite::Sqlite> for $ty {
+            fn to_sql<'b>(
+                &'b self,
+                out: &mut diesel::serialize::Output<'b, '_, diesel::sqlite::Sqlite>,
+            ) -> diesel::serialize::Result {
+                out.set_value(self.as_str());
+                Ok(diesel::serialize::IsNull::No)
+            }
+        }
+
+        #[cfg(feature = "ssr")]
+        impl diesel::deserialize::FromSql<diesel::sql_types::Text, diesel::sqlite::Sqlite> for $ty {
+            fn from_sql(
+                bytes: <diesel::sqlite::Sqlite as diesel::backend::Backend>::RawValue<'_>,
+            ) -> diesel::deserialize::Result<Self> {
+                let text = <String as diesel::deserialize::FromSql<
+                    diesel::sql_types::Text,
+                    diesel::sqlite::Sqlite,
+                >>::from_sql(bytes)?;
+                Ok(text.parse()?)
+            }
+        }
+    };
+}
+pub(crate) use text_enum_sql;
+
 pub mod day;
+pub mod day_meal;
 pub mod ingredient;
+pub mod ingredient_catalog;
 pub mod meal;
+pub mod meal_rating;
+pub mod meal_tag;
 pub mod days_ingredients;
 pub mod extra_item;
+pub mod food;
 pub mod receipt;
+pub mod member;
+pub mod money;
+pub mod ocr_job;
+pub mod store;
+pub mod category;
+pub mod product;
+pub mod restriction;
+pub mod search;
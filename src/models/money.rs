@@ -0,0 +1,262 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+#[cfg(feature = "ssr")]
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSql},
+    serialize::{self, Output, ToSql},
+    sql_types::BigInt,
+    sqlite::Sqlite,
+};
+
+/// An amount of Norwegian kroner, stored as whole øre so sums never drift.
+#[derive(
+    serde::Deserialize,
+    serde::Serialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[cfg_attr(feature = "ssr", derive(diesel::AsExpression, diesel::FromSqlRow))]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::BigInt))]
+#[serde(transparent)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_ore(ore: i64) -> Money {
+        Money(ore)
+    }
+
+    pub fn from_kroner(kroner: i64) -> Money {
+        Money(kroner * 100)
+    }
+
+    pub fn ore(&self) -> i64 {
+        self.0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn abs(&self) -> Money {
+        Money(self.0.abs())
+    }
+
+    /// Splits the amount proportionally to `weights` without losing any øre.
+    pub fn split(&self, weights: &[i64]) -> Vec<Money> {
+        crate::utils::split(self.0, weights)
+            .into_iter()
+            .map(Money)
+            .collect()
+    }
+
+    /// The amount with a decimal comma and no grouping or currency, e.g. `1234,50`.
+    /// This is what payment apps accept when pasting.
+    pub fn format_amount(&self) -> String {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let ore = self.0.unsigned_abs();
+        format!("{sign}{},{:02}", ore / 100, ore % 100)
+    }
+
+    /// The amount as a plain decimal number, e.g. `1234.50`, for `<input type="number">`.
+    pub fn to_decimal_string(self) -> String {
+        self.format_amount().replace(',', ".")
+    }
+}
+
+/// Formats as Norwegian kroner, e.g. `1 234,50 kr`.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ore = self.0.unsigned_abs();
+        let kroner = (ore / 100).to_string();
+        let mut grouped = String::new();
+        for (i, c) in kroner.chars().enumerate() {
+            if i > 0 && (kroner.len() - i).is_multiple_of(3) {
+                grouped.push('\u{a0}');
+            }
+            grouped.push(c);
+        }
+        let sign = if self.0 < 0 { "-" } else { "" };
+        write!(f, "{sign}{grouped},{:02}\u{a0}kr", ore % 100)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseMoneyError(String);
+
+impl fmt::Display for ParseMoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid amount {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMoneyError {}
+
+/// Parses amounts the way they are written on receipts and typed into forms:
+/// `12`, `12,5`, `12.50`, `1 234,50`, `-12,50` and `12,50-`.
+impl FromStr for Money {
+    type Err = ParseMoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMoneyError(s.to_owned());
+        let cleaned: String = s
+            .trim()
+            .trim_end_matches("kr")
+            .trim_end_matches(",-")
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let (negative, digits) = if let Some(rest) = cleaned.strip_prefix('-') {
+            (true, rest)
+        } else if let Some(rest) = cleaned.strip_suffix('-') {
+            (true, rest)
+        } else {
+            (false, cleaned.as_str())
+        };
+        let (kroner, ore) = match digits.split_once([',', '.']) {
+            Some((kroner, ore)) => (kroner, ore),
+            None => (digits, ""),
+        };
+        if kroner.is_empty() && ore.is_empty()
+            || ore.len() > 2
+            || !kroner
+                .chars()
+                .chain(ore.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(err());
+        }
+        let kroner: i64 = match kroner {
+            "" => 0,
+            k => k.parse().map_err(|_| err())?,
+        };
+        let ore: i64 = match ore.len() {
+            0 => 0,
+            1 => ore.parse::<i64>().map_err(|_| err())? * 10,
+            _ => ore.parse().map_err(|_| err())?,
+        };
+        let amount = kroner
+            .checked_mul(100)
+            .and_then(|kroner| kroner.checked_add(ore))
+            .ok_or_else(err)?;
+        Ok(Money(if negative { -amount } else { amount }))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl ToSql<BigInt, Sqlite> for Money {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        <i64 as ToSql<BigInt, Sqlite>>::to_sql(&self.0, out)
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl FromSql<BigInt, Sqlite> for Money {
+    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        <i64 as FromSql<BigInt, Sqlite>>::from_sql(bytes).map(Money)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("12".parse(), Ok(Money(1200)));
+        assert_eq!("12,5".parse(), Ok(Money(1250)));
+        assert_eq!("12.50".parse(), Ok(Money(1250)));
+        assert_eq!("0,05".parse(), Ok(Money(5)));
+        assert_eq!("1 234,50".parse(), Ok(Money(123450)));
+        assert_eq!("-12,50".parse(), Ok(Money(-1250)));
+        assert_eq!("12,50-".parse(), Ok(Money(-1250)));
+        assert_eq!("49,90 kr".parse(), Ok(Money(4990)));
+        assert_eq!("49,-".parse(), Ok(Money(4900)));
+        assert!("12,505".parse::<Money>().is_err());
+        assert!("1x2".parse::<Money>().is_err());
+        assert!("".parse::<Money>().is_err());
+        assert!(",".parse::<Money>().is_err());
+        assert_eq!("92233720368547758,07".parse(), Ok(Money(i64::MAX)));
+        assert!("92233720368547759".parse::<Money>().is_err());
+        assert!("99999999999999999999".parse::<Money>().is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Money(123450).to_string(), "1\u{a0}234,50\u{a0}kr");
+        assert_eq!(Money(-5).to_string(), "-0,05\u{a0}kr");
+        assert_eq!(
+            Money(100000000).to_string(),
+            "1\u{a0}000\u{a0}000,00\u{a0}kr"
+        );
+        assert_eq!(Money(-123450).format_amount(), "-1234,50");
+        assert_eq!(Money(1790).to_decimal_string(), "17.90");
+    }
+
+    #[test]
+    fn test_sum_is_exact() {
+        let items = [Money(10); 10];
+        assert_eq!(items.iter().sum::<Money>(), Money(100));
+        assert_eq!(
+            Money(100).split(&[1, 1, 1]).into_iter().sum::<Money>(),
+            Money(100)
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use crate::models::receipt::{ReceiptForm, ReceiptItemForm};
+use crate::models::store::StoreForm;
+use crate::models::text_enum_sql;
+#[cfg(feature = "ssr")]
+use chrono::NaiveDateTime;
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::schema::ocr_jobs;
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(diesel::AsExpression, diesel::FromSqlRow))]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum OcrJobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl OcrJobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OcrJobStatus::Queued => "queued",
+            OcrJobStatus::Running => "running",
+            OcrJobStatus::Done => "done",
+            OcrJobStatus::Failed => "failed",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, OcrJobStatus::Done | OcrJobStatus::Failed)
+    }
+}
+
+impl std::str::FromStr for OcrJobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(OcrJobStatus::Queued),
+            "running" => Ok(OcrJobStatus::Running),
+            "done" => Ok(OcrJobStatus::Done),
+            "failed" => Ok(OcrJobStatus::Failed),
+            s => Err(format!("Unknown OCR job status {s}")),
+        }
+    }
+}
+
+text_enum_sql!(OcrJobStatus);
+
+/// Items read with a lower confidence than this are flagged for checking.
+pub const LOW_CONFIDENCE: f32 = 0.9;
+
+/// A receipt read from an upload, ready to be reviewed in the receipt form.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct ScannedReceipt {
+    pub receipt: ReceiptForm,
+    pub items: Vec<ReceiptItemForm>,
+    /// What OCR saw for each of `items`, empty for digital receipts.
+    #[serde(default)]
+    pub item_scans: Vec<ScannedItem>,
+    /// Organisation number printed on the receipt, used to find the store.
+    pub org_number: Option<String>,
+    /// The store to add when the receipt is saved, if the org number is new.
+    #[serde(default)]
+    pub new_store: Option<StoreForm>,
+}
+
+/// The line an item was read from, so it can be checked against the photo.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ScannedItem {
+    /// Lowest confidence of the words on the line, from 0 to 1
+    pub confidence: Option<f32>,
+    /// Read with less than `LOW_CONFIDENCE`
+    pub flagged: bool,
+    /// Left, top, right and bottom of the line in the uploaded photo
+    pub bbox: Option<[u32; 4]>,
+    /// The line cut out of the photo, as a PNG data URL
+    pub image: Option<String>,
+}
+
+impl ScannedItem {
+    pub fn new(confidence: Option<f32>, bbox: Option<[u32; 4]>) -> ScannedItem {
+        ScannedItem {
+            confidence,
+            flagged: confidence.is_some_and(|c| c < LOW_CONFIDENCE),
+            bbox,
+            image: None,
+        }
+    }
+}
+
+/// A photo after one of the preprocessing stages, for seeing why OCR misreads it.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct PreprocessStage {
+    pub name: String,
+    /// PNG data URL
+    pub image: String,
+}
+
+/// What the uploader needs to know about a job. Whenever the job changes it is
+/// sent over the websocket without the result, which only the uploader fetches.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct OcrJobUpdate {
+    pub job_id: i32,
+    pub status: OcrJobStatus,
+    pub result: Option<ScannedReceipt>,
+    pub error: Option<String>,
+    /// The photo after every preprocessing stage, only in the reply to an upload
+    /// in debug mode
+    #[serde(default)]
+    pub stages: Vec<PreprocessStage>,
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = crate::schema::ocr_jobs)]
+pub struct OcrJobForm {
+    pub file_path: String,
+}
+
+#[cfg(feature = "ssr")]
+impl OcrJobForm {
+    pub fn insert(&self, db: &mut DbConn) -> Result<OcrJob, Error> {
+        insert_into(ocr_jobs::table).values(self).get_result(db)
+    }
+}
+
+/// Only lives on the server, clients get an `OcrJobUpdate`.
+#[cfg(feature = "ssr")]
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::ocr_jobs)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct OcrJob {
+    pub id: i32,
+    pub status: OcrJobStatus,
+    pub file_path: String,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[cfg(feature = "ssr")]
+impl OcrJob {
+    pub fn to_update(&self) -> OcrJobUpdate {
+        OcrJobUpdate {
+            job_id: self.id,
+            status: self.status,
+            result: self
+                .result
+                .as_deref()
+                .and_then(|r| serde_json::from_str(r).ok()),
+            error: self.error.clone(),
+            stages: vec![],
+        }
+    }
+
+    /// The update without the scanned receipt, safe to send to every client.
+    pub fn to_status_update(&self) -> OcrJobUpdate {
+        OcrJobUpdate {
+            job_id: self.id,
+            status: self.status,
+            result: None,
+            error: self.error.clone(),
+            stages: vec![],
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl OcrJob {
+    pub fn get(db: &mut DbConn, id: i32) -> Result<OcrJob, Error> {
+        ocr_jobs::table.find(id).first(db)
+    }
+
+    fn set(
+        db: &mut DbConn,
+        id: i32,
+        status: OcrJobStatus,
+        result: Option<String>,
+        error: Option<String>,
+    ) -> Result<OcrJob, Error> {
+        update(ocr_jobs::table.find(id))
+            .set((
+                ocr_jobs::status.eq(status),
+                ocr_jobs::result.eq(result),
+                ocr_jobs::error.eq(error),
+                ocr_jobs::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result(db)
+    }
+
+    pub fn start(db: &mut DbConn, id: i32) -> Result<OcrJob, Error> {
+        Self::set(db, id, OcrJobStatus::Running, None, None)
+    }
+
+    pub fn finish(db: &mut DbConn, id: i32, scanned: &ScannedReceipt) -> Result<OcrJob, Error> {
+        let result = serde_json::to_string(scanned).expect("ScannedReceipt is always serializable");
+        Self::set(db, id, OcrJobStatus::Done, Some(result), None)
+    }
+
+    pub fn fail(db: &mut DbConn, id: i32, error: String) -> Result<OcrJob, Error> {
+        Self::set(db, id, OcrJobStatus::Failed, None, Some(error))
+    }
+
+    /// Puts jobs that were running when the server stopped back in the queue and
+    /// returns the ids of every queued job, oldest first.
+    pub fn requeue_unfinished(db: &mut DbConn) -> Result<Vec<i32>, Error> {
+        update(ocr_jobs::table.filter(ocr_jobs::status.eq(OcrJobStatus::Running)))
+            .set(ocr_jobs::status.eq(OcrJobStatus::Queued))
+            .execute(db)?;
+        ocr_jobs::table
+            .filter(ocr_jobs::status.eq(OcrJobStatus::Queued))
+            .select(ocr_jobs::id)
+            .order_by(ocr_jobs::id)
+            .load(db)
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::models::category::normalize_name;
+#[cfg(feature = "ssr")]
+use crate::models::ingredient_catalog::CatalogIngredient;
+#[cfg(feature = "ssr")]
+use crate::models::receipt::ReceiptItemForm;
+#[cfg(feature = "ssr")]
+use crate::schema::{product_aliases, products};
+
+/// The name we use for something we buy, shared by receipts and ingredients.
+#[cfg(feature = "ssr")]
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug, PartialEq)]
+#[diesel(table_name = crate::schema::products)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Product {
+    pub id: i32,
+    pub name: String,
+    /// The catalog entry it is, whose name it goes by
+    pub catalog_id: Option<i32>,
+}
+
+#[cfg(feature = "ssr")]
+impl Product {
+    pub fn get_all(db: &mut DbConn) -> Result<Vec<Product>, Error> {
+        products::table.order_by(products::name).load(db)
+    }
+
+    /// Names are compared without case.
+    pub fn find(db: &mut DbConn, name: &str) -> Result<Option<Product>, Error> {
+        products::table
+            .filter(products::name.eq(name.trim()))
+            .first(db)
+            .optional()
+    }
+
+    pub fn find_or_insert(db: &mut DbConn, name: &str) -> Result<Product, Error> {
+        if let Some(product) = Self::find(db, name)? {
+            return Ok(product);
+        }
+        let catalog_id = CatalogIngredient::find(db, name)?.map(|entry| entry.id);
+        insert_into(products::table)
+            .values((
+                products::name.eq(name.trim()),
+                products::catalog_id.eq(catalog_id),
+            ))
+            .get_result(db)
+    }
+
+    /// The name of its catalog entry if it has one, so receipts and shopping lists
+    /// use the same name.
+    pub fn shared_name(&self, db: &mut DbConn) -> Result<String, Error> {
+        use crate::schema::ingredient_catalog;
+
+        let Some(catalog_id) = self.catalog_id else {
+            return Ok(self.name.clone());
+        };
+        ingredient_catalog::table
+            .find(catalog_id)
+            .select(ingredient_catalog::name)
+            .first(db)
+    }
+
+    /// Links products without a catalog entry to the entry with their name or
+    /// alias. Returns how many were linked.
+    pub fn link_catalog(db: &mut DbConn) -> Result<usize, Error> {
+        let unlinked: Vec<Product> = products::table
+            .filter(products::catalog_id.is_null())
+            .load(db)?;
+        let mut linked = 0;
+        for product in unlinked {
+            if let Some(entry) = CatalogIngredient::find(db, &product.name)? {
+                linked += update(products::table.find(product.id))
+                    .set(products::catalog_id.eq(entry.id))
+                    .execute(db)?;
+            }
+        }
+        Ok(linked)
+    }
+}
+
+/// A raw OCR name and the product it is, see `ProductAlias::learn`.
+#[cfg(feature = "ssr")]
+#[derive(Queryable, Selectable, Identifiable, Clone, Debug)]
+#[diesel(table_name = crate::schema::product_aliases)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ProductAlias {
+    pub id: i32,
+    /// Lowercase with single spaces
+    pub raw_name: String,
+    pub product_id: i32,
+}
+
+#[cfg(feature = "ssr")]
+impl ProductAlias {
+    /// The product a raw OCR name has been renamed to before.
+    pub fn lookup(db: &mut DbConn, raw_name: &str) -> Result<Option<Product>, Error> {
+        product_aliases::table
+            .inner_join(products::table)
+            .filter(product_aliases::raw_name.eq(normalize_name(raw_name)))
+            .select(Product::as_select())
+            .first(db)
+            .optional()
+    }
+
+    /// Remembers what a scanned item was renamed to in the receipt form, so the
+    /// next scan uses the same name. Keeping the raw name forgets an earlier
+    /// rename. Returns the product the item is.
+    pub fn learn(db: &mut DbConn, raw_name: &str, name: &str) -> Result<Option<Product>, Error> {
+        let raw_name = normalize_name(raw_name);
+        if raw_name == normalize_name(name) {
+            delete(product_aliases::table.filter(product_aliases::raw_name.eq(&raw_name)))
+                .execute(db)?;
+            return Product::find(db, name);
+        }
+        let product = Product::find_or_insert(db, name)?;
+        insert_into(product_aliases::table)
+            .values((
+                product_aliases::raw_name.eq(&raw_name),
+                product_aliases::product_id.eq(product.id),
+            ))
+            .on_conflict(product_aliases::raw_name)
+            .do_update()
+            .set(product_aliases::product_id.eq(product.id))
+            .execute(db)?;
+        Ok(Some(product))
+    }
+}
+
+/// Gives scanned items the product names they were renamed to before.
+#[cfg(feature = "ssr")]
+pub fn apply_product_names(db: &mut DbConn, items: &mut [ReceiptItemForm]) -> Result<(), Error> {
+    for item in items {
+        let raw_name = item.raw_name.as_deref().unwrap_or(&item.name);
+        if let Some(product) = ProductAlias::lookup(db, raw_name)? {
+            item.name = product.shared_name(db)?;
+            item.product_id = Some(product.id);
+        }
+    }
+    Ok(())
+}
@@ -1,16 +1,25 @@
-use chrono::NaiveDateTime;
+use crate::models::day::Day;
+use crate::models::member::{Member, Shares};
+use crate::models::money::Money;
+use crate::models::text_enum_sql;
+use crate::utils::apportion;
+#[cfg(feature = "ssr")]
+use chrono::NaiveTime;
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
 #[cfg(feature = "ssr")]
 use diesel::prelude::*;
 
 #[cfg(feature = "ssr")]
 use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use std::collections::HashMap;
 
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct ReceiptWithItems {
     #[serde(flatten)]
     pub receipt: Receipt,
     pub items: Vec<ReceiptItem>,
-    pub days: Option<Vec<crate::models::day::Day>>,
+    pub days: Option<Vec<Day>>,
 }
 #[cfg(feature = "ssr")]
 impl ReceiptWithItems {
@@ -18,7 +27,9 @@ impl ReceiptWithItems {
         let receipt: Receipt = receipts::table.find(id).first(db)?;
 
         // Load all items belonging to this receipt
-        let items: Vec<ReceiptItem> = ReceiptItem::belonging_to(&receipt).load(db)?;
+        let items: Vec<ReceiptItem> = ReceiptItem::belonging_to(&receipt)
+            .order_by(receipt_items::id)
+            .load(db)?;
         Ok(ReceiptWithItems {
             days: Day::get_by_receipt(db, receipt.id)?,
             receipt,
@@ -28,15 +39,17 @@ impl ReceiptWithItems {
 
     pub fn get_all(db: &mut DbConn) -> Result<Vec<ReceiptWithItems>, Error> {
         let receipts: Vec<Receipt> = receipts::table.order_by(receipts::id.desc()).load(db)?;
+        Self::with_items(db, receipts)
+    }
 
-        // Load all receipt items belonging to those receipts
-        let items: Vec<ReceiptItem> = ReceiptItem::belonging_to(&receipts).load(db)?;
-
-        // Group items by receipt
+    /// Loads the items and days of each receipt, keeping the order of `receipts`.
+    fn with_items(db: &mut DbConn, receipts: Vec<Receipt>) -> Result<Vec<ReceiptWithItems>, Error> {
+        let items: Vec<ReceiptItem> = ReceiptItem::belonging_to(&receipts)
+            .order_by(receipt_items::id)
+            .load(db)?;
         let grouped_items = items.grouped_by(&receipts);
 
-        // Zip receipts with their items
-        let result = receipts
+        receipts
             .into_iter()
             .zip(grouped_items)
             .map(|(receipt, items)| {
@@ -46,9 +59,24 @@ impl ReceiptWithItems {
                     items,
                 })
             })
-            .collect::<Result<Vec<ReceiptWithItems>, Error>>()?;
+            .collect()
+    }
 
-        Ok(result)
+    /// One page of the receipts matching `query`, newest first.
+    pub fn search(db: &mut DbConn, query: &ReceiptQuery) -> Result<ReceiptPage, Error> {
+        let total_count: i64 = query.filter().count().get_result(db)?;
+        let page = query.page.max(1);
+        let receipts: Vec<Receipt> = query
+            .filter()
+            .order_by((receipts::datetime.desc(), receipts::id.desc()))
+            .limit(RECEIPTS_PER_PAGE)
+            .offset((page - 1) * RECEIPTS_PER_PAGE)
+            .load(db)?;
+        Ok(ReceiptPage {
+            receipts: Self::with_items(db, receipts)?,
+            page,
+            total_count,
+        })
     }
 
     pub fn get_by_day(
@@ -64,63 +92,540 @@ impl ReceiptWithItems {
             .distinct()
             .load(db)?;
 
-        // Load all items for these receipts
-        let items: Vec<ReceiptItem> = ReceiptItem::belonging_to(&receipts_list).load(db)?;
-        let grouped_items = items.grouped_by(&receipts_list);
+        let result = Self::with_items(db, receipts_list)?;
+        Ok((!result.is_empty()).then_some(result))
+    }
 
-        // Combine receipts with their items
-        let result: Vec<ReceiptWithItems> = receipts_list
+    /// Receipts that look like the same purchase as the one about to be saved,
+    /// either by their contents or by a similar photo. Only receipts from the same
+    /// minute with the same total, or with a similar photo, are loaded to compare.
+    pub fn find_duplicates(
+        db: &mut DbConn,
+        receipt_form: &ReceiptForm,
+        items: &[ReceiptItemForm],
+    ) -> Result<Vec<ReceiptWithItems>, Error> {
+        use crate::ocr::image_hash;
+        use diesel::dsl::sql;
+        use diesel::sql_types::BigInt;
+
+        let fingerprint = receipt_form.fingerprint(items);
+        let minute = receipt_form
+            .datetime
+            .with_second(0)
+            .and_then(|d| d.with_nanosecond(0))
+            .unwrap_or(receipt_form.datetime);
+        let total: Money = items.iter().map(|i| i.price).sum();
+        let mut candidate_ids: Vec<i32> = receipts::table
+            .filter(receipts::datetime.ge(minute))
+            .filter(receipts::datetime.lt(minute + chrono::TimeDelta::minutes(1)))
+            .filter(
+                receipts::id.eq_any(
+                    receipt_items::table
+                        .group_by(receipt_items::receipt_id)
+                        .having(sql::<BigInt>("SUM(price)").eq(total))
+                        .select(receipt_items::receipt_id),
+                ),
+            )
+            .select(receipts::id)
+            .load(db)?;
+        if let Some(hash) = receipt_form.image_hash {
+            let hashes: Vec<(i32, Option<i64>)> = receipts::table
+                .filter(receipts::image_hash.is_not_null())
+                .select((receipts::id, receipts::image_hash))
+                .load(db)?;
+            candidate_ids.extend(hashes.into_iter().filter_map(|(id, other)| {
+                other
+                    .is_some_and(|other| image_hash::is_similar(hash, other))
+                    .then_some(id)
+            }));
+        }
+        let candidates: Vec<Receipt> = receipts::table
+            .filter(receipts::id.eq_any(candidate_ids))
+            .order_by(receipts::id.desc())
+            .load(db)?;
+        Ok(Self::with_items(db, candidates)?
             .into_iter()
-            .zip(grouped_items)
-            .map(|(receipt, items)| {
-                Ok(ReceiptWithItems {
-                    days: Day::get_by_receipt(db, receipt.id)?,
-                    receipt,
-                    items,
-                })
+            .filter(|r| {
+                let similar_image = match (r.receipt.image_hash, receipt_form.image_hash) {
+                    (Some(a), Some(b)) => image_hash::is_similar(a, b),
+                    _ => false,
+                };
+                similar_image || r.fingerprint() == fingerprint
             })
-            .collect::<Result<Vec<ReceiptWithItems>, Error>>()?;
+            .collect())
+    }
+}
 
-        Ok((!result.is_empty()).then_some(result))
+pub const RECEIPTS_PER_PAGE: i64 = 20;
+
+/// Filters for the receipt list. Every field is optional, text is matched as a
+/// case insensitive substring.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ReceiptQuery {
+    pub store: Option<String>,
+    pub store_id: Option<i32>,
+    pub from: Option<NaiveDate>,
+    /// Inclusive
+    pub to: Option<NaiveDate>,
+    /// Only receipts this member pays some of
+    pub member: Option<Member>,
+    /// Only receipts with an item whose name contains this
+    pub item: Option<String>,
+    pub min_total: Option<Money>,
+    pub max_total: Option<Money>,
+    /// Starts at 1
+    pub page: i64,
+}
+
+#[cfg(feature = "ssr")]
+impl ReceiptQuery {
+    fn filter(&self) -> receipts::BoxedQuery<'static, diesel::sqlite::Sqlite> {
+        use diesel::dsl::sql;
+        use diesel::sql_types::{BigInt, Bool};
+
+        let mut query = receipts::table.into_boxed();
+        if let Some(store) = self.store.as_deref().filter(|s| !s.trim().is_empty()) {
+            query = query.filter(receipts::store.like(like_pattern(store)).escape('\\'));
+        }
+        if let Some(store_id) = self.store_id {
+            query = query.filter(receipts::store_id.eq(store_id));
+        }
+        if let Some(from) = self.from {
+            query = query.filter(receipts::datetime.ge(from.and_time(NaiveTime::MIN)));
+        }
+        if let Some(to) = self.to.and_then(|to| to.succ_opt()) {
+            query = query.filter(receipts::datetime.lt(to.and_time(NaiveTime::MIN)));
+        }
+        if let Some(member) = self.member {
+            query = query.filter(sql::<Bool>(&member_pays_sql(member)));
+        }
+        if let Some(item) = self.item.as_deref().filter(|s| !s.trim().is_empty()) {
+            query = query.filter(
+                receipts::id.eq_any(
+                    receipt_items::table
+                        .filter(
+                            receipt_items::name
+                                .like(like_pattern(item))
+                                .escape('\\')
+                                .or(receipt_items::raw_name
+                                    .like(like_pattern(item))
+                                    .escape('\\')),
+                        )
+                        .select(receipt_items::receipt_id),
+                ),
+            );
+        }
+        if self.min_total.is_some() || self.max_total.is_some() {
+            let min = self.min_total.unwrap_or(Money::from_ore(i64::MIN));
+            let max = self.max_total.unwrap_or(Money::from_ore(i64::MAX));
+            query = query.filter(
+                receipts::id.eq_any(
+                    receipt_items::table
+                        .group_by(receipt_items::receipt_id)
+                        .having(sql::<BigInt>("SUM(price)").between(min, max))
+                        .select(receipt_items::receipt_id),
+                ),
+            );
+        }
+        query
+    }
+}
+
+/// SQL for whether the member pays for any line of the receipt, split the same way
+/// as `member_sums`: item shares times the receipt shares, which are weighted by
+/// the linked days in attendance mode, with the same fallbacks. Discounts and
+/// deposits follow their product, so only the other lines count.
+#[cfg(feature = "ssr")]
+fn member_pays_sql(member: Member) -> String {
+    let attended = |m: Member| {
+        format!(
+            "(SELECT COUNT(*) FROM receipt_days rd INNER JOIN days d ON d.id = rd.day_id \
+             WHERE rd.receipt_id = receipts.id AND d.{}_attend)",
+            m.as_str()
+        )
+    };
+    let by_attendance = Member::ALL
+        .map(|m| format!("receipts.{}_share * {} <> 0", m.as_str(), attended(m)))
+        .join(" OR ");
+    let receipt_share = |m: Member| {
+        format!(
+            "MAX(CASE WHEN receipts.split_mode = '{}' AND ({by_attendance}) \
+             THEN receipts.{m}_share * {} ELSE receipts.{m}_share END, 0)",
+            SplitMode::Attendance.as_str(),
+            attended(m),
+            m = m.as_str(),
+        )
+    };
+    let item_weight = |m: Member| format!("MAX(i.{}_share, 0) * {}", m.as_str(), receipt_share(m));
+    let nobody = |weight: &dyn Fn(Member) -> String| {
+        Member::ALL
+            .map(|m| format!("{} = 0", weight(m)))
+            .join(" AND ")
+    };
+    format!(
+        "EXISTS (SELECT 1 FROM receipt_items i WHERE i.receipt_id = receipts.id \
+         AND i.kind NOT IN ('{}', '{}') \
+         AND ({} > 0 OR ({} AND ({} > 0 OR ({})))))",
+        ItemKind::Discount.as_str(),
+        ItemKind::Deposit.as_str(),
+        item_weight(member),
+        nobody(&item_weight),
+        receipt_share(member),
+        nobody(&receipt_share),
+    )
+}
+
+/// Turns user input into a `LIKE` pattern matching it anywhere, escaped with `\`.
+#[cfg(feature = "ssr")]
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{escaped}%")
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct ReceiptPage {
+    pub receipts: Vec<ReceiptWithItems>,
+    pub page: i64,
+    /// Matching receipts across all pages
+    pub total_count: i64,
+}
+
+impl ReceiptPage {
+    pub fn page_count(&self) -> i64 {
+        ((self.total_count + RECEIPTS_PER_PAGE - 1) / RECEIPTS_PER_PAGE).max(1)
     }
 }
 
 impl ReceiptWithItems {
-    pub fn total(&self) -> f32 {
+    pub fn total(&self) -> Money {
         self.items.iter().map(|i| i.price).sum()
     }
 
-    pub fn anders_sum(&self) -> f32 {
-        self.items
-            .iter()
-            .filter(|i| i.anders_pay)
-            .map(|i| i.price / (i.ac_pay as u8 + i.anders_pay as u8 + i.andreas_pay as u8) as f32)
-            .sum()
+    /// The receipt wide shares after applying the split mode.
+    pub fn split_shares(&self) -> Shares {
+        split_shares(
+            self.receipt.split_mode,
+            self.receipt.shares(),
+            self.days.as_deref().unwrap_or_default(),
+        )
+    }
+
+    /// What each member owes for this receipt, in the order of `Member::ALL`.
+    pub fn member_sums(&self) -> [Money; 3] {
+        member_sums(
+            attach_to_products(self.items.iter().map(|i| (i.kind, i.price, i.shares()))),
+            self.split_shares(),
+        )
+    }
+
+    pub fn anders_sum(&self) -> Money {
+        self.member_sums()[Member::Anders.index()]
+    }
+
+    pub fn andreas_sum(&self) -> Money {
+        self.member_sums()[Member::Andreas.index()]
+    }
+
+    pub fn ac_sum(&self) -> Money {
+        self.member_sums()[Member::Ac.index()]
+    }
+
+    pub fn fingerprint(&self) -> ReceiptFingerprint {
+        ReceiptFingerprint::new(
+            &self.receipt.store,
+            self.receipt.datetime,
+            self.items.iter().map(|i| (i.name.as_str(), i.price)),
+        )
     }
 
-    pub fn andreas_sum(&self) -> f32 {
-        self.items
-            .iter()
-            .filter(|i| i.andreas_pay)
-            .map(|i| i.price / (i.ac_pay as u8 + i.anders_pay as u8 + i.andreas_pay as u8) as f32)
-            .sum()
+    /// The part of this receipt that goes to the given day, zero if it isn't linked.
+    pub fn cost_for_day(&self, day_id: i32) -> Money {
+        let day_ids: Vec<i32> = self.days.iter().flatten().map(|d| d.id).collect();
+        allocate_to_days(self.total(), &day_ids)
+            .into_iter()
+            .find_map(|(id, cost)| (id == day_id).then_some(cost))
+            .unwrap_or(Money::ZERO)
     }
+}
+
+/// What makes two receipts the same purchase: the store, the printed time to the
+/// minute, the total and the items regardless of their order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptFingerprint {
+    store: String,
+    datetime: NaiveDateTime,
+    total: Money,
+    items: Vec<(String, Money)>,
+}
 
-    pub fn ac_sum(&self) -> f32 {
-        self.items
-            .iter()
-            .filter(|i| i.ac_pay)
-            .map(|i| i.price / (i.ac_pay as u8 + i.anders_pay as u8 + i.andreas_pay as u8) as f32)
-            .sum()
+impl ReceiptFingerprint {
+    pub fn new<'a>(
+        store: &str,
+        datetime: NaiveDateTime,
+        items: impl IntoIterator<Item = (&'a str, Money)>,
+    ) -> ReceiptFingerprint {
+        let normalize = |s: &str| {
+            s.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        };
+        let mut items: Vec<(String, Money)> = items
+            .into_iter()
+            .map(|(name, price)| (normalize(name), price))
+            .collect();
+        items.sort();
+        ReceiptFingerprint {
+            store: normalize(store),
+            datetime: datetime.with_second(0).unwrap_or(datetime),
+            total: items.iter().map(|(_, price)| *price).sum(),
+            items,
+        }
     }
 }
 
+/// Spreads a receipt total evenly over the days it is linked to. The days are
+/// ordered by id so a leftover øre always lands on the same day.
+pub fn allocate_to_days(total: Money, day_ids: &[i32]) -> Vec<(i32, Money)> {
+    let mut day_ids = day_ids.to_vec();
+    day_ids.sort_unstable();
+    day_ids.dedup();
+    let costs = total.split(&vec![1; day_ids.len()]);
+    day_ids.into_iter().zip(costs).collect()
+}
+
+/// How the receipt wide shares are decided.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(diesel::AsExpression, diesel::FromSqlRow))]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum SplitMode {
+    /// Use the receipt shares as they are.
+    #[default]
+    Equal,
+    /// Multiply the receipt shares with how many of the linked days each member
+    /// attended dinner.
+    Attendance,
+}
+
+impl SplitMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SplitMode::Equal => "equal",
+            SplitMode::Attendance => "attendance",
+        }
+    }
+}
+
+impl std::str::FromStr for SplitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "equal" => Ok(SplitMode::Equal),
+            "attendance" => Ok(SplitMode::Attendance),
+            s => Err(format!("Unknown split mode {s}")),
+        }
+    }
+}
+
+text_enum_sql!(SplitMode);
+
+/// The shares to split a receipt by. In attendance mode each member's share is
+/// multiplied with the number of `days` they attended, falling back to the plain
+/// shares when no days are linked or nobody attended them.
+pub fn split_shares(mode: SplitMode, shares: Shares, days: &[Day]) -> Shares {
+    match mode {
+        SplitMode::Equal => shares,
+        SplitMode::Attendance => {
+            let attended =
+                |member: Member| days.iter().filter(|d| d.attends(member)).count() as i32;
+            let weighted = Member::ALL.map(|m| shares[m.index()] * attended(m));
+            if weighted.iter().all(|w| *w == 0) {
+                shares
+            } else {
+                weighted
+            }
+        }
+    }
+}
+
+/// What a receipt line is. Discounts and deposits belong to the product above them
+/// and are split the same way, deposit returns are split by their own shares.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(diesel::AsExpression, diesel::FromSqlRow))]
+#[cfg_attr(feature = "ssr", diesel(sql_type = diesel::sql_types::Text))]
+pub enum ItemKind {
+    #[default]
+    Product,
+    Discount,
+    Deposit,
+    DepositReturn,
+}
+
+impl ItemKind {
+    pub const ALL: [ItemKind; 4] = [
+        ItemKind::Product,
+        ItemKind::Discount,
+        ItemKind::Deposit,
+        ItemKind::DepositReturn,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Product => "product",
+            ItemKind::Discount => "discount",
+            ItemKind::Deposit => "deposit",
+            ItemKind::DepositReturn => "deposit_return",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemKind::Product => "Item",
+            ItemKind::Discount => "Discount",
+            ItemKind::Deposit => "Deposit",
+            ItemKind::DepositReturn => "Deposit return",
+        }
+    }
+
+    /// Guesses the kind from the line text of a Norwegian receipt.
+    pub fn from_name(name: &str) -> ItemKind {
+        let name = name.to_lowercase();
+        if name.contains("rabatt") {
+            ItemKind::Discount
+        } else if name.contains("panteretur") || name.contains("pantretur") {
+            ItemKind::DepositReturn
+        } else if name
+            .split(|c: char| !c.is_alphanumeric())
+            // `pant`, also when the amount is stuck to it as in `pant3,00`
+            .any(|word| {
+                word.strip_prefix("pant")
+                    .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit()))
+            })
+        {
+            ItemKind::Deposit
+        } else {
+            ItemKind::Product
+        }
+    }
+
+    /// Whether the line is split like the product above it.
+    pub fn follows_product(&self) -> bool {
+        matches!(self, ItemKind::Discount | ItemKind::Deposit)
+    }
+
+    /// Whether the amount is money back, so it should be negative.
+    pub fn is_refund(&self) -> bool {
+        matches!(self, ItemKind::Discount | ItemKind::DepositReturn)
+    }
+}
+
+impl std::str::FromStr for ItemKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ItemKind::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| format!("Unknown item kind {s}"))
+    }
+}
+
+text_enum_sql!(ItemKind);
+
+/// Gives discounts and deposits the shares of the closest product line above them,
+/// so a discount on something only one member buys only lowers that member's sum.
+/// Lines before the first product keep their own shares.
+pub fn attach_to_products(
+    items: impl IntoIterator<Item = (ItemKind, Money, Shares)>,
+) -> Vec<(Money, Shares)> {
+    let mut product_shares = None;
+    items
+        .into_iter()
+        .map(|(kind, price, shares)| {
+            if kind == ItemKind::Product {
+                product_shares = Some(shares);
+            }
+            match (kind.follows_product(), product_shares) {
+                (true, Some(product_shares)) => (price, product_shares),
+                _ => (price, shares),
+            }
+        })
+        .collect()
+}
+
+/// How an item is divided between the members: its own shares multiplied with the
+/// receipt's shares, falling back to the receipt's shares if that gives nobody a share.
+pub fn item_weights(shares: Shares, receipt_shares: Shares) -> [i64; 3] {
+    let weights = Member::ALL
+        .map(|m| shares[m.index()].max(0) as i64 * receipt_shares[m.index()].max(0) as i64);
+    if weights.iter().all(|w| *w == 0) {
+        receipt_shares.map(|s| s.max(0) as i64)
+    } else {
+        weights
+    }
+}
+
+/// Splits a receipt between the members. Each item is divided by its own shares
+/// multiplied with the receipt's shares, and the per member amounts are rounded to
+/// whole øre so they always add up to the receipt total.
+///
+/// An item nobody has a share in falls back to the receipt's shares, and to an even
+/// split if the receipt has none either.
+pub fn member_sums(
+    items: impl IntoIterator<Item = (Money, Shares)>,
+    receipt_shares: Shares,
+) -> [Money; 3] {
+    let mut total = 0;
+    let mut exact = [0f64; 3];
+    for (price, shares) in items {
+        let amount = price.ore();
+        total += amount;
+
+        let weights = item_weights(shares, receipt_shares);
+        let sum: i64 = weights.iter().sum();
+        for (e, weight) in exact.iter_mut().zip(weights) {
+            *e += match sum {
+                0 => amount as f64 / weights.len() as f64,
+                _ => (amount * weight) as f64 / sum as f64,
+            };
+        }
+    }
+    let sums = apportion(total, &exact);
+    Member::ALL.map(|m| Money::from_ore(sums[m.index()]))
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 #[cfg_attr(feature = "ssr", derive(Insertable, AsChangeset))]
 #[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::receipts))]
 pub struct ReceiptForm {
     pub store: String,
     pub datetime: NaiveDateTime,
+    pub anders_share: i32,
+    pub andreas_share: i32,
+    pub ac_share: i32,
+    pub split_mode: SplitMode,
+    /// Perceptual hash of the scanned photo, see `ocr::image_hash`.
+    pub image_hash: Option<i64>,
+    pub store_id: Option<i32>,
+}
+
+impl ReceiptForm {
+    pub fn shares(&self) -> Shares {
+        [self.anders_share, self.andreas_share, self.ac_share]
+    }
+
+    pub fn fingerprint(&self, items: &[ReceiptItemForm]) -> ReceiptFingerprint {
+        ReceiptFingerprint::new(
+            &self.store,
+            self.datetime,
+            items.iter().map(|i| (i.name.as_str(), i.price)),
+        )
+    }
 }
 
 #[cfg(feature = "ssr")]
@@ -141,6 +646,19 @@ pub struct Receipt {
     pub id: i32,
     pub store: String,
     pub datetime: NaiveDateTime,
+    pub anders_share: i32,
+    pub andreas_share: i32,
+    pub ac_share: i32,
+    pub split_mode: SplitMode,
+    /// Perceptual hash of the scanned photo, see `ocr::image_hash`.
+    pub image_hash: Option<i64>,
+    pub store_id: Option<i32>,
+}
+
+impl Receipt {
+    pub fn shares(&self) -> Shares {
+        [self.anders_share, self.andreas_share, self.ac_share]
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
@@ -149,10 +667,21 @@ pub struct Receipt {
 pub struct ReceiptItemForm {
     pub receipt_id: i32,
     pub name: String,
-    pub price: f32,
-    pub anders_pay: bool,
-    pub andreas_pay: bool,
-    pub ac_pay: bool,
+    pub price: Money,
+    pub anders_share: i32,
+    pub andreas_share: i32,
+    pub ac_share: i32,
+    pub kind: ItemKind,
+    pub category_id: Option<i32>,
+    /// The name as read by OCR, only for scanned items
+    pub raw_name: Option<String>,
+    pub product_id: Option<i32>,
+}
+
+impl ReceiptItemForm {
+    pub fn shares(&self) -> Shares {
+        [self.anders_share, self.andreas_share, self.ac_share]
+    }
 }
 
 #[cfg(feature = "ssr")]
@@ -183,12 +712,38 @@ pub struct ReceiptItem {
     pub id: i32,
     pub receipt_id: i32,
     pub name: String,
-    pub price: f32,
-    pub anders_pay: bool,
-    pub andreas_pay: bool,
-    pub ac_pay: bool,
+    pub price: Money,
+    pub anders_share: i32,
+    pub andreas_share: i32,
+    pub ac_share: i32,
+    pub kind: ItemKind,
+    pub category_id: Option<i32>,
+    /// The name as read by OCR, only for scanned items
+    pub raw_name: Option<String>,
+    pub product_id: Option<i32>,
+}
+
+impl ReceiptItem {
+    pub fn shares(&self) -> Shares {
+        [self.anders_share, self.andreas_share, self.ac_share]
+    }
 }
 
+#[cfg(feature = "ssr")]
+impl ReceiptItem {
+    pub fn set_category(
+        db: &mut DbConn,
+        id: i32,
+        category_id: Option<i32>,
+    ) -> Result<ReceiptItem, Error> {
+        update(receipt_items::table.find(id))
+            .set(receipt_items::category_id.eq(category_id))
+            .get_result(db)
+    }
+}
+
+/// Only used by the server, receipts carry their days as `ReceiptWithItems::days`.
+#[cfg(feature = "ssr")]
 #[cfg_attr(
     feature = "ssr",
     derive(Identifiable, Insertable, Queryable, Selectable, Associations,)
@@ -200,8 +755,8 @@ pub struct ReceiptItem {
 #[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
 #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
 pub struct ReceiptDay {
-    pub day_id: i32,
     pub receipt_id: i32,
+    pub day_id: i32,
 }
 
 #[cfg(feature = "ssr")]
@@ -215,4 +770,35 @@ impl ReceiptDay {
             .do_nothing()
             .get_result::<ReceiptDay>(db)
     }
+
+    /// What each day cost, from all receipts linked to it.
+    pub fn costs_by_day(db: &mut DbConn) -> Result<HashMap<i32, Money>, Error> {
+        use crate::schema::receipt_days;
+
+        let links: Vec<ReceiptDay> = receipt_days::table.load(db)?;
+        let prices: Vec<(i32, Money)> = receipt_items::table
+            .select((receipt_items::receipt_id, receipt_items::price))
+            .load(db)?;
+
+        let mut totals: HashMap<i32, Money> = HashMap::new();
+        for (receipt_id, price) in prices {
+            *totals.entry(receipt_id).or_default() += price;
+        }
+        let mut day_ids: HashMap<i32, Vec<i32>> = HashMap::new();
+        for link in links {
+            day_ids
+                .entry(link.receipt_id)
+                .or_default()
+                .push(link.day_id);
+        }
+
+        let mut costs: HashMap<i32, Money> = HashMap::new();
+        for (receipt_id, day_ids) in day_ids {
+            let total = totals.get(&receipt_id).copied().unwrap_or_default();
+            for (day_id, cost) in allocate_to_days(total, &day_ids) {
+                *costs.entry(day_id).or_default() += cost;
+            }
+        }
+        Ok(costs)
+    }
 }
@@ -0,0 +1,166 @@
+use crate::models::ingredient::Ingredient;
+use crate::models::meal_tag::parse_tags;
+use crate::models::member::Member;
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::schema::member_restrictions;
+
+/// Offered when tagging catalog ingredients and editing restrictions.
+pub const COMMON_ALLERGENS: [&str; 10] = [
+    "shellfish",
+    "fish",
+    "gluten",
+    "lactose",
+    "nuts",
+    "peanuts",
+    "egg",
+    "soy",
+    "pork",
+    "meat",
+];
+
+/// Something a member can't or won't eat, matched against the allergens of
+/// catalog ingredients.
+#[cfg_attr(feature = "ssr", derive(Queryable, Selectable, Insertable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::member_restrictions))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct MemberRestriction {
+    pub member: Member,
+    pub allergen: String,
+}
+
+#[cfg(feature = "ssr")]
+impl MemberRestriction {
+    pub fn get_all(db: &mut DbConn) -> Result<Vec<MemberRestriction>, Error> {
+        member_restrictions::table
+            .order_by((member_restrictions::member, member_restrictions::allergen))
+            .select(MemberRestriction::as_select())
+            .load(db)
+    }
+
+    /// Replaces the member's restrictions with the comma separated allergens.
+    pub fn set_for_member(
+        db: &mut DbConn,
+        member: Member,
+        allergens: &str,
+    ) -> Result<Vec<MemberRestriction>, Error> {
+        delete(member_restrictions::table.filter(member_restrictions::member.eq(member)))
+            .execute(db)?;
+        let restrictions: Vec<MemberRestriction> = parse_tags(allergens)
+            .into_iter()
+            .map(|allergen| MemberRestriction { member, allergen })
+            .collect();
+        insert_into(member_restrictions::table)
+            .values(&restrictions)
+            .execute(db)?;
+        Ok(restrictions)
+    }
+}
+
+/// An ingredient an attending member can't have.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub member: Member,
+    pub allergen: String,
+    pub ingredient: String,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} ({})",
+            self.member, self.ingredient, self.allergen
+        )
+    }
+}
+
+/// Every ingredient that has an allergen one of the members is restricted from.
+pub fn find_conflicts<'a>(
+    restrictions: &[MemberRestriction],
+    members: impl IntoIterator<Item = Member>,
+    ingredients: impl IntoIterator<Item = &'a Ingredient>,
+) -> Vec<Conflict> {
+    let members: Vec<Member> = members.into_iter().collect();
+    let mut conflicts = Vec::new();
+    for ingredient in ingredients {
+        for allergen in parse_tags(&ingredient.allergens) {
+            conflicts.extend(
+                restrictions
+                    .iter()
+                    .filter(|r| r.allergen == allergen && members.contains(&r.member))
+                    .map(|r| Conflict {
+                        member: r.member,
+                        allergen: allergen.clone(),
+                        ingredient: ingredient.name.clone(),
+                    }),
+            );
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ingredient(name: &str, allergens: &str) -> Ingredient {
+        Ingredient {
+            id: 0,
+            name: String::from(name),
+            amount: 1,
+            meal_id: 1,
+            catalog_id: 0,
+            allergens: String::from(allergens),
+        }
+    }
+
+    fn restriction(member: Member, allergen: &str) -> MemberRestriction {
+        MemberRestriction {
+            member,
+            allergen: String::from(allergen),
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let restrictions = [
+            restriction(Member::Ac, "shellfish"),
+            restriction(Member::Anders, "lactose"),
+        ];
+        let ingredients = [
+            ingredient("Reker", "shellfish"),
+            ingredient("Fløte", "Lactose"),
+            ingredient("Pasta", "gluten"),
+        ];
+        assert_eq!(
+            find_conflicts(&restrictions, Member::ALL, &ingredients),
+            [
+                Conflict {
+                    member: Member::Ac,
+                    allergen: String::from("shellfish"),
+                    ingredient: String::from("Reker"),
+                },
+                Conflict {
+                    member: Member::Anders,
+                    allergen: String::from("lactose"),
+                    ingredient: String::from("Fløte"),
+                },
+            ]
+        );
+        // Only members eating dinner count
+        let conflicts = find_conflicts(
+            &restrictions,
+            [Member::Anders, Member::Andreas],
+            &ingredients,
+        );
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].to_string(), "Anders: Fløte (lactose)");
+        assert!(find_conflicts(&[], Member::ALL, &ingredients).is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+
+/// A meal matching a search, see `rank_meals`.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct MealHit {
+    pub meal_id: i32,
+    pub score: u32,
+    /// The tag or ingredient that matched, when the name didn't
+    pub matched: Option<String>,
+}
+
+/// What a meal can be found by.
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug, Default)]
+pub struct SearchCandidate {
+    pub meal_id: i32,
+    pub name: String,
+    pub tags: Vec<String>,
+    /// Ingredient names and their catalog aliases
+    pub ingredients: Vec<String>,
+}
+
+/// Lowercase words without diacritics, so "Rødløk", "rodlok" and "roedloek" are
+/// the same. Both "å" and "aa" become "a", "æ" becomes "ae" and "ø" and "oe"
+/// become "o".
+#[cfg(feature = "ssr")]
+pub fn fold(text: &str) -> Vec<String> {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.to_lowercase().chars() {
+        match c {
+            'æ' | 'ä' => folded.push_str("ae"),
+            'ø' | 'ö' => folded.push('o'),
+            'å' | 'à' | 'á' | 'â' => folded.push('a'),
+            'é' | 'è' | 'ê' | 'ë' => folded.push('e'),
+            'ü' | 'ú' | 'ù' | 'û' => folded.push('u'),
+            'í' | 'ì' | 'ï' | 'î' => folded.push('i'),
+            'ó' | 'ò' | 'ô' => folded.push('o'),
+            c if c.is_alphanumeric() => folded.push(c),
+            _ => folded.push(' '),
+        }
+    }
+    folded
+        .replace("aa", "a")
+        .replace("oe", "o")
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Edits to turn `a` into `b`, counting a swap of two neighbours as one.
+#[cfg(feature = "ssr")]
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// How well a folded query word matches a folded word, from 0 to 100. Short
+/// words have to be exact, longer ones may have a typo or two.
+#[cfg(feature = "ssr")]
+fn word_score(query: &str, word: &str) -> u32 {
+    if query == word {
+        return 100;
+    }
+    if word.starts_with(query) {
+        return 80;
+    }
+    let query: Vec<char> = query.chars().collect();
+    let word: Vec<char> = word.chars().collect();
+    if query.len() >= 3 && word.windows(query.len()).any(|w| w == query) {
+        return 60;
+    }
+    let allowed = match query.len() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    if allowed == 0 {
+        return 0;
+    }
+    // Compare with the start of the word too, for typos while still typing
+    let prefix = &word[..word.len().min(query.len())];
+    let distance = edit_distance(&query, &word).min(edit_distance(&query, prefix));
+    if distance <= allowed {
+        50 - 10 * distance as u32
+    } else {
+        0
+    }
+}
+
+/// The best score of a query word in a text.
+#[cfg(feature = "ssr")]
+fn text_score(query: &str, text: &str) -> u32 {
+    fold(text)
+        .iter()
+        .map(|word| word_score(query, word))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Meals where every word of the query matches the name, a tag or an ingredient,
+/// best match first. Name matches count the most, then tags.
+#[cfg(feature = "ssr")]
+pub fn rank_meals(query: &str, candidates: &[SearchCandidate]) -> Vec<MealHit> {
+    let words = fold(query);
+    if words.is_empty() {
+        return vec![];
+    }
+    let mut hits: Vec<(MealHit, &str)> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let mut score = 0;
+            let mut matched = None;
+            for word in &words {
+                let name = text_score(word, &candidate.name) * 3;
+                let (tag, tag_score) = best_of(word, &candidate.tags);
+                let (ingredient, ingredient_score) = best_of(word, &candidate.ingredients);
+                let best = name.max(tag_score * 2).max(ingredient_score);
+                if best == 0 {
+                    return None;
+                }
+                if best > name && matched.is_none() {
+                    matched = if tag_score * 2 >= ingredient_score {
+                        tag
+                    } else {
+                        ingredient
+                    };
+                }
+                score += best;
+            }
+            let hit = MealHit {
+                meal_id: candidate.meal_id,
+                score,
+                matched,
+            };
+            Some((hit, candidate.name.as_str()))
+        })
+        .collect();
+    hits.sort_by(|(a, a_name), (b, b_name)| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a_name.to_lowercase().cmp(&b_name.to_lowercase()))
+    });
+    hits.into_iter().map(|(hit, _)| hit).collect()
+}
+
+#[cfg(feature = "ssr")]
+fn best_of(word: &str, texts: &[String]) -> (Option<String>, u32) {
+    texts
+        .iter()
+        .map(|text| (Some(text.clone()), text_score(word, text)))
+        .max_by_key(|(_, score)| *score)
+        .unwrap_or((None, 0))
+}
+
+/// Every meal with its tags, ingredients and their aliases.
+#[cfg(feature = "ssr")]
+pub fn get_candidates(db: &mut DbConn) -> Result<Vec<SearchCandidate>, Error> {
+    use crate::models::ingredient_catalog::CatalogIngredient;
+    use crate::schema::meal_tags;
+    use std::collections::HashMap;
+
+    let catalog: HashMap<i32, CatalogIngredient> = CatalogIngredient::get_all(db)?
+        .into_iter()
+        .map(|entry| (entry.id, entry))
+        .collect();
+    let mut candidates: HashMap<i32, SearchCandidate> = Meal::get_all(db)?
+        .into_iter()
+        .map(|meal| {
+            let candidate = SearchCandidate {
+                meal_id: meal.id,
+                name: meal.name,
+                ..Default::default()
+            };
+            (meal.id, candidate)
+        })
+        .collect();
+    for ingredient in Ingredient::get_all(db)? {
+        if let Some(candidate) = candidates.get_mut(&ingredient.meal_id) {
+            candidate.ingredients.push(ingredient.name);
+            if let Some(entry) = catalog.get(&ingredient.catalog_id) {
+                candidate
+                    .ingredients
+                    .extend(entry.alias_list().into_iter().map(str::to_owned));
+            }
+        }
+    }
+    let tags: Vec<(i32, String)> = meal_tags::table
+        .select((meal_tags::meal_id, meal_tags::name))
+        .load(db)?;
+    for (meal_id, tag) in tags {
+        if let Some(candidate) = candidates.get_mut(&meal_id) {
+            candidate.tags.push(tag);
+        }
+    }
+    Ok(candidates.into_values().collect())
+}
+
+#[cfg(feature = "ssr")]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candidate(meal_id: i32, name: &str, tags: &[&str], ingredients: &[&str]) -> SearchCandidate {
+        SearchCandidate {
+            meal_id,
+            name: String::from(name),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ingredients: ingredients.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    fn ids(hits: Vec<MealHit>) -> Vec<i32> {
+        hits.into_iter().map(|h| h.meal_id).collect()
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(fold("Rødløk"), fold("rodlok"));
+        assert_eq!(fold("Rødløk"), fold("ROEDLOEK"));
+        assert_eq!(fold("Blåbær-pai"), ["blabaer", "pai"]);
+        assert_eq!(fold("blaabaer"), ["blabaer"]);
+        assert_eq!(fold("Crème brûlée"), ["creme", "brulee"]);
+    }
+
+    #[test]
+    fn test_word_score() {
+        assert_eq!(word_score("taco", "taco"), 100);
+        assert_eq!(word_score("tac", "taco"), 80);
+        assert_eq!(word_score("lok", "rodlok"), 60);
+        // One typo in a short word, two in a long one
+        assert_eq!(word_score("tcao", "taco"), 40);
+        assert_eq!(word_score("lasange", "lasagne"), 40);
+        assert_eq!(word_score("spagetti", "spaghetti"), 40);
+        assert_eq!(word_score("kyllign", "kyllingfilet"), 40);
+        assert_eq!(word_score("ris", "rå"), 0);
+        assert_eq!(word_score("tomat", "potet"), 0);
+    }
+
+    #[test]
+    fn test_rank_meals() {
+        let meals = vec![
+            candidate(
+                1,
+                "Taco",
+                &["quick", "kid-friendly"],
+                &["Kjøttdeig", "Rødløk"],
+            ),
+            candidate(2, "Laksetaco", &["fish"], &["Laks", "Tortilla"]),
+            candidate(3, "Lasagne", &["weekend"], &["Kjøttdeig", "Pasta"]),
+            candidate(4, "Dal", &["vegetarian"], &["Linser", "Red onion"]),
+        ];
+        assert_eq!(ids(rank_meals("taco", &meals)), [1, 2]);
+        assert_eq!(ids(rank_meals("tcao", &meals)), [1]);
+        assert_eq!(ids(rank_meals("lasange", &meals)), [3]);
+        // Ingredients and tags, with what matched
+        let hits = rank_meals("kjottdeig", &meals);
+        assert_eq!(ids(hits.clone()), [3, 1]);
+        assert_eq!(hits[0].matched.as_deref(), Some("Kjøttdeig"));
+        assert_eq!(ids(rank_meals("vegetarian", &meals)), [4]);
+        // Every word has to match somewhere
+        assert_eq!(ids(rank_meals("taco fish", &meals)), [2]);
+        assert_eq!(ids(rank_meals("rodlok", &meals)), [1]);
+        assert!(rank_meals("  ", &meals).is_empty());
+    }
+}
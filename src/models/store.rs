@@ -0,0 +1,187 @@
+use crate::models::money::Money;
+#[cfg(feature = "ssr")]
+use diesel::prelude::*;
+
+#[cfg(feature = "ssr")]
+use crate::api::ssr::*;
+#[cfg(feature = "ssr")]
+use crate::schema::stores;
+
+/// Stores are only added by saving a scanned receipt from a new one, see
+/// `Store::detect`.
+#[cfg_attr(feature = "ssr", derive(Insertable))]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::stores))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct StoreForm {
+    pub chain: String,
+    pub name: String,
+    pub address: Option<String>,
+    pub org_number: Option<String>,
+    pub aisles: String,
+}
+
+#[cfg(feature = "ssr")]
+impl StoreForm {
+    pub fn insert(&self, db: &mut DbConn) -> Result<Store, Error> {
+        insert_into(stores::table).values(self).get_result(db)
+    }
+
+    /// Adds the store unless one with the org number is saved already, as when
+    /// two receipts from a new store are scanned before either is saved.
+    pub fn find_or_insert(&self, db: &mut DbConn) -> Result<Store, Error> {
+        let known = match &self.org_number {
+            Some(org_number) => stores::table
+                .filter(stores::org_number.eq(org_number))
+                .first(db)
+                .optional()?,
+            None => None,
+        };
+        match known {
+            Some(store) => Ok(store),
+            None => self.insert(db),
+        }
+    }
+}
+
+/// What a scanned receipt tells about its store.
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DetectedStore {
+    Known(Store),
+    /// A new org number, the store is added when the receipt is saved
+    New(StoreForm),
+}
+
+#[cfg_attr(
+    feature = "ssr",
+    derive(Queryable, Selectable, Identifiable, PartialEq, AsChangeset)
+)]
+#[cfg_attr(feature = "ssr", diesel(table_name = crate::schema::stores))]
+#[cfg_attr(feature = "ssr", diesel(check_for_backend(diesel::sqlite::Sqlite)))]
+#[cfg_attr(feature = "ssr", diesel(treat_none_as_null = true))]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct Store {
+    pub id: i32,
+    pub chain: String,
+    pub name: String,
+    pub address: Option<String>,
+    pub org_number: Option<String>,
+    pub aisles: String,
+}
+
+impl Store {
+    /// The chain followed by the branch, e.g. `Rema 1000 Grünerløkka`.
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() || self.name == self.chain {
+            self.chain.clone()
+        } else {
+            format!("{} {}", self.chain, self.name)
+        }
+    }
+
+    /// Aisle names in walking order.
+    pub fn aisle_list(&self) -> Vec<&str> {
+        self.aisles
+            .lines()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl Store {
+    pub fn get(db: &mut DbConn, id: i32) -> Result<Store, Error> {
+        stores::table.find(id).first(db)
+    }
+
+    pub fn get_all(db: &mut DbConn) -> Result<Vec<Store>, Error> {
+        stores::table
+            .order_by((stores::chain, stores::name))
+            .load(db)
+    }
+
+    pub fn update(&self, db: &mut DbConn) -> Result<Store, Error> {
+        self.save_changes(db)
+    }
+
+    /// Finds the store a scanned receipt is from, without saving anything. The org
+    /// number identifies a store exactly, and a new one proposes a store to add
+    /// with the receipt. Without it the chain is only enough when we know a single
+    /// store of that chain.
+    pub fn detect(
+        db: &mut DbConn,
+        chain: Option<&str>,
+        org_number: Option<&str>,
+    ) -> Result<Option<DetectedStore>, Error> {
+        if let Some(org_number) = org_number {
+            let known = stores::table
+                .filter(stores::org_number.eq(org_number))
+                .first(db)
+                .optional()?;
+            return Ok(match (known, chain) {
+                (Some(store), _) => Some(DetectedStore::Known(store)),
+                (None, Some(chain)) => Some(DetectedStore::New(StoreForm {
+                    chain: chain.to_owned(),
+                    name: chain.to_owned(),
+                    org_number: Some(org_number.to_owned()),
+                    ..Default::default()
+                })),
+                (None, None) => None,
+            });
+        }
+        let Some(chain) = chain else {
+            return Ok(None);
+        };
+        let mut stores: Vec<Store> = stores::table
+            .filter(stores::chain.eq(chain))
+            .limit(2)
+            .load(db)?;
+        Ok(match stores.len() {
+            1 => stores.pop().map(DetectedStore::Known),
+            _ => None,
+        })
+    }
+}
+
+/// A store with what has been spent there, for the store list.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct StoreSpending {
+    pub store: Store,
+    pub receipt_count: i64,
+    pub total: Money,
+}
+
+#[cfg(feature = "ssr")]
+impl StoreSpending {
+    pub fn get_all(db: &mut DbConn) -> Result<Vec<StoreSpending>, Error> {
+        use std::collections::HashMap;
+
+        let receipt_stores: Vec<Option<i32>> =
+            receipts::table.select(receipts::store_id).load(db)?;
+        let prices: Vec<(Option<i32>, Money)> = receipt_items::table
+            .inner_join(receipts::table)
+            .select((receipts::store_id, receipt_items::price))
+            .load(db)?;
+
+        let mut counts: HashMap<i32, i64> = HashMap::new();
+        for store_id in receipt_stores.into_iter().flatten() {
+            *counts.entry(store_id).or_default() += 1;
+        }
+        let mut totals: HashMap<i32, Money> = HashMap::new();
+        for (store_id, price) in prices {
+            if let Some(store_id) = store_id {
+                *totals.entry(store_id).or_default() += price;
+            }
+        }
+
+        Ok(Store::get_all(db)?
+            .into_iter()
+            .map(|store| StoreSpending {
+                receipt_count: counts.get(&store.id).copied().unwrap_or_default(),
+                total: totals.get(&store.id).copied().unwrap_or_default(),
+                store,
+            })
+            .collect())
+    }
+}
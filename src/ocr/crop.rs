@@ -0,0 +1,69 @@
+use std::io::Cursor;
+
+use base64::Engine;
+use image::{DynamicImage, ImageFormat};
+use leptos::logging::error;
+
+use crate::models::ocr_job::ScannedItem;
+
+/// Space kept around the line, so letters cut by a tight box are still readable.
+const PADDING: u32 = 6;
+
+/// Cuts the line of every scanned item out of the photo it was read from, see
+/// `ScannedItem::image`.
+pub fn add_item_images(path: &str, item_scans: &mut [ScannedItem]) {
+    if item_scans.iter().all(|scan| scan.bbox.is_none()) {
+        return;
+    }
+    let photo = match image::open(path) {
+        Ok(photo) => photo,
+        Err(e) => {
+            error!("Could not open {path} to crop items: {e}");
+            return;
+        }
+    };
+    for scan in item_scans {
+        scan.image = scan.bbox.and_then(|bbox| crop_data_url(&photo, bbox));
+    }
+}
+
+/// The part of the image inside `bbox` as a PNG data URL, `None` if the box is
+/// outside the image.
+pub fn crop_data_url(image: &DynamicImage, [left, top, right, bottom]: [u32; 4]) -> Option<String> {
+    let left = left.saturating_sub(PADDING);
+    let top = top.saturating_sub(PADDING);
+    let right = (right + PADDING).min(image.width());
+    let bottom = (bottom + PADDING).min(image.height());
+    if left >= right || top >= bottom {
+        return None;
+    }
+
+    png_data_url(&image.crop_imm(left, top, right - left, bottom - top))
+}
+
+/// The image as a PNG data URL.
+pub fn png_data_url(image: &DynamicImage) -> Option<String> {
+    let mut png = Cursor::new(vec![]);
+    image.write_to(&mut png, ImageFormat::Png).ok()?;
+    let base64 = base64::engine::general_purpose::STANDARD.encode(png.into_inner());
+    Some(format!("data:image/png;base64,{base64}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn test_crop_data_url() {
+        let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(200, 100, Luma([255])));
+        let url = crop_data_url(&image, [10, 20, 190, 40]).unwrap();
+        let png = base64::engine::general_purpose::STANDARD
+            .decode(url.strip_prefix("data:image/png;base64,").unwrap())
+            .unwrap();
+        let crop = image::load_from_memory(&png).unwrap();
+        assert_eq!((crop.width(), crop.height()), (192, 32));
+
+        assert_eq!(crop_data_url(&image, [300, 20, 400, 40]), None);
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+
+use crate::models::money::Money;
+use crate::models::ocr_job::ScannedReceipt;
+use crate::models::receipt::{ItemKind, ReceiptForm, ReceiptItemForm, SplitMode};
+
+/// A receipt exported from a loyalty app, e.g.
+///
+/// ```json
+/// {
+///   "store": "Kiwi Majorstuen",
+///   "purchased_at": "2025-03-12T17:45:00",
+///   "items": [
+///     { "name": "Tortilla", "price": 24.90 },
+///     { "name": "Kjøttdeig", "price": "81,99" },
+///     { "name": "Rabatt kjøttdeig", "price": -20, "kind": "discount" }
+///   ]
+/// }
+/// ```
+///
+/// `price` is the line total in kroner, as a number or a string. `kind` is optional
+/// and guessed from the name when missing, and so is the store's `org_number`.
+#[derive(Debug, Deserialize)]
+struct DigitalReceipt {
+    store: String,
+    org_number: Option<String>,
+    purchased_at: NaiveDateTime,
+    items: Vec<DigitalItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DigitalItem {
+    name: String,
+    price: Amount,
+    kind: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Amount {
+    Number(serde_json::Number),
+    Text(String),
+}
+
+impl Amount {
+    fn to_money(&self) -> Result<Money, String> {
+        let text = match self {
+            Amount::Number(n) => n.to_string(),
+            Amount::Text(t) => t.clone(),
+        };
+        text.parse().map_err(|e| format!("{e}"))
+    }
+}
+
+/// Reads a digital receipt into the same form a scanned receipt produces.
+pub fn parse_digital_receipt(json: &str) -> Result<ScannedReceipt, String> {
+    let digital: DigitalReceipt =
+        serde_json::from_str(json).map_err(|e| format!("Not a valid digital receipt: {e}"))?;
+
+    let items = digital
+        .items
+        .into_iter()
+        .map(|item| {
+            let kind = match &item.kind {
+                Some(kind) => kind.parse()?,
+                None => ItemKind::from_name(&item.name),
+            };
+            let price = item.price.to_money()?;
+            Ok(ReceiptItemForm {
+                receipt_id: -1, // Set in `create_receipt_with_items`
+                name: item.name,
+                price: if kind.is_refund() {
+                    -price.abs()
+                } else {
+                    price
+                },
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                kind,
+                category_id: None,
+                raw_name: None,
+                product_id: None,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(ScannedReceipt {
+        receipt: ReceiptForm {
+            store: digital.store,
+            datetime: digital.purchased_at,
+            anders_share: 1,
+            andreas_share: 1,
+            ac_share: 1,
+            split_mode: SplitMode::Equal,
+            image_hash: None,
+            store_id: None,
+        },
+        items,
+        item_scans: vec![],
+        org_number: digital.org_number,
+        new_store: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_digital_receipt() {
+        let scanned = parse_digital_receipt(
+            r#"{
+                "store": "Kiwi Majorstuen",
+                "purchased_at": "2025-03-12T17:45:00",
+                "items": [
+                    { "name": "Tortilla", "price": 24.9 },
+                    { "name": "Kjøttdeig", "price": "81,99" },
+                    { "name": "Rabatt kjøttdeig", "price": 20 },
+                    { "name": "Flaskepant", "price": "3.00", "kind": "deposit" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(scanned.receipt.store, "Kiwi Majorstuen");
+        let items: Vec<_> = scanned
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.price.ore(), i.kind))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("Tortilla", 2490, ItemKind::Product),
+                ("Kjøttdeig", 8199, ItemKind::Product),
+                ("Rabatt kjøttdeig", -2000, ItemKind::Discount),
+                ("Flaskepant", 300, ItemKind::Deposit),
+            ]
+        );
+        assert!(parse_digital_receipt(r#"{"store": "Kiwi"}"#).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+/// Photos of the same receipt rarely hash to exactly the same value, but stay
+/// within a few bits of each other.
+pub const MAX_DISTANCE: u32 = 8;
+
+/// Difference hash: shrinks the image to 9x8 gray pixels and sets one bit per pixel
+/// that is brighter than its right neighbour. Survives rescaling and recompression.
+pub fn dhash(image: &DynamicImage) -> i64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | (left > right) as u64;
+        }
+    }
+    hash as i64
+}
+
+/// Hashes the image file, `None` if it can't be decoded (e.g. a PDF).
+pub fn hash_file(path: &str) -> Option<i64> {
+    image::open(path).ok().map(|image| dhash(&image))
+}
+
+pub fn distance(a: i64, b: i64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+pub fn is_similar(a: i64, b: i64) -> bool {
+    distance(a, b) <= MAX_DISTANCE
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    fn gradient(width: u32, height: u32, flip: bool) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(width, height, |x, y| {
+            let v = ((x * 7 + y * 3) % 256) as u8;
+            Luma([if flip { 255 - v } else { v }])
+        }))
+    }
+
+    #[test]
+    fn test_dhash_survives_resizing() {
+        let original = gradient(900, 1200, false);
+        let smaller = original.resize(300, 400, FilterType::Nearest);
+        assert!(is_similar(dhash(&original), dhash(&smaller)));
+        assert!(!is_similar(
+            dhash(&original),
+            dhash(&gradient(900, 1200, true))
+        ));
+    }
+}
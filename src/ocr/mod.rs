@@ -0,0 +1,381 @@
+pub mod crop;
+pub mod digital;
+pub mod image_hash;
+pub mod normalize;
+pub mod pdf;
+pub mod preprocess;
+pub mod worker;
+
+use crate::models::money::Money;
+use crate::models::ocr_job::{ScannedItem, ScannedReceipt};
+use crate::models::receipt::{ItemKind, ReceiptForm, ReceiptItemForm, SplitMode};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use leptos::logging::error;
+use serde::Deserialize;
+use tokio::process::Command;
+
+/// Words identifying a chain on the receipt, and the chain name. More specific
+/// names come first so "Coop Extra" isn't detected as just "Coop".
+const CHAINS: [(&str, &str); 11] = [
+    ("rema", "Rema 1000"),
+    ("coop extra", "Coop Extra"),
+    ("coop prix", "Coop Prix"),
+    ("coop mega", "Coop Mega"),
+    ("coop obs", "Coop Obs"),
+    ("coop", "Coop"),
+    ("kiwi", "Kiwi"),
+    ("meny", "Meny"),
+    ("spar", "Spar"),
+    ("joker", "Joker"),
+    ("bunnpris", "Bunnpris"),
+];
+
+/// Store name used when no chain is recognised.
+pub const UNKNOWN_STORE: &str = "Unknown";
+
+#[derive(Debug, Deserialize)]
+struct ReceiptResult {
+    result: bool,
+    lines: Option<Vec<OcrLine>>,
+    error: Option<Vec<String>>,
+}
+
+/// A line of text on the receipt. Confidence and box are only known for photos.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct OcrLine {
+    pub words: Vec<String>,
+    /// Lowest confidence of the words, from 0 to 1
+    pub confidence: Option<f32>,
+    /// Left, top, right and bottom in the uploaded photo
+    #[serde(rename = "box")]
+    pub bbox: Option<[u32; 4]>,
+}
+
+impl From<Vec<String>> for OcrLine {
+    fn from(words: Vec<String>) -> Self {
+        OcrLine {
+            words,
+            ..Default::default()
+        }
+    }
+}
+
+/// Runs PaddleOCR on the image and returns its lines, top to bottom.
+/// The Python process is killed if the returned future is dropped.
+pub async fn ocr_image(image_path: &str) -> Result<Vec<OcrLine>, String> {
+    let output = match Command::new("uv")
+        .arg("run")
+        .arg("--project")
+        .arg("ocr/")
+        .arg("ocr/main.py")
+        .arg(image_path)
+        .kill_on_drop(true)
+        .output()
+        .await
+    {
+        Ok(o) => o,
+        Err(e) => {
+            error!("OCR command failed: {}", e);
+            return Err(String::from("Parsing OCR image failed"));
+        }
+    };
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    let stdout = String::from_utf8(output.stdout).map_err(|e| {
+        error!("Got error while parsing string from utf8 {e}");
+        String::from("Could not parse OCR output")
+    })?;
+    let results: ReceiptResult = serde_json::from_str(&stdout).map_err(|e| {
+        error!("Got error while deserializing {e}");
+        String::from("Could not parse OCR output")
+    })?;
+
+    if !results.result {
+        error!("OCR command failed: {:?}", results.error);
+        return Err(String::from("Parsing OCR image failed"));
+    }
+
+    Ok(results.lines.unwrap_or_default())
+}
+
+/// Reads an uploaded receipt: digital receipts and the text layer of PDFs are read
+/// directly, everything else is treated as a photo and run through OCR.
+pub async fn scan_file(path: &str) -> Result<ScannedReceipt, String> {
+    match std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+    {
+        Some("json") => {
+            let json = tokio::fs::read_to_string(path).await.map_err(|e| {
+                error!("Could not read digital receipt {path}: {e}");
+                String::from("Could not read digital receipt")
+            })?;
+            digital::parse_digital_receipt(&json)
+        }
+        Some("pdf") => pdf::pdf_text(path)
+            .await
+            .map(|lines| parse_receipt(lines.into_iter().map(OcrLine::from).collect())),
+        _ => ocr_image(path).await.map(parse_receipt),
+    }
+}
+
+/// The first chain named on the receipt. Names are matched as whole words, so
+/// "Du sparte" doesn't count as Spar.
+pub fn detect_chain(lines: &[Vec<String>]) -> Option<&'static str> {
+    lines.iter().find_map(|line| {
+        let words: Vec<String> = line.iter().map(|w| w.to_lowercase()).collect();
+        CHAINS.iter().find_map(|(keywords, chain)| {
+            let keywords: Vec<&str> = keywords.split(' ').collect();
+            words
+                .windows(keywords.len())
+                .any(|window| window.iter().zip(&keywords).all(|(w, k)| w == k))
+                .then_some(*chain)
+        })
+    })
+}
+
+/// Finds the nine digit organisation number, written as e.g. `Org.nr: 912 345 678 MVA`
+/// or `Foretaksregisteret NO912345678MVA`.
+pub fn parse_org_number(lines: &[Vec<String>]) -> Option<String> {
+    lines.iter().find_map(|line| {
+        let start = line.iter().position(|word| {
+            let word = word.to_lowercase();
+            word.starts_with("org") || word.starts_with("foretaksreg")
+        })?;
+        let mut digits = String::new();
+        for word in &line[start..] {
+            let word_digits: String = word.chars().filter(char::is_ascii_digit).collect();
+            if word_digits.is_empty() {
+                if !digits.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            digits.push_str(&word_digits);
+            if digits.len() >= 9 {
+                break;
+            }
+        }
+        (digits.len() == 9).then_some(digits)
+    })
+}
+
+/// Finds the time the receipt was printed, written as e.g. `12.03.2025 17:45` or
+/// `12.03.25 17:45:10`.
+pub fn parse_printed_datetime(lines: &[Vec<String>]) -> Option<NaiveDateTime> {
+    let words: Vec<&str> = lines.iter().flatten().map(String::as_str).collect();
+    words.windows(2).find_map(|pair| {
+        let date = ["%d.%m.%Y", "%d.%m.%y", "%d/%m/%Y", "%Y-%m-%d"]
+            .iter()
+            .find_map(|f| NaiveDate::parse_from_str(pair[0], f).ok())?;
+        let time = ["%H:%M:%S", "%H:%M"]
+            .iter()
+            .find_map(|f| NaiveTime::parse_from_str(pair[1], f).ok())?;
+        Some(date.and_time(time))
+    })
+}
+
+/// Turns OCR lines into a receipt with one item per line that ends in a price.
+/// Items read with a low confidence are flagged, see `ScannedItem`.
+pub fn parse_receipt(lines: Vec<OcrLine>) -> ScannedReceipt {
+    let words: Vec<Vec<String>> = lines.iter().map(|l| l.words.clone()).collect();
+    let store = detect_chain(&words).unwrap_or(UNKNOWN_STORE);
+    let org_number = parse_org_number(&words);
+    let datetime =
+        parse_printed_datetime(&words).unwrap_or_else(|| chrono::Local::now().naive_local());
+
+    let mut items = vec![];
+    for OcrLine {
+        words,
+        confidence,
+        bbox,
+    } in lines
+    {
+        if let Some(price) = words.last() {
+            // Only amounts with decimals are prices, plain numbers are usually quantities
+            if !price.contains([',', '.']) {
+                continue;
+            }
+            if let Ok(price) = price.parse::<Money>() {
+                let words = &words[..words.len() - 1];
+                // For kr per kg elements, ex: xxxkg x kr <price>
+                // x and kr is either split into two words or combined
+                // Inshallah no other items match this pattern
+                if (words.contains(&String::from("x")) && words.contains(&String::from("kr")))
+                    || words.contains(&String::from("xkr"))
+                {
+                    continue;
+                }
+                let name = words
+                    .iter()
+                    .filter(|word| !(word.ends_with('%') && word.len() <= 3))
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if name.contains("Totalt (") || name.contains("Sum ") {
+                    break;
+                }
+
+                if name.len() > 2 {
+                    let kind = ItemKind::from_name(&name);
+                    // Refunds are sometimes printed without a sign
+                    let price = if kind.is_refund() && !price.is_negative() {
+                        -price
+                    } else {
+                        price
+                    };
+                    items.push((name, price, kind, ScannedItem::new(confidence, bbox)));
+                }
+            }
+        }
+    }
+
+    let receipt = ReceiptForm {
+        store: store.to_owned(),
+        datetime,
+        anders_share: 1,
+        andreas_share: 1,
+        ac_share: 1,
+        split_mode: SplitMode::Equal,
+        image_hash: None,
+        store_id: None,
+    };
+
+    let (items, item_scans) = items
+        .into_iter()
+        .map(|(name, price, kind, scan)| {
+            let item = ReceiptItemForm {
+                receipt_id: -1, // This is a temporary hack as we dont have the id yet. It will be set in `create_receipt_with_items`
+                raw_name: Some(name.clone()),
+                name,
+                price,
+                anders_share: 1,
+                andreas_share: 1,
+                ac_share: 1,
+                kind,
+                category_id: None,
+                product_id: None,
+            };
+            (item, scan)
+        })
+        .unzip();
+
+    ScannedReceipt {
+        receipt,
+        items,
+        item_scans,
+        org_number,
+        new_store: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(words: &str) -> Vec<String> {
+        words.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_receipt() {
+        let lines: Vec<Vec<String>> = vec![
+            line("REMA 1000 Grünerløkka"),
+            line("2 stk"),
+            line("TORTILLA 15% 24,90"),
+            line("0,412kg x kr 199,00 81,99"),
+            line("KJØTTDEIG 81,99"),
+            line("Rabatt kjøttdeig 20,00-"),
+            line("PEPSI MAX 1,5L 32,90"),
+            line("+ Pant 3,00"),
+            line("Panteretur 6,00"),
+            line("Sum 2 varer 106,89"),
+            line("BÆREPOSE 2,50"),
+            line("Bax: 1234 12.03.2025 17:45"),
+            line("Org.nr: 912 345 678 MVA"),
+        ];
+        let scanned = parse_receipt(lines.into_iter().map(OcrLine::from).collect());
+        assert_eq!(scanned.receipt.store, "Rema 1000");
+        assert_eq!(scanned.org_number.as_deref(), Some("912345678"));
+        assert_eq!(
+            scanned.receipt.datetime,
+            NaiveDate::from_ymd_opt(2025, 3, 12)
+                .unwrap()
+                .and_hms_opt(17, 45, 0)
+                .unwrap()
+        );
+        let items: Vec<_> = scanned
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.price.ore(), i.kind))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("TORTILLA", 2490, ItemKind::Product),
+                ("KJØTTDEIG", 8199, ItemKind::Product),
+                ("Rabatt kjøttdeig", -2000, ItemKind::Discount),
+                ("PEPSI MAX 1,5L", 3290, ItemKind::Product),
+                ("+ Pant", 300, ItemKind::Deposit),
+                ("Panteretur", -600, ItemKind::DepositReturn),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flag_low_confidence() {
+        let scanned = parse_receipt(vec![
+            OcrLine {
+                words: line("TORTILLA 24,90"),
+                confidence: Some(0.98),
+                bbox: Some([10, 40, 300, 60]),
+            },
+            OcrLine {
+                words: line("KJ0TTDE1G 81,99"),
+                confidence: Some(0.61),
+                bbox: Some([10, 70, 300, 90]),
+            },
+            line("BÆREPOSE 2,50").into(),
+        ]);
+        let scans: Vec<_> = scanned
+            .item_scans
+            .iter()
+            .map(|s| (s.flagged, s.bbox))
+            .collect();
+        assert_eq!(
+            scans,
+            vec![
+                (false, Some([10, 40, 300, 60])),
+                (true, Some([10, 70, 300, 90])),
+                (false, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_chain() {
+        assert_eq!(
+            detect_chain(&[line("Du sparte 20,00"), line("COOP EXTRA Storo")]),
+            Some("Coop Extra")
+        );
+        assert_eq!(detect_chain(&[line("Kvittering")]), None);
+        assert_eq!(
+            parse_org_number(&[line("Foretaksregisteret NO912345678MVA")]),
+            Some(String::from("912345678"))
+        );
+        assert_eq!(parse_org_number(&[line("Org nr 12 34")]), None);
+    }
+
+    #[test]
+    fn test_item_kind_from_name() {
+        assert_eq!(ItemKind::from_name("PANT"), ItemKind::Deposit);
+        assert_eq!(ItemKind::from_name("+ Pant 2,00"), ItemKind::Deposit);
+        assert_eq!(ItemKind::from_name("pant3,00"), ItemKind::Deposit);
+        assert_eq!(ItemKind::from_name("Panteretur"), ItemKind::DepositReturn);
+        assert_eq!(ItemKind::from_name("Pantene shampoo"), ItemKind::Product);
+        assert_eq!(ItemKind::from_name("Pantry pasta"), ItemKind::Product);
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, GrayImage, ImageDecoder, ImageReader};
+use leptos::logging::error;
+use tokio::process::Command;
+
+use crate::models::ocr_job::PreprocessStage;
+use crate::ocr::crop::png_data_url;
+use crate::ocr::preprocess::{preprocess, Stage};
+
+/// Photos are scaled down so their longest side is at most this many pixels, which
+/// is still plenty for OCR.
+pub const MAX_SIDE: u32 = 2000;
+
+/// Largest upload accepted, from `MAX_UPLOAD_MB`.
+pub fn max_upload_bytes() -> usize {
+    let mb: usize = std::env::var("MAX_UPLOAD_MB")
+        .ok()
+        .and_then(|mb| mb.parse().ok())
+        .filter(|mb| *mb > 0)
+        .unwrap_or(20);
+    mb * 1024 * 1024
+}
+
+/// Gets an uploaded file ready for scanning and returns where it ended up. Photos,
+/// including HEIC from iPhones, are rewritten as upright, preprocessed PNGs, while
+/// PDFs and digital receipts are kept as they are. The upload is removed on errors.
+/// With `debug` the photo after every preprocessing stage is returned too.
+pub async fn prepare_upload(
+    path: PathBuf,
+    extension: &str,
+    debug: bool,
+) -> Result<(PathBuf, Vec<PreprocessStage>), String> {
+    let result = match extension {
+        "pdf" | "json" => return Ok((path, vec![])),
+        "heic" => convert_heic(&path).await,
+        _ => Ok(path.clone()),
+    };
+    let result = match result {
+        Ok(image_path) => {
+            let normalized = tokio::task::spawn_blocking({
+                let image_path = image_path.clone();
+                move || normalize_image(&image_path, debug)
+            })
+            .await
+            .unwrap_or_else(|e| Err(format!("Could not normalize image: {e}")));
+            if image_path != path {
+                let _ = tokio::fs::remove_file(&image_path).await;
+            }
+            normalized
+        }
+        Err(e) => Err(e),
+    };
+    if !matches!(&result, Ok((p, _)) if *p == path) {
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+    result
+}
+
+/// Whether `heif-convert` from libheif is installed, checked once. The Docker image
+/// has it, other machines may not.
+pub fn heic_supported() -> bool {
+    static SUPPORTED: once_cell::sync::Lazy<bool> = once_cell::sync::Lazy::new(|| {
+        std::process::Command::new("heif-convert")
+            .arg("--version")
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok()
+    });
+    *SUPPORTED
+}
+
+/// Decodes HEIC/HEIF with `heif-convert` from libheif, as PNG next to the original.
+async fn convert_heic(path: &Path) -> Result<PathBuf, String> {
+    if !heic_supported() {
+        return Err(String::from(
+            "HEIC images are not supported on this server, upload a JPEG or PNG instead",
+        ));
+    }
+    let png_path = path.with_extension("heic.png");
+    let output = Command::new("heif-convert")
+        .arg(path)
+        .arg(&png_path)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| {
+            error!("heif-convert failed: {e}");
+            String::from("HEIC images are not supported on this server")
+        })?;
+    if !output.status.success() {
+        error!(
+            "heif-convert failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return Err(String::from("Could not read HEIC image"));
+    }
+    Ok(png_path)
+}
+
+/// Where to save the image after every preprocessing stage, from `OCR_DEBUG_DIR`.
+fn debug_dir() -> Option<PathBuf> {
+    std::env::var_os("OCR_DEBUG_DIR").map(PathBuf::from)
+}
+
+/// Decodes the photo, turns it upright from its EXIF orientation, scales it down and
+/// preprocesses it for OCR, then saves it as PNG with a new name. Returns the path
+/// of the PNG, and with `debug` the image after every stage.
+pub fn normalize_image(
+    path: &Path,
+    debug: bool,
+) -> Result<(PathBuf, Vec<PreprocessStage>), String> {
+    let unreadable = |e: image::ImageError| {
+        error!("Could not decode image {}: {e}", path.display());
+        String::from("Could not read the image, is it a photo of a receipt?")
+    };
+    let mut decoder = ImageReader::open(path)
+        .map_err(|e| format!("Could not open image: {e}"))?
+        .with_guessed_format()
+        .map_err(|e| format!("Could not open image: {e}"))?
+        .into_decoder()
+        .map_err(unreadable)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let image = DynamicImage::from_decoder(decoder).map_err(unreadable)?;
+
+    let debug_dir = debug_dir();
+    let preprocessed = preprocess(&normalize(image, orientation), debug || debug_dir.is_some());
+    if let Some(debug_dir) = debug_dir {
+        save_stages(&debug_dir, path, &preprocessed.stages);
+    }
+    let stages = if debug {
+        stage_images(&preprocessed.stages)
+    } else {
+        vec![]
+    };
+
+    let png_path = path.with_extension("normalized.png");
+    preprocessed
+        .image
+        .save(&png_path)
+        .map_err(|e| format!("Could not save normalized image: {e}"))?;
+    Ok((png_path, stages))
+}
+
+fn stage_images(stages: &[(Stage, GrayImage)]) -> Vec<PreprocessStage> {
+    stages
+        .iter()
+        .filter_map(|(stage, image)| {
+            Some(PreprocessStage {
+                name: stage.name().to_owned(),
+                image: png_data_url(&DynamicImage::ImageLuma8(image.clone()))?,
+            })
+        })
+        .collect()
+}
+
+/// Saves the intermediate images as `<upload>-<n>-<stage>.png`.
+fn save_stages(debug_dir: &Path, path: &Path, stages: &[(Stage, GrayImage)]) {
+    let upload = path.file_stem().unwrap_or_default().to_string_lossy();
+    for (i, (stage, image)) in stages.iter().enumerate() {
+        let stage_path = debug_dir.join(format!("{upload}-{}-{}.png", i + 1, stage.name()));
+        if let Err(e) = image.save(&stage_path) {
+            error!("Could not save {}: {e}", stage_path.display());
+        }
+    }
+}
+
+/// Rotates the image upright and makes sure no side is longer than `MAX_SIDE`.
+pub fn normalize(mut image: DynamicImage, orientation: Orientation) -> DynamicImage {
+    image.apply_orientation(orientation);
+    if image.width() > MAX_SIDE || image.height() > MAX_SIDE {
+        image = image.resize(MAX_SIDE, MAX_SIDE, FilterType::Triangle);
+    }
+    image
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    #[test]
+    fn test_normalize() {
+        let photo = DynamicImage::ImageLuma8(GrayImage::from_pixel(4000, 1000, Luma([255])));
+        let upright = normalize(photo, Orientation::Rotate90);
+        assert_eq!((upright.width(), upright.height()), (500, 2000));
+
+        let small = DynamicImage::ImageLuma8(GrayImage::from_pixel(300, 400, Luma([255])));
+        let kept = normalize(small, Orientation::NoTransforms);
+        assert_eq!((kept.width(), kept.height()), (300, 400));
+    }
+
+    #[test]
+    fn test_debug_stages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("receipt.png");
+        GrayImage::from_pixel(300, 400, Luma([255]))
+            .save(&path)
+            .unwrap();
+        let (png_path, stages) = normalize_image(&path, true).unwrap();
+        assert!(png_path.exists());
+        let names: Vec<&str> = stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, Stage::ALL.map(|s| s.name()));
+        assert!(stages[0].image.starts_with("data:image/png;base64,"));
+        assert!(normalize_image(&path, false).unwrap().1.is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+use leptos::logging::error;
+use tokio::process::Command;
+
+/// Reads the text layer of a PDF receipt with `pdftotext`, no OCR involved.
+/// Returns the words of each line, top to bottom, like `ocr_image`.
+pub async fn pdf_text(pdf_path: &str) -> Result<Vec<Vec<String>>, String> {
+    let output = Command::new("pdftotext")
+        .arg("-layout")
+        .arg(pdf_path)
+        .arg("-")
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| {
+            error!("pdftotext failed: {e}");
+            String::from("Reading PDF failed")
+        })?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    let lines = text_lines(&String::from_utf8_lossy(&output.stdout));
+    if lines.is_empty() {
+        return Err(String::from(
+            "The PDF has no text, upload a photo of the receipt instead",
+        ));
+    }
+    Ok(lines)
+}
+
+/// Splits text into the words of each non-empty line.
+pub fn text_lines(text: &str) -> Vec<Vec<String>> {
+    text.lines()
+        .map(|line| {
+            line.split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        })
+        .filter(|words| !words.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_text_lines() {
+        let lines = text_lines("REMA 1000\n\n  TORTILLA       24,90\n\u{c}KJØTTDEIG   81,99 \n");
+        assert_eq!(
+            lines,
+            vec![
+                vec!["REMA", "1000"],
+                vec!["TORTILLA", "24,90"],
+                vec!["KJØTTDEIG", "81,99"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use image::{DynamicImage, GrayImage, Luma};
+
+/// The steps a photo goes through before OCR, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Grayscale,
+    Contrast,
+    Crop,
+    Deskew,
+    Binarize,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [
+        Stage::Grayscale,
+        Stage::Contrast,
+        Stage::Crop,
+        Stage::Deskew,
+        Stage::Binarize,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Grayscale => "grayscale",
+            Stage::Contrast => "contrast",
+            Stage::Crop => "crop",
+            Stage::Deskew => "deskew",
+            Stage::Binarize => "binarize",
+        }
+    }
+}
+
+/// The preprocessed image, and in debug mode the image after every stage.
+pub struct Preprocessed {
+    pub image: GrayImage,
+    pub stages: Vec<(Stage, GrayImage)>,
+}
+
+/// Runs every stage on a receipt photo, see `Stage`.
+pub fn preprocess(image: &DynamicImage, debug: bool) -> Preprocessed {
+    let mut stages = vec![];
+    let mut image = grayscale(image);
+    for stage in Stage::ALL {
+        image = match stage {
+            Stage::Grayscale => image,
+            Stage::Contrast => normalize_contrast(&image),
+            Stage::Crop => crop_to_receipt(&image),
+            Stage::Deskew => deskew(&image),
+            Stage::Binarize => binarize(&image),
+        };
+        if debug {
+            stages.push((stage, image.clone()));
+        }
+    }
+    Preprocessed { image, stages }
+}
+
+pub fn grayscale(image: &DynamicImage) -> GrayImage {
+    image.to_luma8()
+}
+
+/// Stretches the values so the darkest and brightest percent become black and
+/// white, which makes faded thermal print darker.
+pub fn normalize_contrast(image: &GrayImage) -> GrayImage {
+    let histogram = histogram(image);
+    let (low, high) = (percentile(&histogram, 0.01), percentile(&histogram, 0.99));
+    if high <= low {
+        return image.clone();
+    }
+    let range = (high - low) as f32;
+    let mut stretched = image.clone();
+    for Luma([value]) in stretched.pixels_mut() {
+        let scaled = (*value as f32 - low as f32) / range * 255.0;
+        *value = scaled.clamp(0.0, 255.0).round() as u8;
+    }
+    stretched
+}
+
+/// Crops away the table around the receipt. The receipt is the bright paper, so
+/// this keeps the columns where at least a quarter is bright, and within those the
+/// rows where at least a quarter is bright. Text can cover much of a row, while the
+/// table around the receipt has no bright paper at all. Leaves the image alone
+/// when no clear receipt is found.
+pub fn crop_to_receipt(image: &GrayImage) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let threshold = otsu_threshold(image);
+    let bright = |x: u32, y: u32| image.get_pixel(x, y)[0] > threshold;
+
+    let columns: Vec<bool> = (0..width)
+        .map(|x| (0..height).filter(|y| bright(x, *y)).count() * 4 > height as usize)
+        .collect();
+    let Some((left, right)) = longest_run(&columns) else {
+        return image.clone();
+    };
+    let column_count = right - left;
+    let rows: Vec<bool> = (0..height)
+        .map(|y| (left..right).filter(|x| bright(*x, y)).count() * 4 > column_count as usize)
+        .collect();
+    let Some((top, bottom)) = longest_run(&rows) else {
+        return image.clone();
+    };
+
+    let (crop_width, crop_height) = (right - left, bottom - top);
+    let area = (crop_width as u64 * crop_height as u64) as f32;
+    let full_area = (width as u64 * height as u64) as f32;
+    if area < full_area * 0.2 || area > full_area * 0.95 {
+        return image.clone();
+    }
+    image::imageops::crop_imm(image, left, top, crop_width, crop_height).to_image()
+}
+
+/// Largest angle in degrees a receipt is expected to be skewed by.
+const MAX_SKEW: f32 = 10.0;
+
+/// Straightens the text lines, see `skew_angle`.
+pub fn deskew(image: &GrayImage) -> GrayImage {
+    let angle = skew_angle(image);
+    if angle.abs() < 0.25 {
+        return image.clone();
+    }
+    rotate(image, angle)
+}
+
+/// The rotation in degrees that makes the text lines horizontal. Tries every angle
+/// up to `MAX_SKEW` and keeps the one where the dark pixels fall into the fewest,
+/// fullest rows.
+pub fn skew_angle(image: &GrayImage) -> f32 {
+    // A smaller copy is plenty to find the angle
+    let scale = (400.0 / image.width().max(1) as f32).min(1.0);
+    let small = image::imageops::resize(
+        image,
+        ((image.width() as f32 * scale) as u32).max(1),
+        ((image.height() as f32 * scale) as u32).max(1),
+        image::imageops::FilterType::Triangle,
+    );
+    let threshold = otsu_threshold(&small);
+    let (cx, cy) = (small.width() as f32 / 2.0, small.height() as f32 / 2.0);
+    let ink: Vec<(f32, f32)> = small
+        .enumerate_pixels()
+        .filter(|(_, _, p)| p[0] <= threshold)
+        .map(|(x, y, _)| (x as f32 - cx, y as f32 - cy))
+        .collect();
+    // An almost blank or almost black image has no lines to go by
+    if ink.is_empty() || ink.len() * 2 > (small.width() * small.height()) as usize {
+        return 0.0;
+    }
+
+    let rows = (small.width() + small.height()) as usize * 2;
+    let score = |angle: f32| {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let mut profile = vec![0u64; rows];
+        for (dx, dy) in &ink {
+            let row = (sin * dx + cos * dy + rows as f32 / 2.0) as usize;
+            profile[row.min(rows - 1)] += 1;
+        }
+        profile.iter().map(|count| count * count).sum::<u64>()
+    };
+    let steps = (MAX_SKEW * 4.0) as i32;
+    (-steps..=steps)
+        .map(|step| step as f32 / 4.0)
+        .max_by_key(|angle| (score(*angle), -(angle.abs() * 4.0) as i64))
+        .unwrap_or(0.0)
+}
+
+/// Rotates the image around its center, filling the corners with white.
+pub fn rotate(image: &GrayImage, degrees: f32) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    GrayImage::from_fn(width, height, |x, y| {
+        let (ox, oy) = (x as f32 - cx, y as f32 - cy);
+        let sx = cx + cos * ox + sin * oy;
+        let sy = cy - sin * ox + cos * oy;
+        Luma([sample(image, sx, sy)])
+    })
+}
+
+/// Bilinear sample, white outside the image.
+fn sample(image: &GrayImage, x: f32, y: f32) -> u8 {
+    let (width, height) = image.dimensions();
+    if x < 0.0 || y < 0.0 || x > (width - 1) as f32 || y > (height - 1) as f32 {
+        return 255;
+    }
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let p = |x, y| image.get_pixel(x, y)[0] as f32;
+    let top = p(x0, y0) * (1.0 - fx) + p(x1, y0) * fx;
+    let bottom = p(x0, y1) * (1.0 - fx) + p(x1, y1) * fx;
+    (top * (1.0 - fy) + bottom * fy).round() as u8
+}
+
+/// Black text on white, with a threshold that follows the local brightness so
+/// shadows and creases don't turn into black blobs. Uses the mean of the area
+/// around every pixel (Bradley's method).
+pub fn binarize(image: &GrayImage) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let (w, h) = (width as usize, height as usize);
+    let mut integral = vec![0u64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = 0u64;
+        for x in 0..w {
+            row_sum += image.get_pixel(x as u32, y as u32)[0] as u64;
+            integral[(y + 1) * (w + 1) + x + 1] = integral[y * (w + 1) + x + 1] + row_sum;
+        }
+    }
+
+    let half = (w.max(h) / 32).max(7);
+    GrayImage::from_fn(width, height, |x, y| {
+        let (x, y) = (x as usize, y as usize);
+        let (x0, x1) = (x.saturating_sub(half), (x + half + 1).min(w));
+        let (y0, y1) = (y.saturating_sub(half), (y + half + 1).min(h));
+        let sum = integral[y1 * (w + 1) + x1] + integral[y0 * (w + 1) + x0]
+            - integral[y0 * (w + 1) + x1]
+            - integral[y1 * (w + 1) + x0];
+        let count = ((x1 - x0) * (y1 - y0)) as u64;
+        let value = image.get_pixel(x as u32, y as u32)[0] as u64;
+        // Darker than 85% of the local mean is ink
+        match value * count * 100 < sum * 85 {
+            true => Luma([0]),
+            false => Luma([255]),
+        }
+    })
+}
+
+fn histogram(image: &GrayImage) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    for Luma([value]) in image.pixels() {
+        histogram[*value as usize] += 1;
+    }
+    histogram
+}
+
+fn percentile(histogram: &[u64; 256], fraction: f32) -> u8 {
+    let total: u64 = histogram.iter().sum();
+    let target = (total as f32 * fraction) as u64;
+    let mut seen = 0;
+    for (value, count) in histogram.iter().enumerate() {
+        seen += count;
+        if seen > target {
+            return value as u8;
+        }
+    }
+    255
+}
+
+/// The threshold that best splits the image into dark and bright pixels.
+fn otsu_threshold(image: &GrayImage) -> u8 {
+    let histogram = histogram(image);
+    let total: u64 = histogram.iter().sum();
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, count)| value as f64 * *count as f64)
+        .sum();
+
+    let (mut dark_count, mut dark_sum) = (0u64, 0f64);
+    let (mut best, mut best_variance) = (0u8, 0f64);
+    for (value, count) in histogram.iter().enumerate() {
+        dark_count += count;
+        dark_sum += value as f64 * *count as f64;
+        let bright_count = total - dark_count;
+        if dark_count == 0 || bright_count == 0 {
+            continue;
+        }
+        let dark_mean = dark_sum / dark_count as f64;
+        let bright_mean = (sum - dark_sum) / bright_count as f64;
+        let variance = dark_count as f64 * bright_count as f64 * (dark_mean - bright_mean).powi(2);
+        if variance > best_variance {
+            (best, best_variance) = (value as u8, variance);
+        }
+    }
+    best
+}
+
+/// Start and end (exclusive) of the longest stretch of `true`.
+fn longest_run(values: &[bool]) -> Option<(u32, u32)> {
+    let mut best: Option<(u32, u32)> = None;
+    let mut start = None;
+    for (i, value) in values.iter().chain([&false]).enumerate() {
+        match (value, start) {
+            (true, None) => start = Some(i as u32),
+            (false, Some(s)) => {
+                if best.is_none_or(|(bs, be)| i as u32 - s > be - bs) {
+                    best = Some((s, i as u32));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A receipt with lines of "text" lying on a gray table.
+    fn receipt_fixture() -> GrayImage {
+        let mut image = GrayImage::from_pixel(400, 300, Luma([80]));
+        for y in 20..280 {
+            for x in 100..250 {
+                let text = (y - 20) % 20 < 6 && (110..240).contains(&x) && (x / 7) % 3 != 0;
+                image.put_pixel(x, y, Luma([if text { 20 } else { 235 }]));
+            }
+        }
+        image
+    }
+
+    /// Just the paper with the text lines.
+    fn text_fixture() -> GrayImage {
+        let receipt = receipt_fixture();
+        image::imageops::crop_imm(&receipt, 100, 20, 150, 260).to_image()
+    }
+
+    #[test]
+    fn test_grayscale() {
+        let color =
+            DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0])));
+        let gray = grayscale(&color);
+        assert_eq!(gray.dimensions(), (2, 2));
+        assert!(gray.pixels().all(|p| p[0] > 0 && p[0] < 255));
+    }
+
+    #[test]
+    fn test_normalize_contrast() {
+        let faded = GrayImage::from_fn(100, 100, |x, _| Luma([100 + (x / 2) as u8]));
+        let stretched = normalize_contrast(&faded);
+        let histogram = histogram(&stretched);
+        assert!(histogram[..5].iter().sum::<u64>() > 0);
+        assert!(histogram[250..].iter().sum::<u64>() > 0);
+    }
+
+    #[test]
+    fn test_crop_to_receipt() {
+        let cropped = crop_to_receipt(&receipt_fixture());
+        let (width, height) = cropped.dimensions();
+        assert!((145..=155).contains(&width), "width {width}");
+        assert!((250..=265).contains(&height), "height {height}");
+
+        // Nothing to crop on a plain page
+        assert_eq!(crop_to_receipt(&text_fixture()).dimensions(), (150, 260));
+    }
+
+    #[test]
+    fn test_deskew() {
+        let text = text_fixture();
+        assert_eq!(skew_angle(&text), 0.0);
+
+        let skewed = rotate(&text, 4.0);
+        let angle = skew_angle(&skewed);
+        assert!((angle + 4.0).abs() <= 0.5, "angle {angle}");
+        assert!(skew_angle(&deskew(&skewed)).abs() <= 0.5);
+    }
+
+    #[test]
+    fn test_binarize() {
+        // Text on paper that goes from shadow to bright
+        let shaded = GrayImage::from_fn(200, 100, |x, y| {
+            let paper = 120 + (x / 2) as u8;
+            match y % 20 < 4 && x % 10 < 6 {
+                true => Luma([paper - 80]),
+                false => Luma([paper]),
+            }
+        });
+        let binary = binarize(&shaded);
+        assert!(binary.pixels().all(|p| p[0] == 0 || p[0] == 255));
+        assert_eq!(binary.get_pixel(2, 1)[0], 0);
+        assert_eq!(binary.get_pixel(195, 1)[0], 0);
+        assert_eq!(binary.get_pixel(2, 10)[0], 255);
+        assert_eq!(binary.get_pixel(195, 10)[0], 255);
+    }
+
+    #[test]
+    fn test_preprocess_debug() {
+        let photo = DynamicImage::ImageLuma8(receipt_fixture());
+        let preprocessed = preprocess(&photo, true);
+        let stages: Vec<Stage> = preprocessed.stages.iter().map(|(s, _)| *s).collect();
+        assert_eq!(stages, Stage::ALL);
+        assert_eq!(
+            preprocessed.stages.last().map(|(_, i)| i.dimensions()),
+            Some(preprocessed.image.dimensions())
+        );
+        assert!(preprocess(&photo, false).stages.is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use leptos::logging::{error, log};
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::api::ssr::DbConn;
+use crate::db::Db;
+use crate::models::ocr_job::{OcrJob, ScannedReceipt};
+use crate::models::product::apply_product_names;
+use crate::models::store::{DetectedStore, Store};
+use crate::ocr::{crop::add_item_images, image_hash::hash_file, scan_file, UNKNOWN_STORE};
+use crate::ws::{server::BroadcastTx, WsMessage};
+
+/// How many OCR processes may run at once, from `OCR_CONCURRENCY`.
+fn concurrency() -> usize {
+    std::env::var("OCR_CONCURRENCY")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|c| *c > 0)
+        .unwrap_or(1)
+}
+
+/// How long a single OCR run may take before it is killed, from `OCR_TIMEOUT_SECS`.
+fn timeout() -> Duration {
+    let secs = std::env::var("OCR_TIMEOUT_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(120);
+    Duration::from_secs(secs)
+}
+
+/// Handle for queueing OCR jobs. Jobs are stored in `ocr_jobs` before they are
+/// pushed here, so the queue itself only carries ids.
+#[derive(Clone)]
+pub struct OcrQueue {
+    tx: mpsc::UnboundedSender<i32>,
+}
+
+impl OcrQueue {
+    /// Starts the worker and queues any jobs left over from before a restart.
+    pub fn start(pool: Db, broadcast_tx: BroadcastTx) -> OcrQueue {
+        let (tx, mut rx) = mpsc::unbounded_channel::<i32>();
+        let limit = Arc::new(Semaphore::new(concurrency()));
+        let timeout = timeout();
+
+        {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                while let Some(job_id) = rx.recv().await {
+                    let permit = limit
+                        .clone()
+                        .acquire_owned()
+                        .await
+                        .expect("OCR semaphore is never closed");
+                    let pool = pool.clone();
+                    let broadcast_tx = broadcast_tx.clone();
+                    tokio::spawn(async move {
+                        run_job(&pool, &broadcast_tx, job_id, timeout).await;
+                        drop(permit);
+                    });
+                }
+            });
+        }
+
+        let queue = OcrQueue { tx };
+        match pool.get().map(|mut db| OcrJob::requeue_unfinished(&mut db)) {
+            Ok(Ok(job_ids)) => job_ids.into_iter().for_each(|id| queue.push(id)),
+            Ok(Err(e)) => error!("Could not requeue OCR jobs: {e}"),
+            Err(e) => error!("Could not get DB connection to requeue OCR jobs: {e}"),
+        }
+        queue
+    }
+
+    pub fn push(&self, job_id: i32) {
+        if self.tx.send(job_id).is_err() {
+            error!("OCR worker has stopped, job {job_id} stays queued");
+        }
+    }
+}
+
+async fn run_job(pool: &Db, broadcast_tx: &BroadcastTx, job_id: i32, timeout: Duration) {
+    // Connections are only taken for the status changes, scanning can take minutes
+    let started = pool
+        .get()
+        .map_err(|e| e.to_string())
+        .and_then(|mut db| OcrJob::start(&mut db, job_id).map_err(|e| e.to_string()));
+    let job = match started {
+        Ok(job) => job,
+        Err(e) => {
+            error!("Could not start OCR job {job_id}: {e}");
+            return;
+        }
+    };
+    send_update(broadcast_tx, &job);
+    log!("Running OCR job {job_id}");
+
+    let mut result: Result<ScannedReceipt, String> =
+        match tokio::time::timeout(timeout, scan_file(&job.file_path)).await {
+            Ok(scanned) => scanned,
+            Err(_) => Err(format!(
+                "Scanning took longer than {} seconds",
+                timeout.as_secs()
+            )),
+        };
+    if let Ok(scanned) = &mut result {
+        let path = job.file_path.clone();
+        scanned.receipt.image_hash = tokio::task::spawn_blocking(move || hash_file(&path))
+            .await
+            .ok()
+            .flatten();
+        let path = job.file_path.clone();
+        let mut item_scans = std::mem::take(&mut scanned.item_scans);
+        scanned.item_scans = tokio::task::spawn_blocking(move || {
+            add_item_images(&path, &mut item_scans);
+            item_scans
+        })
+        .await
+        .unwrap_or_default();
+    }
+    let _ = std::fs::remove_file(&job.file_path);
+
+    let saved = pool.get().map_err(|e| e.to_string()).and_then(|mut db| {
+        let job = match result {
+            Ok(mut scanned) => {
+                detect_store(&mut db, &mut scanned);
+                if let Err(e) = apply_product_names(&mut db, &mut scanned.items) {
+                    error!("Could not apply product names: {e}");
+                }
+                OcrJob::finish(&mut db, job_id, &scanned)
+            }
+            Err(e) => OcrJob::fail(&mut db, job_id, e),
+        };
+        job.map_err(|e| e.to_string())
+    });
+    match saved {
+        Ok(job) => send_update(broadcast_tx, &job),
+        Err(e) => error!("Could not save result of OCR job {job_id}: {e}"),
+    }
+}
+
+/// Links the receipt to a known store and uses its name, leaving it unlinked if
+/// the store can't be told apart. A new store is only proposed, it is added when
+/// the receipt is saved.
+fn detect_store(db: &mut DbConn, scanned: &mut ScannedReceipt) {
+    let chain = Some(scanned.receipt.store.as_str()).filter(|s| *s != UNKNOWN_STORE);
+    match Store::detect(db, chain, scanned.org_number.as_deref()) {
+        Ok(Some(DetectedStore::Known(store))) => {
+            scanned.receipt.store = store.display_name();
+            scanned.receipt.store_id = Some(store.id);
+        }
+        Ok(Some(DetectedStore::New(store))) => scanned.new_store = Some(store),
+        Ok(None) => {}
+        Err(e) => error!("Could not detect store: {e}"),
+    }
+}
+
+fn send_update(broadcast_tx: &BroadcastTx, job: &OcrJob) {
+    // Every client gets this, so the uploader fetches the result with `get_ocr_job`.
+    // Non-fatal if nobody is connected.
+    let _ = broadcast_tx.send(WsMessage::OcrJobUpdate(Box::new(job.to_status_update())));
+}
@@ -0,0 +1,277 @@
+use crate::api::category::{
+    delete_category_rule, get_categories, get_category_report, get_category_rules,
+    insert_category_rule,
+};
+use crate::app::RouteUrl;
+use crate::components::error_list;
+use crate::models::category::{Category, CategoryRule, CategoryRuleForm, CategorySpending};
+use crate::models::member::Member;
+use crate::models::money::Money;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+fn category_name(categories: &[Category], category_id: Option<i32>) -> String {
+    category_id
+        .and_then(|id| categories.iter().find(|c| c.id == id))
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| String::from("Uncategorized"))
+}
+
+#[component]
+fn MonthReport(rows: Vec<CategorySpending>, categories: Vec<Category>) -> impl IntoView {
+    let (year, month) = (rows[0].year, rows[0].month);
+    let total: Money = rows.iter().map(|r| r.total).sum();
+    let columns = "grid-template-columns: 1fr 80px 60px 60px 60px;";
+
+    view! {
+        <div class="w-full max-w-lg mx-auto rounded-xl border border-gray-200 bg-white p-4 shadow-md">
+            <div class="flex justify-between items-center mb-2">
+                <h3 class="text-lg font-semibold text-gray-900">
+                    {format!("{} {year}", MONTHS[month as usize - 1])}
+                </h3>
+                <span class="font-semibold text-gray-700">{total.to_string()}</span>
+            </div>
+            <div class="grid gap-1 text-xs font-semibold text-gray-600 mb-1" style=columns>
+                <div>"Category"</div>
+                <div class="text-right">"Total"</div>
+                {Member::ALL
+                    .map(|member| view! { <div class="text-right">{member.name()}</div> })
+                    .to_vec()}
+            </div>
+            {rows
+                .into_iter()
+                .map(|row| {
+                    view! {
+                        <div
+                            class="grid gap-1 items-center border-b border-gray-200 py-1 text-sm text-gray-900"
+                            style=columns
+                        >
+                            <span class="truncate">
+                                {category_name(&categories, row.category_id)}
+                            </span>
+                            <span class="text-right">{row.total.format_amount()}</span>
+                            {row
+                                .member_totals
+                                .map(|amount| {
+                                    view! {
+                                        <span class="text-right text-gray-700">
+                                            {amount.format_amount()}
+                                        </span>
+                                    }
+                                })
+                                .to_vec()}
+                        </div>
+                    }
+                })
+                .collect::<Vec<_>>()}
+        </div>
+    }
+}
+
+#[component]
+fn CategoryRules(categories: Vec<Category>) -> impl IntoView {
+    let rules_resource = OnceResource::new(get_category_rules());
+    let rules: RwSignal<Vec<CategoryRule>> = RwSignal::new(Vec::new());
+    Effect::watch(
+        move || rules_resource.get(),
+        move |r_rules, _, _| {
+            if let Some(Ok(r_rules)) = r_rules {
+                rules.set(r_rules.clone());
+            }
+        },
+        true,
+    );
+
+    let insert_rule_action = Action::new(|rule_form: &CategoryRuleForm| {
+        let rule_form = rule_form.clone();
+        async move { insert_category_rule(rule_form).await }
+    });
+    Effect::watch(
+        move || insert_rule_action.value().get(),
+        move |rule, _, _| {
+            if let Some(Ok(rule)) = rule {
+                rules.update(|rules| {
+                    rules.push(rule.clone());
+                    rules.sort_by(|a, b| a.keyword.cmp(&b.keyword));
+                });
+            }
+        },
+        false,
+    );
+    let delete_rule_action = Action::new(|id: &i32| {
+        let id = *id;
+        async move { delete_category_rule(id).await }
+    });
+
+    let keyword = RwSignal::new(String::new());
+    let category_id = RwSignal::new(categories.first().map(|c| c.id));
+    let form_submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        if let Some(category_id) = category_id.get() {
+            insert_rule_action.dispatch(CategoryRuleForm {
+                keyword: keyword.get(),
+                category_id,
+                manual: false,
+            });
+            keyword.set(String::new());
+        }
+    };
+
+    let input_class = "px-2 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500";
+    let categories = StoredValue::new(categories);
+    view! {
+        <div class="w-full max-w-lg mx-auto rounded-xl border border-gray-200 bg-white p-4 shadow-md">
+            <h3 class="text-lg font-semibold text-gray-900 mb-1">"Rules"</h3>
+            <p class="text-xs text-gray-500 mb-2">
+                "Items with a word starting or ending with the keyword get its category. Categories picked by hand on a receipt are remembered here."
+            </p>
+            <form on:submit=form_submit class="flex gap-2 mb-2">
+                <input
+                    type="text"
+                    placeholder="Keyword"
+                    bind:value=keyword
+                    class=format!("{input_class} flex-1 min-w-0")
+                    required
+                />
+                <select
+                    class=input_class
+                    on:change:target=move |ev| {
+                        category_id.set(ev.target().value().parse().ok())
+                    }
+                >
+                    {categories
+                        .get_value()
+                        .into_iter()
+                        .map(|c| view! { <option value=c.id>{c.name}</option> })
+                        .collect::<Vec<_>>()}
+                </select>
+                <button type="submit" class="px-3 py-1 rounded bg-blue-500 text-white text-sm">
+                    "Add"
+                </button>
+            </form>
+            {move || {
+                insert_rule_action
+                    .value()
+                    .get()
+                    .and_then(Result::err)
+                    .map(|e| view! { <p class="text-sm text-red-600">{e.to_string()}</p> })
+            }}
+            <div class="flex flex-wrap gap-2">
+                {move || {
+                    rules
+                        .get()
+                        .into_iter()
+                        .map(|rule| {
+                            let id = rule.id;
+                            let category = categories
+                                .with_value(|c| category_name(c, Some(rule.category_id)));
+                            view! {
+                                <span class=if rule.manual {
+                                    "px-2 py-1 text-xs rounded-full border border-blue-300 bg-blue-50 text-blue-900"
+                                } else {
+                                    "px-2 py-1 text-xs rounded-full border border-gray-300 bg-gray-100 text-gray-700"
+                                }>
+                                    {format!("{} → {category} ", rule.keyword)}
+                                    <button
+                                        type="button"
+                                        class="text-gray-500 hover:text-red-600"
+                                        title="Remove rule"
+                                        on:click=move |_| {
+                                            delete_rule_action.dispatch(id);
+                                            rules.update(|rules| rules.retain(|r| r.id != id));
+                                        }
+                                    >
+                                        "×"
+                                    </button>
+                                </span>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+pub fn CategoryReportRoute() -> impl IntoView {
+    let categories_resource = OnceResource::new(get_categories());
+    let report_resource = OnceResource::new(get_category_report());
+
+    let report_data = move || {
+        let categories = categories_resource.get()?;
+        let report = report_resource.get()?;
+        Some(categories.and_then(|categories| {
+            report.map(|report| {
+                let mut months: Vec<Vec<CategorySpending>> = vec![];
+                for row in report {
+                    match months.last_mut() {
+                        Some(month) if (month[0].year, month[0].month) == (row.year, row.month) => {
+                            month.push(row)
+                        }
+                        _ => months.push(vec![row]),
+                    }
+                }
+                view! {
+                    <CategoryRules categories=categories.clone() />
+                    {months
+                        .into_iter()
+                        .map(|rows| view! { <MonthReport rows categories=categories.clone() /> })
+                        .collect::<Vec<_>>()}
+                }
+            })
+        }))
+    };
+
+    view! {
+        <A href=RouteUrl::ReceiptList.to_string()>
+            <button
+                type="button"
+                class="fixed bottom-4 right-4 z-50 px-4 py-3 rounded-full bg-blue-500 text-white font-semibold text-base shadow-lg  focus:outline-none focus:ring-2  transition flex items-center justify-center whitespace-nowrap"
+                title="View receipts"
+            >
+                <svg
+                    xmlns="http://www.w3.org/2000/svg"
+                    fill="none"
+                    viewBox="0 0 24 24"
+                    stroke-width="1.5"
+                    stroke="currentColor"
+                    class="size-6"
+                >
+                    <path
+                        stroke-linecap="round"
+                        stroke-linejoin="round"
+                        d="M10.5 19.5 3 12m0 0 7.5-7.5M3 12h18"
+                    />
+                </svg>
+            </button>
+        </A>
+        <div class="flex justify-center items-center gap-4 mb-2 sticky top-0 z-10 bg-white dark:bg-gray-800 py-2 shadow">
+            <span class="font-bold text-base text-gray-900 dark:text-white">
+                "Spending by category"
+            </span>
+        </div>
+        <Transition fallback=move || {
+            view! { <p class="text-center text-gray-400 dark:text-gray-800">"Loading..."</p> }
+        }>
+            <ErrorBoundary fallback=error_list>
+                <div class="flex flex-col gap-4 py-2 px-2">{report_data}</div>
+            </ErrorBoundary>
+        </Transition>
+    }
+}
@@ -0,0 +1,78 @@
+use crate::api::food::get_linked_foods;
+use crate::api::ingredient_catalog::get_ingredient_catalog;
+use crate::app::{FoodList, RouteUrl};
+use crate::components::error_list;
+use crate::components::forms::food_import_form::FoodImportForm;
+use crate::components::models::ingredient_catalog::CatalogEntry;
+use crate::routes::receipt::provide_categories;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+#[component]
+pub fn IngredientCatalogRoute() -> impl IntoView {
+    provide_categories();
+    let foods: FoodList = RwSignal::new(Vec::new());
+    provide_context(foods);
+    let foods_resource = OnceResource::new(get_linked_foods());
+    Effect::watch(
+        move || foods_resource.get(),
+        move |r_foods, _, _| {
+            if let Some(Ok(r_foods)) = r_foods {
+                foods.set(r_foods.clone());
+            }
+        },
+        true,
+    );
+    let catalog_resource = OnceResource::new(get_ingredient_catalog());
+    let catalog_data = move || {
+        catalog_resource.get().map(|catalog| {
+            catalog.map(|catalog| {
+                catalog
+                    .into_iter()
+                    .map(|entry| view! { <CatalogEntry entry /> })
+                    .collect::<Vec<_>>()
+            })
+        })
+    };
+
+    view! {
+        <A href=RouteUrl::MealList.to_string()>
+            <button
+                type="button"
+                class="fixed bottom-4 right-4 z-50 px-4 py-3 rounded-full bg-blue-500 text-white font-semibold text-base shadow-lg  focus:outline-none focus:ring-2  transition flex items-center justify-center whitespace-nowrap"
+                title="View meals"
+            >
+                <svg
+                    xmlns="http://www.w3.org/2000/svg"
+                    fill="none"
+                    viewBox="0 0 24 24"
+                    stroke-width="1.5"
+                    stroke="currentColor"
+                    class="size-6"
+                >
+                    <path
+                        stroke-linecap="round"
+                        stroke-linejoin="round"
+                        d="M10.5 19.5 3 12m0 0 7.5-7.5M3 12h18"
+                    />
+                </svg>
+            </button>
+        </A>
+        <div class="flex justify-center items-center gap-4 mb-2 sticky top-0 z-10 bg-white dark:bg-gray-800 py-2 shadow">
+            <span class="font-bold text-base text-gray-900 dark:text-white">"Ingredients"</span>
+            <A href=RouteUrl::Restrictions.to_string()>
+                <span class="text-blue-600 hover:underline">"Restrictions"</span>
+            </A>
+        </div>
+        <Transition fallback=move || {
+            view! { <p class="text-center text-gray-400 dark:text-gray-800">"Loading..."</p> }
+        }>
+            <ErrorBoundary fallback=error_list>
+                <div class="flex flex-col gap-4 py-2 items-center justify-center">
+                    <FoodImportForm />
+                    {catalog_data}
+                </div>
+            </ErrorBoundary>
+        </Transition>
+    }
+}
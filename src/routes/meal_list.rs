@@ -1,10 +1,12 @@
 use crate::api::meal::get_all_meals_with_ingredients;
 use crate::app::RouteUrl;
 use crate::components::error_list;
+use crate::components::forms::meal_filter::{use_meal_search, MealFilter};
 use crate::components::forms::meal_form::CreateMealForm;
 use crate::components::modal::Modal;
 use crate::components::models::meal::Meal;
-use crate::models::meal::MealWithIngredients;
+use crate::models::meal::{all_tags, filter_meals, MealQuery, MealWithIngredients};
+use chrono::Local;
 use leptos::prelude::*;
 use leptos_router::components::A;
 use leptos_use::math::use_not;
@@ -17,6 +19,8 @@ pub fn MealList() -> impl IntoView {
     let new_meal: RwSignal<Option<MealWithIngredients>> = RwSignal::new(None);
     let meals: RwSignal<Vec<MealWithIngredients>> = RwSignal::new(Vec::new());
     let search_input = RwSignal::new(String::new());
+    let query = RwSignal::new(MealQuery::default());
+    let hits = use_meal_search(search_input);
 
     Effect::watch(
         move || meals_resource.get(),
@@ -38,24 +42,18 @@ pub fn MealList() -> impl IntoView {
     );
     // Close modal when create_completed becomes true
     let meals_data = move || {
-        let meals_vec = meals.get();
-        let search_input = search_input.get();
-        let filtered = if !search_input.is_empty() {
-            let search_input = search_input.to_lowercase();
-            meals_vec
-                .iter()
-                .filter(|m| m.meal.name.to_lowercase().contains(&search_input))
-                .cloned()
-                .collect::<Vec<_>>()
-        } else {
-            meals_vec.clone()
-        };
-        filtered
-            .into_iter()
-            .map(|meal| {
-                view! { <Meal meal=meal /> }
-            })
-            .collect::<Vec<_>>()
+        let today = Local::now().date_naive();
+        filter_meals(
+            &meals.read(),
+            hits.read().as_deref(),
+            &query.read(),
+            today,
+        )
+        .into_iter()
+        .map(|meal| {
+            view! { <Meal meal=meal /> }
+        })
+        .collect::<Vec<_>>()
     };
 
     view! {
@@ -107,6 +105,9 @@ pub fn MealList() -> impl IntoView {
         </button>
         <div class="flex justify-center items-center gap-4 mb-2 sticky top-0 z-10 bg-white dark:bg-gray-800 py-2 shadow">
             <span class="font-bold text-base text-gray-900 dark:text-white">"Meals"</span>
+            <A href=RouteUrl::IngredientCatalog.to_string()>
+                <span class="text-blue-600 hover:underline">"Ingredients"</span>
+            </A>
         </div>
 
         <div class="w-80 mx-auto">
@@ -137,11 +138,15 @@ pub fn MealList() -> impl IntoView {
                 <input
                     type="text"
                     class="block w-full p-4 ps-10 text-sm text-gray-900 border border-gray-300 rounded-lg bg-gray-50 focus:ring-blue-500 focus:border-blue-500 dark:bg-gray-700 dark:border-gray-600 dark:placeholder-gray-400 dark:text-white dark:focus:ring-blue-500 dark:focus:border-blue-500"
-                    placeholder="Search meals..."
+                    placeholder="Search meals, ingredients or tags..."
                     prop:value=search_input
                     bind:value=search_input
                 />
             </div>
+            <MealFilter
+                tags=Signal::derive(move || all_tags(&meals.read()))
+                query=query
+            />
         </div>
 
         <Transition fallback=move || {
@@ -2,3 +2,7 @@ pub mod meal_list;
 pub mod week;
 pub mod shopping_list;
 pub mod receipt;
+pub mod store;
+pub mod category;
+pub mod ingredient_catalog;
+pub mod restriction;
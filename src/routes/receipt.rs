@@ -1,22 +1,47 @@
 use crate::{
-    api::receipt::get_all_receipts_with_items,
-    app::RouteUrl,
+    api::{category::get_categories, receipt::search_receipts},
+    app::{CategoryList, RouteUrl},
     components::{
         error_list,
         forms::{receipt_form::ReceiptForm, receipt_upload_form::ReceiptUpload},
         models::receipt::Receipt,
     },
-    models::receipt::{ReceiptForm, ReceiptItemForm, ReceiptWithItems},
+    models::{
+        member::Member,
+        ocr_job::ScannedItem,
+        receipt::{ReceiptForm, ReceiptItemForm, ReceiptQuery, ReceiptWithItems},
+        store::StoreForm,
+    },
 };
 use leptos::prelude::*;
 use leptos_router::components::A;
+use leptos_router::hooks::use_query_map;
+
+/// Loads the categories for the item pickers in `Receipt`.
+pub fn provide_categories() {
+    let categories: CategoryList = RwSignal::new(Vec::new());
+    provide_context(categories);
+    let categories_resource = OnceResource::new(get_categories());
+    Effect::watch(
+        move || categories_resource.get(),
+        move |r_categories, _, _| {
+            if let Some(Ok(r_categories)) = r_categories {
+                categories.set(r_categories.clone());
+            }
+        },
+        true,
+    );
+}
 
 #[component]
 pub fn ReceiptCreateRoute() -> impl IntoView {
+    provide_categories();
     let receipt: RwSignal<Option<ReceiptWithItems>> = RwSignal::new(None);
     let receipt_editing: RwSignal<bool> = RwSignal::new(false);
     let receipt_form: RwSignal<Option<ReceiptForm>> = RwSignal::new(None);
     let receipt_items_forms: RwSignal<Option<Vec<ReceiptItemForm>>> = RwSignal::new(None);
+    let item_scans: RwSignal<Vec<ScannedItem>> = RwSignal::new(Vec::new());
+    let new_store: RwSignal<Option<StoreForm>> = RwSignal::new(None);
     view! {
         <A href=RouteUrl::ReceiptList.to_string()>
             <button
@@ -74,6 +99,8 @@ pub fn ReceiptCreateRoute() -> impl IntoView {
                         <ReceiptUpload
                             receipt_form
                             receipt_items_forms
+                            item_scans
+                            new_store
                             receipt_editing=receipt_editing.write_only()
                         />
                     </div>
@@ -95,6 +122,8 @@ pub fn ReceiptCreateRoute() -> impl IntoView {
                         receipt_editing=receipt_editing.write_only()
                         receipt_form=receipt_form.read().as_ref().unwrap().clone()
                         receipt_items_forms=receipt_items_forms.read().as_ref().unwrap().clone()
+                        item_scans=item_scans.get()
+                        new_store=new_store.get()
                     />
                 }
             }}
@@ -111,28 +140,87 @@ pub fn ReceiptCreateRoute() -> impl IntoView {
     }
 }
 
+/// `None` for blank input, so clearing a field removes the filter.
+fn non_empty(value: String) -> Option<String> {
+    (!value.trim().is_empty()).then_some(value)
+}
+
 #[component]
 pub fn ReceiptListRoute() -> impl IntoView {
-    let receipt_resource = OnceResource::new(get_all_receipts_with_items());
-    let receipts: RwSignal<Vec<ReceiptWithItems>> = RwSignal::new(Vec::new());
-
+    provide_categories();
+    // Store pages link here with `?store_id=`
+    let params = use_query_map();
+    let store_id = move || params.read().get("store_id").and_then(|id| id.parse().ok());
+    let query = RwSignal::new(ReceiptQuery {
+        store_id: store_id(),
+        page: 1,
+        ..Default::default()
+    });
+    let receipts_resource = Resource::new(move || query.get(), search_receipts);
+    // Any change to the filters starts over at the first page
+    let set_filter = move |set: &dyn Fn(&mut ReceiptQuery)| {
+        query.update(|q| {
+            set(q);
+            q.page = 1;
+        })
+    };
     Effect::watch(
-        move || receipt_resource.get(),
-        move |r_receipts, _, _| {
-            if let Some(Ok(r_receipts)) = r_receipts {
-                receipts.set(r_receipts.clone());
-            }
+        store_id,
+        move |store_id, _, _| {
+            query.update(|q| {
+                q.store_id = *store_id;
+                q.page = 1;
+            })
         },
-        true,
+        false,
     );
+    let input_class = "px-2 py-1 text-sm border border-gray-300 rounded focus:outline-none focus:ring-1 focus:ring-blue-500 w-full";
+
     let receipts_data = move || {
-        receipts
+        receipts_resource
             .get()
-            .into_iter()
-            .map(|receipt_with_items| {
-                view! { <Receipt receipt_with_items /> }
+            .map(|page| {
+                page.map(|page| {
+                    let page_count = page.page_count();
+                    view! {
+                        <div class="flex flex-col gap-4 items-center justify-center">
+                            {page
+                                .receipts
+                                .into_iter()
+                                .map(|receipt_with_items| {
+                                    view! { <Receipt receipt_with_items /> }
+                                })
+                                .collect::<Vec<_>>()}
+                        </div>
+                        <div class="flex items-center justify-between w-80 mx-auto py-2 text-sm text-gray-700">
+                            <button
+                                type="button"
+                                class="px-3 py-1 rounded bg-blue-500 text-white disabled:bg-gray-300"
+                                disabled=page.page <= 1
+                                on:click=move |_| query.update(|q| q.page -= 1)
+                            >
+                                "Previous"
+                            </button>
+                            <span>
+                                {format!(
+                                    "Page {} of {} ({} receipts)",
+                                    page.page,
+                                    page_count,
+                                    page.total_count,
+                                )}
+                            </span>
+                            <button
+                                type="button"
+                                class="px-3 py-1 rounded bg-blue-500 text-white disabled:bg-gray-300"
+                                disabled=page.page >= page_count
+                                on:click=move |_| query.update(|q| q.page += 1)
+                            >
+                                "Next"
+                            </button>
+                        </div>
+                    }
+                })
             })
-            .collect::<Vec<_>>()
     };
 
     view! {
@@ -159,13 +247,92 @@ pub fn ReceiptListRoute() -> impl IntoView {
 
             </button>
         </A>
+        <div class="flex justify-between items-center w-80 mx-auto pt-2 text-sm">
+            {move || {
+                query
+                    .read()
+                    .store_id
+                    .map(|_| {
+                        view! {
+                            <A href=RouteUrl::ReceiptList.to_string()>
+                                <span class="text-blue-600 hover:underline">
+                                    "One store, show all"
+                                </span>
+                            </A>
+                        }
+                    })
+            }}
+            <span class="flex gap-3">
+                <A href=RouteUrl::CategoryReport.to_string()>
+                    <span class="text-blue-600 hover:underline">"Categories"</span>
+                </A>
+                <A href=RouteUrl::StoreList.to_string()>
+                    <span class="text-blue-600 hover:underline">"Stores"</span>
+                </A>
+            </span>
+        </div>
+        <div class="grid grid-cols-2 gap-2 w-80 mx-auto py-2">
+            <input
+                type="text"
+                placeholder="Store"
+                class=input_class
+                on:change:target=move |ev| set_filter(&|q| q.store = non_empty(ev.target().value()))
+            />
+            <input
+                type="text"
+                placeholder="Item"
+                class=input_class
+                on:change:target=move |ev| set_filter(&|q| q.item = non_empty(ev.target().value()))
+            />
+            <input
+                type="date"
+                title="From"
+                class=input_class
+                on:change:target=move |ev| set_filter(&|q| q.from = ev.target().value().parse().ok())
+            />
+            <input
+                type="date"
+                title="To"
+                class=input_class
+                on:change:target=move |ev| set_filter(&|q| q.to = ev.target().value().parse().ok())
+            />
+            <input
+                type="text"
+                inputmode="decimal"
+                placeholder="Min kr"
+                class=input_class
+                on:change:target=move |ev| {
+                    set_filter(&|q| q.min_total = ev.target().value().parse().ok())
+                }
+            />
+            <input
+                type="text"
+                inputmode="decimal"
+                placeholder="Max kr"
+                class=input_class
+                on:change:target=move |ev| {
+                    set_filter(&|q| q.max_total = ev.target().value().parse().ok())
+                }
+            />
+            <select
+                class=format!("{input_class} col-span-2")
+                on:change:target=move |ev| {
+                    let value = ev.target().value();
+                    set_filter(&|q| q.member = Member::ALL.into_iter().find(|m| m.name() == value))
+                }
+            >
+                <option value="">"Everyone"</option>
+                {Member::ALL
+                    .into_iter()
+                    .map(|member| view! { <option value=member.name()>{member.name()}</option> })
+                    .collect::<Vec<_>>()}
+            </select>
+        </div>
         <Transition fallback=move || {
             view! { <p class="text-center text-gray-400 dark:text-gray-800">"Loading..."</p> }
         }>
             <ErrorBoundary fallback=error_list>
-                <div class="flex flex-col gap-4 py-2 items-center justify-center">
-                    {move || receipts_data()}
-                </div>
+                <div class="py-2">{receipts_data}</div>
             </ErrorBoundary>
         </Transition>
     }
@@ -0,0 +1,65 @@
+use crate::api::restriction::get_member_restrictions;
+use crate::app::{RestrictionList, RouteUrl};
+use crate::components::forms::restriction_form::MemberRestrictionsForm;
+use crate::models::member::Member;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+/// Loads the restrictions for the allergen warnings in `Day` and `DayForm`.
+pub fn provide_restrictions() -> RestrictionList {
+    let restrictions: RestrictionList = RwSignal::new(Vec::new());
+    provide_context(restrictions);
+    let restrictions_resource = OnceResource::new(get_member_restrictions());
+    Effect::watch(
+        move || restrictions_resource.get(),
+        move |r_restrictions, _, _| {
+            if let Some(Ok(r_restrictions)) = r_restrictions {
+                restrictions.set(r_restrictions.clone());
+            }
+        },
+        true,
+    );
+    restrictions
+}
+
+#[component]
+pub fn RestrictionsRoute() -> impl IntoView {
+    let restrictions = provide_restrictions();
+
+    view! {
+        <A href=RouteUrl::IngredientCatalog.to_string()>
+            <button
+                type="button"
+                class="fixed bottom-4 right-4 z-50 px-4 py-3 rounded-full bg-blue-500 text-white font-semibold text-base shadow-lg  focus:outline-none focus:ring-2  transition flex items-center justify-center whitespace-nowrap"
+                title="View ingredients"
+            >
+                <svg
+                    xmlns="http://www.w3.org/2000/svg"
+                    fill="none"
+                    viewBox="0 0 24 24"
+                    stroke-width="1.5"
+                    stroke="currentColor"
+                    class="size-6"
+                >
+                    <path
+                        stroke-linecap="round"
+                        stroke-linejoin="round"
+                        d="M10.5 19.5 3 12m0 0 7.5-7.5M3 12h18"
+                    />
+                </svg>
+            </button>
+        </A>
+        <div class="flex justify-center items-center gap-4 mb-2 sticky top-0 z-10 bg-white dark:bg-gray-800 py-2 shadow">
+            <span class="font-bold text-base text-gray-900 dark:text-white">"Restrictions"</span>
+        </div>
+        <p class="text-center text-sm text-gray-500 dark:text-gray-400 px-4">
+            "Meals with ingredients tagged with these allergens get a warning when the member is eating."
+        </p>
+        <div class="flex flex-col gap-4 py-2 items-center justify-center">
+            {Member::ALL
+                .into_iter()
+                .map(|member| view! { <MemberRestrictionsForm member restrictions /> })
+                .collect::<Vec<_>>()}
+        </div>
+    }
+}
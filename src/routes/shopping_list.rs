@@ -81,14 +81,7 @@ pub fn ShoppingList() -> impl IntoView {
         move |days, _, _| {
             let num_days = days
                 .iter()
-                .filter(|day| {
-                    if let Some((_, ingredients)) = day.meal.as_ref() {
-                        if ingredients.iter().any(|i| !i.bought) {
-                            return true;
-                        }
-                    }
-                    false
-                })
+                .filter(|day| day.ingredients().any(|i| !i.bought))
                 .count();
             week_ingredients_count.set(num_days)
         },
@@ -122,28 +115,18 @@ pub fn ShoppingList() -> impl IntoView {
         //     val.map(|days|{
         days.get()
             .iter()
-            .filter(|day| {
-                if let Some((_, ingredients)) = day.meal.as_ref() {
-                    if ingredients.iter().any(|i| !i.bought) {
-                        return true;
-                    }
-                }
-                false
-            })
+            .filter(|day| day.ingredients().any(|i| !i.bought))
             .map(|day| {
-                let (meal, ingredients) = day.meal.as_ref().unwrap();
-                // if let Some((meal, ingredients)) = day.meal.as_ref() {
-                //     if ingredients.iter().any(|i|i.bought==false){
-                let header = format!("{} - {}", day.day.date.weekday(), meal.name.clone());
-                // let (meal, ingredients) = day.meal.as_ref().unwrap().clone();
+                // The ingredients of all the dishes are bought together
+                let header = format!("{} - {}", day.day.date.weekday(), day.meal_names());
                 view! {
                     <div class="mb-6 p-4 rounded-lg shadow bg-white dark:bg-gray-800">
                         <h2 class="text-lg font-bold text-gray-800 dark:text-gray-100 mb-1">
                             {header}
                         </h2>
                         <ul class="flex justify-center items-center flex-wrap gap-2">
-                            {ingredients
-                                .iter()
+                            {day
+                                .ingredients()
                                 .map(|ingredient| {
                                     view! {
                                         <li>
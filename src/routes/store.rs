@@ -0,0 +1,58 @@
+use crate::api::store::get_stores_with_spending;
+use crate::app::RouteUrl;
+use crate::components::error_list;
+use crate::components::models::store::Store;
+use leptos::prelude::*;
+use leptos_router::components::A;
+
+#[component]
+pub fn StoreListRoute() -> impl IntoView {
+    let stores_resource = OnceResource::new(get_stores_with_spending());
+    let stores_data = move || {
+        stores_resource.get().map(|stores| {
+            stores.map(|stores| {
+                stores
+                    .into_iter()
+                    .map(|spending| view! { <Store spending /> })
+                    .collect::<Vec<_>>()
+            })
+        })
+    };
+
+    view! {
+        <A href=RouteUrl::ReceiptList.to_string()>
+            <button
+                type="button"
+                class="fixed bottom-4 right-4 z-50 px-4 py-3 rounded-full bg-blue-500 text-white font-semibold text-base shadow-lg  focus:outline-none focus:ring-2  transition flex items-center justify-center whitespace-nowrap"
+                title="View receipts"
+            >
+                <svg
+                    xmlns="http://www.w3.org/2000/svg"
+                    fill="none"
+                    viewBox="0 0 24 24"
+                    stroke-width="1.5"
+                    stroke="currentColor"
+                    class="size-6"
+                >
+                    <path
+                        stroke-linecap="round"
+                        stroke-linejoin="round"
+                        d="M10.5 19.5 3 12m0 0 7.5-7.5M3 12h18"
+                    />
+                </svg>
+            </button>
+        </A>
+        <div class="flex justify-center items-center gap-4 mb-2 sticky top-0 z-10 bg-white dark:bg-gray-800 py-2 shadow">
+            <span class="font-bold text-base text-gray-900 dark:text-white">"Stores"</span>
+        </div>
+        <Transition fallback=move || {
+            view! { <p class="text-center text-gray-400 dark:text-gray-800">"Loading..."</p> }
+        }>
+            <ErrorBoundary fallback=error_list>
+                <div class="flex flex-col gap-4 py-2 items-center justify-center">
+                    {stores_data}
+                </div>
+            </ErrorBoundary>
+        </Transition>
+    }
+}
@@ -1,8 +1,10 @@
 use crate::components::error_list;
 use crate::components::models::day::Day;
+use crate::routes::restriction::provide_restrictions;
 use crate::{
     api::week::{days_for_week, Week},
     app::RouteUrl,
+    models::days_ingredients::nutrition_per_person,
 };
 use leptos::prelude::*;
 use leptos_router::{components::A, hooks::use_query};
@@ -17,6 +19,7 @@ pub struct WeekQuery {
 }
 #[component]
 pub fn Week() -> impl IntoView {
+    provide_restrictions();
     let query = use_query::<WeekQuery>();
 
     let (week, set_week) = signal(Week::current());
@@ -26,6 +29,17 @@ pub fn Week() -> impl IntoView {
         }
     });
     let days_resource = Resource::new(move || week.get(), days_for_week);
+    let nutrition_data = move || {
+        days_resource.get().and_then(Result::ok).and_then(|days| {
+            nutrition_per_person(&days).map(|nutrition| {
+                view! {
+                    <p class="text-center text-sm text-gray-500 dark:text-gray-400">
+                        {format!("This week per person: {nutrition}")}
+                    </p>
+                }
+            })
+        })
+    };
     let days_data = move || {
         days_resource.get().map(|val| {
             val.map(|days| {
@@ -155,6 +169,7 @@ pub fn Week() -> impl IntoView {
                 <Transition fallback=move || {
                     view! { <p>"Loading..."</p> }
                 }>
+                    {nutrition_data}
                     <ErrorBoundary fallback=error_list>{move || days_data}</ErrorBoundary>
                 </Transition>
 
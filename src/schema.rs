@@ -1,15 +1,39 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    categories (id) {
+        id -> Integer,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    category_rules (id) {
+        id -> Integer,
+        keyword -> Text,
+        category_id -> Integer,
+        manual -> Bool,
+    }
+}
+
+diesel::table! {
+    day_meals (day_id, meal_id) {
+        day_id -> Integer,
+        meal_id -> Integer,
+        course -> Text,
+    }
+}
+
 diesel::table! {
     days (id) {
         id -> Integer,
         date -> Date,
-        meal_id -> Nullable<Integer>,
         week -> Integer,
         year -> Integer,
         anders_attend -> Bool,
         ac_attend -> Bool,
         andreas_attend -> Bool,
+        leftovers_from_day_id -> Nullable<Integer>,
     }
 }
 
@@ -31,11 +55,64 @@ diesel::table! {
 }
 
 diesel::table! {
-    ingredients (id) {
+    foods (id) {
         id -> Integer,
+        source_id -> Text,
         name -> Text,
+        kcal -> Double,
+        protein -> Double,
+        fat -> Double,
+        carbs -> Double,
+    }
+}
+
+diesel::table! {
+    image_download_failures (url) {
+        url -> Text,
+        error -> Text,
+        failed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    ingredient_catalog (id) {
+        id -> Integer,
+        name -> Text,
+        key -> Text,
+        aliases -> Text,
+        unit -> Nullable<Text>,
+        category_id -> Nullable<Integer>,
+        allergens -> Text,
+        food_id -> Nullable<Integer>,
+        grams_per_unit -> Nullable<Double>,
+    }
+}
+
+diesel::table! {
+    ingredients (id) {
+        id -> Integer,
         amount -> Integer,
         meal_id -> Integer,
+        catalog_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    meal_ratings (id) {
+        id -> Integer,
+        member -> Text,
+        meal_id -> Integer,
+        day_id -> Integer,
+        score -> Integer,
+        comment -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    meal_tags (id) {
+        id -> Integer,
+        meal_id -> Integer,
+        name -> Text,
     }
 }
 
@@ -45,6 +122,42 @@ diesel::table! {
         name -> Text,
         image -> Text,
         recipie_url -> Nullable<Text>,
+        servings -> Integer,
+    }
+}
+
+diesel::table! {
+    member_restrictions (member, allergen) {
+        member -> Text,
+        allergen -> Text,
+    }
+}
+
+diesel::table! {
+    ocr_jobs (id) {
+        id -> Integer,
+        status -> Text,
+        file_path -> Text,
+        result -> Nullable<Text>,
+        error -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    product_aliases (id) {
+        id -> Integer,
+        raw_name -> Text,
+        product_id -> Integer,
+    }
+}
+
+diesel::table! {
+    products (id) {
+        id -> Integer,
+        name -> Text,
+        catalog_id -> Nullable<Integer>,
     }
 }
 
@@ -60,10 +173,14 @@ diesel::table! {
         id -> Integer,
         receipt_id -> Integer,
         name -> Text,
-        price -> Float,
-        anders_pay -> Bool,
-        andreas_pay -> Bool,
-        ac_pay -> Bool,
+        price -> BigInt,
+        anders_share -> Integer,
+        andreas_share -> Integer,
+        ac_share -> Integer,
+        kind -> Text,
+        category_id -> Nullable<Integer>,
+        raw_name -> Nullable<Text>,
+        product_id -> Nullable<Integer>,
     }
 }
 
@@ -72,24 +189,67 @@ diesel::table! {
         id -> Integer,
         store -> Text,
         datetime -> Timestamp,
+        anders_share -> Integer,
+        andreas_share -> Integer,
+        ac_share -> Integer,
+        split_mode -> Text,
+        image_hash -> Nullable<BigInt>,
+        store_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    stores (id) {
+        id -> Integer,
+        chain -> Text,
+        name -> Text,
+        address -> Nullable<Text>,
+        org_number -> Nullable<Text>,
+        aisles -> Text,
     }
 }
 
-diesel::joinable!(days -> meals (meal_id));
+diesel::joinable!(category_rules -> categories (category_id));
+diesel::joinable!(day_meals -> days (day_id));
+diesel::joinable!(day_meals -> meals (meal_id));
 diesel::joinable!(days_ingredients -> days (day_id));
 diesel::joinable!(days_ingredients -> ingredients (ingredient_id));
+diesel::joinable!(ingredient_catalog -> categories (category_id));
+diesel::joinable!(ingredient_catalog -> foods (food_id));
+diesel::joinable!(ingredients -> ingredient_catalog (catalog_id));
 diesel::joinable!(ingredients -> meals (meal_id));
+diesel::joinable!(meal_ratings -> days (day_id));
+diesel::joinable!(meal_ratings -> meals (meal_id));
+diesel::joinable!(meal_tags -> meals (meal_id));
+diesel::joinable!(product_aliases -> products (product_id));
+diesel::joinable!(products -> ingredient_catalog (catalog_id));
 diesel::joinable!(receipt_days -> days (day_id));
 diesel::joinable!(receipt_days -> receipts (receipt_id));
+diesel::joinable!(receipt_items -> categories (category_id));
+diesel::joinable!(receipt_items -> products (product_id));
 diesel::joinable!(receipt_items -> receipts (receipt_id));
+diesel::joinable!(receipts -> stores (store_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    categories,
+    category_rules,
+    day_meals,
     days,
     days_ingredients,
     extra_items,
+    foods,
+    image_download_failures,
+    ingredient_catalog,
     ingredients,
+    meal_ratings,
+    meal_tags,
     meals,
+    member_restrictions,
+    ocr_jobs,
+    product_aliases,
+    products,
     receipt_days,
     receipt_items,
     receipts,
+    stores,
 );
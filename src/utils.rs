@@ -1 +1,71 @@
-pub type NotificationCount = u32;
\ No newline at end of file
+pub type NotificationCount = u32;
+
+/// Rounds the `exact` parts down to whole units and hands out what is left of `total`
+/// one unit at a time to the parts with the largest remainders, so the result always
+/// adds up to exactly `total`.
+pub fn apportion(total: i64, exact: &[f64]) -> Vec<i64> {
+    let mut parts: Vec<i64> = exact.iter().map(|e| e.floor() as i64).collect();
+    let mut left = total - parts.iter().sum::<i64>();
+
+    let mut by_remainder: Vec<usize> = (0..exact.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        let ra = exact[a] - exact[a].floor();
+        let rb = exact[b] - exact[b].floor();
+        rb.total_cmp(&ra).then(a.cmp(&b))
+    });
+    for i in by_remainder.into_iter().cycle() {
+        match left.signum() {
+            0 => break,
+            1 => parts[i] += 1,
+            _ => parts[i] -= 1,
+        }
+        left -= left.signum();
+    }
+    parts
+}
+
+/// Splits `total` into whole units proportionally to `weights`. If every weight is
+/// zero the total is split evenly.
+pub fn split(total: i64, weights: &[i64]) -> Vec<i64> {
+    if weights.is_empty() {
+        return vec![];
+    }
+    let sum: i64 = weights.iter().sum();
+    let exact: Vec<f64> = if sum == 0 {
+        weights
+            .iter()
+            .map(|_| total as f64 / weights.len() as f64)
+            .collect()
+    } else {
+        weights
+            .iter()
+            .map(|w| total as f64 * *w as f64 / sum as f64)
+            .collect()
+    };
+    apportion(total, &exact)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_adds_up() {
+        assert_eq!(split(100, &[1, 1, 1]), vec![34, 33, 33]);
+        assert_eq!(split(1000, &[2, 1, 1]), vec![500, 250, 250]);
+        assert_eq!(split(-100, &[1, 1, 1]), vec![-33, -33, -34]);
+        assert_eq!(split(7, &[0, 0, 0]), vec![3, 2, 2]);
+        assert_eq!(split(5, &[0, 1, 0]), vec![0, 5, 0]);
+        for total in [-1001, -1, 0, 1, 2, 999, 12345] {
+            for weights in [[1, 1, 1], [3, 2, 1], [0, 7, 3], [5, 0, 0]] {
+                assert_eq!(split(total, &weights).iter().sum::<i64>(), total);
+            }
+        }
+    }
+
+    #[test]
+    fn test_apportion_uses_largest_remainder() {
+        assert_eq!(apportion(10, &[3.3, 3.3, 3.4]), vec![3, 3, 4]);
+        assert_eq!(apportion(3, &[0.9, 0.9, 1.2]), vec![1, 1, 1]);
+    }
+}
@@ -1,5 +1,14 @@
+use crate::models::ocr_job::OcrJobUpdate;
 use serde::{Deserialize, Serialize};
 
+/// Every message broadcast over the WebSocket, tagged with its kind.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WsMessage {
+    IngredientUpdate(IngredientUpdate),
+    OcrJobUpdate(Box<OcrJobUpdate>),
+}
+
 /// Broadcast message emitted whenever a day-ingredient's bought state changes.
 /// Sent over WebSocket to all connected clients.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -11,7 +20,7 @@ pub struct IngredientUpdate {
 
 #[cfg(feature = "ssr")]
 pub mod server {
-    use super::IngredientUpdate;
+    use super::WsMessage;
     use axum::extract::ws::{Message, WebSocket};
     use axum::extract::{Extension, WebSocketUpgrade};
     use axum::response::IntoResponse;
@@ -19,7 +28,7 @@ pub mod server {
     use tokio::sync::broadcast;
 
     /// Clone-able sender handle. Each clone sends to the same channel.
-    pub type BroadcastTx = broadcast::Sender<IngredientUpdate>;
+    pub type BroadcastTx = broadcast::Sender<WsMessage>;
 
     pub fn create_channel() -> BroadcastTx {
         broadcast::channel(256).0